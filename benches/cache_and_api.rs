@@ -1,6 +1,6 @@
+use std::time::Instant;
 use criterion::{criterion_group, criterion_main, Criterion};
-use serde_json;
-use speedtest_statuspage::{clear_last_result_for_test, get_last_result, set_last_result_for_test, SpeedTestResult};
+use speedtest_statuspage::{clear_last_result_for_test, get_last_result, set_last_result_at, set_last_result_for_test, SpeedTestResult};
 
 fn dummy_result() -> SpeedTestResult {
     SpeedTestResult {
@@ -15,6 +15,7 @@ fn dummy_result() -> SpeedTestResult {
         server: Default::default(),
         share: None,
         timestamp: "2025-08-07T12:34:56Z".into(),
+        ..Default::default()
     }
 }
 
@@ -29,6 +30,18 @@ fn bench_set_get_clear(c: &mut Criterion) {
     });
 }
 
+fn bench_set_get_clear_fixed_instant(c: &mut Criterion) {
+    let res = dummy_result();
+    let at = Instant::now();
+    c.bench_function("set_get_clear_fixed_instant", |b| {
+        b.iter(|| {
+            set_last_result_at(res.clone(), at);
+            let _ = get_last_result();
+            clear_last_result_for_test();
+        })
+    });
+}
+
 fn bench_serialize(c: &mut Criterion) {
     let res = dummy_result();
     c.bench_function("serialize_speedtest_result", |b| {
@@ -36,6 +49,13 @@ fn bench_serialize(c: &mut Criterion) {
     });
 }
 
+fn bench_serialize_msgpack(c: &mut Criterion) {
+    let res = dummy_result();
+    c.bench_function("serialize_speedtest_result_msgpack", |b| {
+        b.iter(|| rmp_serde::to_vec_named(&res).unwrap())
+    });
+}
+
 fn bench_speed_endpoint_cached(c: &mut Criterion) {
     set_last_result_for_test(dummy_result());
     c.bench_function("speed_endpoint_cached", |b| {
@@ -45,10 +65,31 @@ fn bench_speed_endpoint_cached(c: &mut Criterion) {
     });
 }
 
+fn bench_concurrent_reads(c: &mut Criterion) {
+    set_last_result_for_test(dummy_result());
+    c.bench_function("concurrent_reads_8_threads", |b| {
+        b.iter(|| {
+            std::thread::scope(|scope| {
+                for _ in 0..8 {
+                    scope.spawn(|| {
+                        for _ in 0..10_000 {
+                            let _ = get_last_result();
+                        }
+                    });
+                }
+            })
+        })
+    });
+    clear_last_result_for_test();
+}
+
 criterion_group!(
     benches,
     bench_set_get_clear,
+    bench_set_get_clear_fixed_instant,
     bench_serialize,
-    bench_speed_endpoint_cached
+    bench_serialize_msgpack,
+    bench_speed_endpoint_cached,
+    bench_concurrent_reads
 );
 criterion_main!(benches);
@@ -0,0 +1,177 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Restricting the endpoints to a list of client networks.
+//!
+//! `ALLOW_CIDRS` holds a comma-separated list of IPv4 and IPv6 networks. When set,
+//! [`enforce_allow_list`] rejects requests from any other client address with
+//! HTTP 403 Forbidden. When unset or empty, every client is allowed.
+
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::http::header;
+use actix_web::{web, Error, HttpRequest, HttpResponse};
+use crate::config;
+
+/// An IP network such as `192.168.1.0/24` or `fd00::/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Returns `true` if `ip` lies within this network.
+    ///
+    /// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) are matched as IPv4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::access::Cidr;
+    ///
+    /// let lan: Cidr = "192.168.1.0/24".parse().unwrap();
+    /// assert!(lan.contains("192.168.1.42".parse().unwrap()));
+    /// assert!(!lan.contains("192.168.2.1".parse().unwrap()));
+    /// ```
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - u32::from(self.prefix)).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - u32::from(self.prefix)).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    /// Parses `address/prefix`; a bare address is a single-host network.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (address, prefix) = match s.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = address.parse().map_err(|_| format!("invalid CIDR '{}'", s))?;
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max_prefix)
+                .ok_or_else(|| format!("invalid prefix length in CIDR '{}'", s))?,
+            None => max_prefix,
+        };
+        Ok(Cidr { network, prefix })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// The networks allowed to reach the endpoints. An empty list allows everyone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowList(pub Vec<Cidr>);
+
+impl AllowList {
+    /// Parses a comma-separated list of CIDRs, ignoring empty entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first entry which is not a valid CIDR.
+    pub fn parse(list: &str) -> Result<Self, String> {
+        list.split(',')
+            .filter(|entry| !entry.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .map(AllowList)
+    }
+
+    /// Reads `ALLOW_CIDRS`; unset means allow all.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the variable contains an invalid CIDR.
+    pub fn from_env() -> Result<Self, String> {
+        config::ALLOW_CIDRS.raw().map_or(Ok(AllowList::default()), |list| AllowList::parse(&list))
+    }
+
+    /// Returns `true` if a client at `ip` may access the endpoints.
+    ///
+    /// An empty list allows every client; otherwise clients without a known
+    /// address are rejected.
+    pub fn allows(&self, ip: Option<IpAddr>) -> bool {
+        if self.0.is_empty() {
+            return true;
+        }
+        ip.is_some_and(|ip| self.0.iter().any(|cidr| cidr.contains(ip)))
+    }
+}
+
+/// Middleware rejecting clients outside the [`AllowList`] registered as app data.
+///
+/// Use with [`actix_web::middleware::from_fn`]. Without a registered allow list
+/// every request is passed through.
+pub async fn enforce_allow_list<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let allowed = match req.app_data::<web::Data<AllowList>>() {
+        Some(list) => list.allows(req.peer_addr().map(|addr| addr.ip())),
+        None => true,
+    };
+    if allowed {
+        next.call(req).await.map(ServiceResponse::map_into_left_body)
+    } else {
+        Ok(req.into_response(HttpResponse::Forbidden().body("Forbidden")).map_into_right_body())
+    }
+}
+
+/// Checks that `req` carries `Authorization: Bearer <token>`.
+///
+/// Returns `None` when it does, or the HTTP 401 Unauthorized response to send.
+pub fn require_bearer(req: &HttpRequest, token: &str) -> Option<HttpResponse> {
+    let presented = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if presented.is_some_and(|presented| constant_time_eq(presented.as_bytes(), token.as_bytes())) {
+        None
+    } else {
+        Some(HttpResponse::Unauthorized().insert_header((header::WWW_AUTHENTICATE, "Bearer")).finish())
+    }
+}
+
+/// Compares `a` and `b` in time independent of where they first differ, so a
+/// token cannot be guessed byte by byte from response times.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
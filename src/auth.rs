@@ -0,0 +1,91 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Bearer-token authentication for every endpoint.
+//!
+//! When `API_TOKEN` is set, [`require_api_token`] rejects requests which do not
+//! carry `Authorization: Bearer <API_TOKEN>` with HTTP 401 Unauthorized. When unset
+//! or empty, every request is let through.
+//!
+//! Routes guarded by their own token (`POST /run` with `RUN_TOKEN`, `POST
+//! /maintenance` with `MAINTENANCE_TOKEN`, `/debug/env` with `DEBUG_TOKEN`,
+//! `/compare/backends` with `COMPARE_TOKEN`, `POST /history/clear` with
+//! `HISTORY_CLEAR_TOKEN`) are exempt while that token is set, so each can be given
+//! a different value.
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use crate::{access, config};
+
+/// Routes which check their own bearer token, with the variable holding it.
+const OWN_TOKEN_ROUTES: &[(&str, config::EnvVar)] = &[
+    ("/run", config::RUN_TOKEN),
+    ("/maintenance", config::MAINTENANCE_TOKEN),
+    ("/debug/env", config::DEBUG_TOKEN),
+    ("/compare/backends", config::COMPARE_TOKEN),
+    ("/history/clear", config::HISTORY_CLEAR_TOKEN),
+];
+
+/// The bearer token requests must present, if any, and the routes exempt from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiToken {
+    token: Option<String>,
+    exempt: Vec<String>,
+}
+
+impl ApiToken {
+    /// Requires `token`; an empty token requires nothing.
+    pub fn new(token: &str) -> Self {
+        ApiToken { token: Some(token.to_string()).filter(|token| !token.is_empty()), exempt: Vec::new() }
+    }
+
+    /// Exempts requests to `path`, a route which checks its own token instead.
+    pub fn exempt(mut self, path: &str) -> Self {
+        self.exempt.push(path.to_string());
+        self
+    }
+
+    /// Reads `API_TOKEN`, exempting every route whose own token is set; unset
+    /// means no token is required.
+    pub fn from_env() -> Self {
+        let token = config::API_TOKEN.raw().map_or_else(ApiToken::default, |token| ApiToken::new(&token));
+        OWN_TOKEN_ROUTES
+            .iter()
+            .filter(|(_, var)| var.raw().is_some_and(|value| !value.is_empty()))
+            .fold(token, |token, (path, _)| token.exempt(path))
+    }
+}
+
+/// Middleware rejecting requests without the [`ApiToken`] registered as app data.
+///
+/// Use with [`actix_web::middleware::from_fn`]. Without a registered token, or
+/// with an empty one, every request is passed through, as are requests to exempt
+/// routes.
+pub async fn require_api_token<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let token = req
+        .app_data::<web::Data<ApiToken>>()
+        .filter(|token| !token.exempt.iter().any(|path| path == req.path()))
+        .and_then(|token| token.token.clone());
+    if let Some(unauthorized) = token.and_then(|token| access::require_bearer(req.request(), &token)) {
+        return Ok(req.into_response(unauthorized).map_into_right_body());
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
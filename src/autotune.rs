@@ -0,0 +1,238 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Automatic selection of the best speedtest server.
+//!
+//! When `AUTO_TUNE=true` and no server is pinned via `SPEEDTEST_SERVER_IDS`, the
+//! closest servers from `speedtest-cli --list` are probed with a ping-only run
+//! (`--no-download --no-upload`). The lowest-latency server is recorded in
+//! `AUTO_TUNE_STATE_FILE` and pinned for subsequent runs until
+//! `AUTO_TUNE_REFRESH_HOURS` have elapsed. Only the legacy backend supports this.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tracing::{error, info, warn};
+use crate::{config, Backend, RunnerConfig, SpeedtestError, SpeedtestRunner};
+
+/// The persisted outcome of an auto-tuning pass.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TuneState {
+    /// The id of the server chosen as the best performing.
+    pub server_id: String,
+
+    /// Unix timestamp in seconds at which the server was chosen.
+    pub tuned_at: u64,
+}
+
+impl TuneState {
+    /// Returns `true` while the state is younger than `refresh` at unix time `now`.
+    pub fn is_fresh(&self, now: u64, refresh: Duration) -> bool {
+        now.saturating_sub(self.tuned_at) < refresh.as_secs()
+    }
+}
+
+/// Picks the server with the lowest probed latency.
+///
+/// Probes without a latency, or with a non-finite or non-positive one, are ignored.
+/// Ties are resolved in favour of the earlier (closer) candidate.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::autotune::pick_best_server;
+/// let probes = vec![
+///     ("1".to_string(), Some(25.0)),
+///     ("2".to_string(), None),
+///     ("3".to_string(), Some(11.5)),
+/// ];
+/// assert_eq!(pick_best_server(&probes), Some("3".to_string()));
+/// ```
+pub fn pick_best_server(probes: &[(String, Option<f64>)]) -> Option<String> {
+    probes
+        .iter()
+        .filter_map(|(id, latency)| latency.filter(|l| l.is_finite() && *l > 0.0).map(|l| (id, l)))
+        .fold(None::<(&String, f64)>, |best, (id, latency)| match best {
+            Some((_, best_latency)) if best_latency <= latency => best,
+            _ => Some((id, latency)),
+        })
+        .map(|(id, _)| id.clone())
+}
+
+/// Parses the server ids out of `speedtest-cli --list` output, in listed order.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::autotune::parse_server_list;
+/// let output = "Retrieving speedtest.net configuration...\n\
+///     12345) Example Networks (London, United Kingdom) [4.21 km]\n\
+///      6789) Other ISP (Slough, United Kingdom) [30.02 km]\n";
+/// assert_eq!(parse_server_list(output), vec!["12345", "6789"]);
+/// ```
+pub fn parse_server_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, _) = line.trim().split_once(')')?;
+            (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+        })
+        .collect()
+}
+
+/// Lists candidate servers, closest first, using `speedtest-cli --list`.
+pub async fn list_servers() -> Result<Vec<String>, SpeedtestError> {
+    let output = Command::new("speedtest-cli")
+        .arg("--list")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| SpeedtestError::CommandFailed(format!("Failed to run speedtest-cli --list: {}", e)))?;
+
+    if output.status.success() {
+        Ok(parse_server_list(&String::from_utf8_lossy(&output.stdout)))
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(SpeedtestError::CommandFailed(format!("speedtest-cli --list failed: {}", stderr)))
+    }
+}
+
+/// Probes each candidate with a ping-only run and returns the measured latencies.
+pub async fn probe_latencies(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    candidates: &[String],
+) -> Vec<(String, Option<f64>)> {
+    let mut probes = Vec::with_capacity(candidates.len());
+    for id in candidates {
+        let probe_cfg = RunnerConfig {
+            server_ids: vec![id.clone()],
+            no_download: true,
+            no_upload: true,
+            ..cfg.clone()
+        };
+        let latency = match runner.run_speedtest(&probe_cfg).await {
+            Ok(raw) => runner.parse_output(&raw).ok().map(|result| result.ping_ms),
+            Err(e) => {
+                warn!("Auto-tune probe of server {} failed: {}", id, e);
+                None
+            }
+        };
+        probes.push((id.clone(), latency));
+    }
+    probes
+}
+
+/// Probes `candidates` and returns the resulting state, or `None` if none responded.
+pub async fn tune(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    candidates: &[String],
+    now: u64,
+) -> Option<TuneState> {
+    let probes = probe_latencies(runner, cfg, candidates).await;
+    pick_best_server(&probes).map(|server_id| TuneState { server_id, tuned_at: now })
+}
+
+/// Loads a previously saved state from `path`, if present and valid.
+pub fn load_state(path: &Path) -> Option<TuneState> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents)
+        .map_err(|e| warn!("Ignoring invalid auto-tune state in {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Saves `state` to `path` as JSON.
+pub fn save_state(path: &Path, state: &TuneState) -> std::io::Result<()> {
+    let json = serde_json::to_string(state).map_err(std::io::Error::other)?;
+    fs::write(path, json)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Keeps the auto-tuned server pinned between scheduled runs.
+pub struct AutoTuner {
+    state_path: PathBuf,
+    refresh: Duration,
+    candidates: usize,
+    state: Option<TuneState>,
+}
+
+impl AutoTuner {
+    /// Creates a tuner from the environment.
+    ///
+    /// Returns `None` unless `AUTO_TUNE` is enabled, the legacy backend is in use,
+    /// and `cfg` does not already pin a server. Reads `AUTO_TUNE_STATE_FILE`
+    /// (default `speedtest-autotune.json`), `AUTO_TUNE_REFRESH_HOURS` (default 168)
+    /// and `AUTO_TUNE_CANDIDATES` (default 5).
+    pub fn from_env(backend: Backend, cfg: &RunnerConfig) -> Option<Self> {
+        if !config::AUTO_TUNE.flag() || backend != Backend::Legacy || !cfg.server_ids.is_empty() {
+            return None;
+        }
+        let hours: u64 = config::AUTO_TUNE_REFRESH_HOURS.get();
+        Some(AutoTuner {
+            state_path: config::AUTO_TUNE_STATE_FILE.get::<String>().into(),
+            refresh: Duration::from_secs(hours * 3600),
+            candidates: config::AUTO_TUNE_CANDIDATES.get(),
+            state: None,
+        })
+    }
+
+    /// Returns the configuration for the next run, pinned to the tuned server.
+    ///
+    /// Re-tunes when no fresh state is available. If tuning fails, the previous
+    /// server (if any) stays pinned.
+    pub async fn config_for_run(&mut self, runner: &dyn SpeedtestRunner, cfg: &RunnerConfig) -> RunnerConfig {
+        if self.state.is_none() {
+            self.state = load_state(&self.state_path);
+        }
+
+        let now = unix_now();
+        let fresh = self.state.as_ref().is_some_and(|s| s.is_fresh(now, self.refresh));
+        if !fresh {
+            match list_servers().await {
+                Ok(mut candidates) => {
+                    candidates.truncate(self.candidates);
+                    if let Some(state) = tune(runner, cfg, &candidates, now).await {
+                        info!("Auto-tune pinned speedtest server {}", state.server_id);
+                        if let Err(e) = save_state(&self.state_path, &state) {
+                            error!("Failed to save auto-tune state to {}: {}", self.state_path.display(), e);
+                        }
+                        self.state = Some(state);
+                    }
+                }
+                Err(e) => warn!("Auto-tune could not list servers: {}", e),
+            }
+        }
+
+        match &self.state {
+            Some(state) => RunnerConfig {
+                server_ids: vec![state.server_id.clone()],
+                ..cfg.clone()
+            },
+            None => cfg.clone(),
+        }
+    }
+}
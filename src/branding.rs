@@ -0,0 +1,97 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! The `Server` response header.
+//!
+//! `SERVER_HEADER` sets the value [`set_server_header`] puts on every response,
+//! identifying this crate and version by default. Setting it to an empty string
+//! omits the header entirely.
+
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use crate::config;
+
+/// Default `Server` header value, `speedtest_statuspage/<version>`.
+pub const DEFAULT_SERVER_HEADER: &str = concat!("speedtest_statuspage/", env!("CARGO_PKG_VERSION"));
+
+/// The `Server` header to send, or `None` to omit it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerHeader(Option<HeaderValue>);
+
+impl ServerHeader {
+    /// Creates the header from `value`; an empty value omits it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `value` is not a valid header value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::branding::ServerHeader;
+    ///
+    /// assert_eq!(ServerHeader::new("acme-probe").unwrap().value(), Some("acme-probe"));
+    /// assert_eq!(ServerHeader::new("").unwrap().value(), None);
+    /// assert!(ServerHeader::new("bad\nvalue").is_err());
+    /// ```
+    pub fn new(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(ServerHeader(None));
+        }
+        HeaderValue::from_str(value)
+            .map(|v| ServerHeader(Some(v)))
+            .map_err(|_| format!("invalid Server header value '{}'", value.escape_debug()))
+    }
+
+    /// Reads `SERVER_HEADER`, falling back to [`DEFAULT_SERVER_HEADER`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming `SERVER_HEADER` if its value is not a valid header value.
+    pub fn from_env() -> Result<Self, String> {
+        Self::new(&config::SERVER_HEADER.get::<String>()).map_err(|e| format!("{}: {}", config::SERVER_HEADER.name, e))
+    }
+
+    /// Returns the header value, or `None` if the header is omitted.
+    pub fn value(&self) -> Option<&str> {
+        self.0.as_ref().and_then(|v| v.to_str().ok())
+    }
+}
+
+/// Middleware setting or removing the `Server` header according to the
+/// [`ServerHeader`] registered as app data.
+///
+/// Use with [`actix_web::middleware::from_fn`]. Without a registered value the
+/// response is left unchanged.
+pub async fn set_server_header<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<B>, Error> {
+    let server = req.app_data::<web::Data<ServerHeader>>().cloned();
+    let mut res = next.call(req).await?;
+    if let Some(server) = server {
+        let headers = res.headers_mut();
+        match &server.0 {
+            Some(value) => headers.insert(header::SERVER, value.clone()),
+            None => headers.remove(header::SERVER),
+        };
+    }
+    Ok(res)
+}
@@ -0,0 +1,79 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Capturing raw backend output for use as test fixtures.
+//!
+//! With `CAPTURE_DIR` set, the scheduler wraps its runner in a [`RecordingRunner`]
+//! which writes every successful raw output to a timestamped file in that directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use async_trait::async_trait;
+use tracing::warn;
+use crate::{config, RunnerConfig, SpeedTestResult, SpeedtestError, SpeedtestRunner};
+
+/// Runner decorator saving each raw output of `inner` into a capture directory.
+///
+/// Parsing is delegated to `inner`, so the wrapper works with any backend. Failing
+/// to write a capture is logged and never affects the run.
+pub struct RecordingRunner<R> {
+    inner: R,
+    dir: PathBuf,
+}
+
+impl<R: SpeedtestRunner> RecordingRunner<R> {
+    /// Wraps `inner`, capturing its outputs into `dir`.
+    pub fn new(inner: R, dir: impl Into<PathBuf>) -> Self {
+        RecordingRunner { inner, dir: dir.into() }
+    }
+
+    /// Returns the directory captures are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn capture(&self, raw: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let name = chrono::Utc::now().format("capture-%Y%m%dT%H%M%S%.6fZ.json").to_string();
+        let path = self.dir.join(name);
+        fs::write(&path, raw)?;
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl<R: SpeedtestRunner> SpeedtestRunner for RecordingRunner<R> {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        let output = self.inner.run_speedtest(cfg).await?;
+        if let Err(e) = self.capture(&output) {
+            warn!("Failed to capture speedtest output to {}: {}", self.dir.display(), e);
+        }
+        Ok(output)
+    }
+
+    fn parse_output(&self, raw: &str) -> Result<SpeedTestResult, String> {
+        self.inner.parse_output(raw)
+    }
+}
+
+/// Wraps `runner` in a [`RecordingRunner`] when `CAPTURE_DIR` is set.
+pub fn with_capture_from_env(runner: Box<dyn SpeedtestRunner>) -> Box<dyn SpeedtestRunner> {
+    match config::CAPTURE_DIR.raw().filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => Box::new(RecordingRunner::new(runner, dir)),
+        None => runner,
+    }
+}
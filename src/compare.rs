@@ -0,0 +1,117 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Side-by-side comparison of every configured speedtest backend.
+//!
+//! The comparison never touches the cached result served by `/speed`. The endpoint
+//! is disabled unless `COMPARE_TOKEN` is set, and it takes the same run lock as
+//! scheduled and triggered runs so their measurements never overlap.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use actix_web::{get, HttpRequest, HttpResponse, Responder};
+use futures::future::join_all;
+use serde::Serialize;
+use tokio::time;
+use tracing::warn;
+use crate::{access, config, Backend, RunnerConfig, SpeedTestResult, SpeedtestRunner, RUN_LOCK};
+
+/// The outcome of running a single backend during a comparison.
+///
+/// Serializes as `{"result": {...}}` on success or `{"error": "..."}` on failure.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendOutcome {
+    /// The backend produced a parsed result.
+    Result(Box<SpeedTestResult>),
+
+    /// The backend failed to run, timed out, or produced unparseable output.
+    Error(String),
+}
+
+/// Reads the environment variable `COMPARE_BACKENDS` (comma-separated backend names).
+///
+/// Defaults to every known backend. Unknown names are logged and skipped.
+pub fn compare_backends_from_env() -> Vec<Backend> {
+    config::COMPARE_BACKENDS
+        .value()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .filter_map(|name| {
+            name.parse::<Backend>()
+                .map_err(|e| warn!("Ignoring COMPARE_BACKENDS entry: {}", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Reads the environment variable `COMPARE_TIMEOUT_SECS` or returns a default of 60 seconds.
+///
+/// The timeout applies to each backend individually.
+pub fn compare_timeout() -> Duration {
+    Duration::from_secs(config::COMPARE_TIMEOUT_SECS.get())
+}
+
+/// Runs every `(name, runner)` pair concurrently and collects each outcome by name.
+///
+/// Each runner is bounded by `timeout`; results are parsed but not cached.
+pub async fn compare_backends(
+    runners: &[(String, Box<dyn SpeedtestRunner>)],
+    cfg: &RunnerConfig,
+    timeout: Duration,
+) -> BTreeMap<String, BackendOutcome> {
+    let runs = runners.iter().map(|(name, runner)| async move {
+        let outcome = match time::timeout(timeout, runner.run_speedtest(cfg)).await {
+            Ok(Ok(raw)) => match runner.parse_output(&raw) {
+                Ok(result) => BackendOutcome::Result(Box::new(result)),
+                Err(e) => BackendOutcome::Error(format!("failed to parse output: {}", e)),
+            },
+            Ok(Err(e)) => BackendOutcome::Error(e.to_string()),
+            Err(_) => BackendOutcome::Error(format!("timed out after {}s", timeout.as_secs())),
+        };
+        (name.clone(), outcome)
+    });
+    join_all(runs).await.into_iter().collect()
+}
+
+/// HTTP GET endpoint `/compare/backends` runs every configured backend concurrently.
+///
+/// Returns a JSON object mapping each backend name to its result or error. The
+/// backends are configured by `COMPARE_BACKENDS` and bounded by `COMPARE_TIMEOUT_SECS`.
+///
+/// Returns HTTP 404 Not Found unless `COMPARE_TOKEN` is set, HTTP 401 Unauthorized
+/// unless the request carries `Authorization: Bearer <COMPARE_TOKEN>`, and HTTP 429
+/// Too Many Requests while another run is in progress.
+#[get("/compare/backends")]
+pub async fn compare_backends_endpoint(req: HttpRequest) -> impl Responder {
+    let Some(token) = config::COMPARE_TOKEN.raw().filter(|t| !t.is_empty()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Some(unauthorized) = access::require_bearer(&req, &token) {
+        return unauthorized;
+    }
+    let Ok(_running) = RUN_LOCK.try_lock() else {
+        return HttpResponse::TooManyRequests().body("A speedtest is already running");
+    };
+
+    let runners: Vec<(String, Box<dyn SpeedtestRunner>)> = compare_backends_from_env()
+        .into_iter()
+        .map(|backend| (backend.name().to_string(), backend.runner()))
+        .collect();
+    let report = compare_backends(&runners, &RunnerConfig::from_env(), compare_timeout()).await;
+    HttpResponse::Ok().json(report)
+}
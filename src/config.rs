@@ -0,0 +1,602 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Central registry of every supported environment variable.
+//!
+//! Each variable is declared once here with its name, default and description.
+//! The application reads its configuration through these definitions, and the
+//! `.env` template printed by `RUN_MODE=print-env-template` is generated from
+//! [`ENV_VARS`], so the two cannot drift apart. The server settings are validated
+//! up front into a typed [`Config`].
+
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::parse_flag;
+
+/// A supported environment variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvVar {
+    /// Name of the variable.
+    pub name: &'static str,
+
+    /// Default used when the variable is unset, if any.
+    pub default: Option<&'static str>,
+
+    /// One-line description for the `.env` template.
+    pub description: &'static str,
+}
+
+impl EnvVar {
+    const fn new(name: &'static str, default: Option<&'static str>, description: &'static str) -> Self {
+        EnvVar { name, default, description }
+    }
+
+    /// Returns the raw value from the environment, if set.
+    pub fn raw(&self) -> Option<String> {
+        env::var(self.name).ok()
+    }
+
+    /// Returns the value from the environment, or the registered default.
+    pub fn value(&self) -> Option<String> {
+        self.raw().or_else(|| self.default.map(String::from))
+    }
+
+    /// Parses the value from the environment, falling back to the registered default
+    /// when the variable is unset or does not parse.
+    pub fn parse<T: FromStr>(&self) -> Option<T> {
+        self.raw()
+            .and_then(|s| s.trim().parse().ok())
+            .or_else(|| self.default.and_then(|d| d.parse().ok()))
+    }
+
+    /// Like [`parse`](Self::parse), for variables with a registered default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registered default does not parse as `T`, which is a bug in
+    /// the registry.
+    pub fn get<T: FromStr>(&self) -> T {
+        self.parse()
+            .unwrap_or_else(|| panic!("registered default for {} must parse", self.name))
+    }
+
+    /// Reads the variable as a boolean flag (see [`parse_flag`]).
+    pub fn flag(&self) -> bool {
+        self.value().map(|s| parse_flag(&s)).unwrap_or(false)
+    }
+}
+
+/// Selects what the binary does: `serve` or `print-env-template`.
+pub const RUN_MODE: EnvVar = EnvVar::new("RUN_MODE", Some("serve"), "What to run: serve, or print-env-template to print this template and exit");
+/// Log filter for the `tracing` subscriber.
+pub const RUST_LOG: EnvVar = EnvVar::new("RUST_LOG", Some("info"), "Log level filter, e.g. warn or info,actix_server=warn");
+/// Comma-separated addresses the HTTP server binds to (see [`BindTarget`]).
+pub const BIND_ADDRESS: EnvVar = EnvVar::new("BIND_ADDRESS", Some("127.0.0.1"), "Comma-separated addresses to serve endpoints on: host, host:port, [ipv6]:port or unix:/path/to.sock");
+/// Port of a second listener serving only `/metrics` and `/health`.
+pub const METRICS_BIND_PORT: EnvVar = EnvVar::new("METRICS_BIND_PORT", None, "Port of a separate listener for /metrics and /health, on the BIND_ADDRESS hosts; unset serves them on the main port");
+/// Port the HTTP server binds to.
+pub const BIND_PORT: EnvVar = EnvVar::new("BIND_PORT", Some("8080"), "Port to serve endpoints on");
+/// Seconds between attempts to bind the HTTP server.
+pub const BIND_RETRY_SECONDS: EnvVar = EnvVar::new("BIND_RETRY_SECONDS", Some("5"), "Seconds between attempts to bind the server at startup");
+/// Extra attempts to bind the HTTP server before giving up.
+pub const BIND_MAX_RETRIES: EnvVar = EnvVar::new("BIND_MAX_RETRIES", Some("12"), "Retries when binding the server fails at startup before giving up");
+/// Client networks allowed to reach the endpoints.
+pub const ALLOW_CIDRS: EnvVar = EnvVar::new("ALLOW_CIDRS", None, "Comma-separated client networks allowed to reach the endpoints (unset allows all)");
+/// Bearer token required by `/debug/env`.
+pub const DEBUG_TOKEN: EnvVar = EnvVar::new("DEBUG_TOKEN", None, "Bearer token enabling /debug/env (disabled when unset)");
+/// Bearer token required by `POST /maintenance`.
+pub const MAINTENANCE_TOKEN: EnvVar = EnvVar::new("MAINTENANCE_TOKEN", None, "Bearer token enabling POST /maintenance (disabled when unset)");
+/// Bearer token for `/compare/backends`.
+pub const COMPARE_TOKEN: EnvVar = EnvVar::new("COMPARE_TOKEN", None, "Bearer token enabling /compare/backends (disabled when unset)");
+/// Bearer token for `POST /history/clear`.
+pub const HISTORY_CLEAR_TOKEN: EnvVar = EnvVar::new("HISTORY_CLEAR_TOKEN", None, "Bearer token enabling POST /history/clear (disabled when unset)");
+/// Bearer token required on every endpoint.
+pub const API_TOKEN: EnvVar = EnvVar::new("API_TOKEN", None, "Bearer token required on every endpoint (open when unset)");
+/// Bearer token required by `POST /run`.
+pub const RUN_TOKEN: EnvVar = EnvVar::new("RUN_TOKEN", None, "Bearer token required by POST /run (open to any request when unset)");
+/// Enables the `/test/set` and `/test/clear` cache scripting endpoints.
+pub const TEST_ENDPOINTS_ENABLED: EnvVar = EnvVar::new("TEST_ENDPOINTS_ENABLED", Some("false"), "Enable POST /test/set and /test/clear for scripting the cache in tests; never enable in production");
+/// Largest request body accepted by the write endpoints.
+pub const MAX_INGEST_BYTES: EnvVar = EnvVar::new("MAX_INGEST_BYTES", Some("65536"), "Largest request body in bytes accepted by POST endpoints; larger bodies get 413");
+/// Value of the `Server` response header.
+pub const SERVER_HEADER: EnvVar = EnvVar::new("SERVER_HEADER", Some(crate::branding::DEFAULT_SERVER_HEADER), "Value of the Server response header; empty omits the header");
+/// Units of the download and upload gauges in `/metrics` (see [`crate::MetricsUnits`]).
+pub const METRICS_UNITS: EnvVar = EnvVar::new("METRICS_UNITS", Some("mbps"), "Units of the /metrics speed gauges: mbps (speedtest_download_mbps) or bps (speedtest_download_bits_per_second)");
+/// Minutes between scheduled speedtests.
+pub const INTERVAL_MINUTES: EnvVar = EnvVar::new("INTERVAL_MINUTES", Some("10"), "Minutes between speedtests");
+/// Time-of-day windows with their own run intervals.
+pub const SCHEDULE: EnvVar = EnvVar::new("SCHEDULE", None, "Comma-separated HH:MM-HH:MM@interval windows in local time, e.g. 08:00-23:00@5m,23:00-08:00@30m; overrides INTERVAL_MINUTES");
+/// Shortened interval used while runs are failing.
+pub const OUTAGE_PROBE_INTERVAL_SECONDS: EnvVar = EnvVar::new("OUTAGE_PROBE_INTERVAL_SECONDS", None, "Seconds between runs after a failure, until a run succeeds");
+/// Age after which the cached result is cleared.
+pub const CLEAR_AFTER_SECONDS: EnvVar = EnvVar::new("CLEAR_AFTER_SECONDS", None, "Clear the cached result once it is older than this many seconds, so /speed returns 503 (0 or unset keeps it)");
+/// Number of past results kept in memory.
+pub const HISTORY_SIZE: EnvVar = EnvVar::new("HISTORY_SIZE", Some("100"), "Number of past results kept in memory; the oldest is dropped once full");
+/// Speedtest backend used by the scheduler.
+pub const SPEEDTEST_BACKEND: EnvVar = EnvVar::new("SPEEDTEST_BACKEND", Some("legacy"), "Speedtest backend: legacy (speedtest-cli), fast (fast-cli) or ookla (official Ookla speedtest)");
+/// Whether incomplete results are discarded.
+pub const REJECT_INCOMPLETE: EnvVar = EnvVar::new("REJECT_INCOMPLETE", Some("false"), "Discard results with a zero or invalid download, upload or ping");
+/// How often repeated identical failures are logged.
+pub const FAILURE_LOG_EVERY: EnvVar = EnvVar::new("FAILURE_LOG_EVERY", Some("10"), "Log only every Nth repeated identical failure");
+/// Whether an outdated speedtest binary prevents startup.
+pub const STRICT_VERSION: EnvVar = EnvVar::new("STRICT_VERSION", Some("false"), "Refuse to start when the speedtest binary is older than the supported minimum");
+/// Whether the scheduler runs on its own runtime.
+pub const ISOLATE_SCHEDULER: EnvVar = EnvVar::new("ISOLATE_SCHEDULER", Some("false"), "Run the scheduler on a dedicated single-threaded runtime");
+/// Servers passed to `speedtest-cli --server`.
+pub const SPEEDTEST_SERVER_IDS: EnvVar = EnvVar::new("SPEEDTEST_SERVER_IDS", None, "Comma-separated server ids to test against");
+/// HTTP timeout passed to `speedtest-cli --timeout`.
+pub const SPEEDTEST_CLI_TIMEOUT_SECS: EnvVar = EnvVar::new("SPEEDTEST_CLI_TIMEOUT_SECS", None, "HTTP timeout in seconds passed to speedtest-cli");
+/// Silence period after which the speedtest process is killed.
+pub const CLI_STALL_SECONDS: EnvVar = EnvVar::new("CLI_STALL_SECONDS", None, "Kill the speedtest process if it writes no output for this many seconds");
+/// Wall-clock limit for a single speedtest run.
+pub const SPEEDTEST_TIMEOUT_SECS: EnvVar = EnvVar::new("SPEEDTEST_TIMEOUT_SECS", Some("90"), "Kill a speedtest run that takes longer than this many seconds (0 disables)");
+/// Retries of a failed speedtest run.
+pub const SPEEDTEST_RETRIES: EnvVar = EnvVar::new("SPEEDTEST_RETRIES", Some("3"), "Times a failed speedtest run is retried before giving up until the next interval");
+/// Delay before the first retry.
+pub const SPEEDTEST_RETRY_BASE_MS: EnvVar = EnvVar::new("SPEEDTEST_RETRY_BASE_MS", Some("2000"), "Milliseconds before the first retry, doubled for each further retry");
+/// Randomise each retry delay between zero and its backoff.
+pub const RETRY_JITTER: EnvVar = EnvVar::new("RETRY_JITTER", Some("false"), "Wait a random time between zero and the backoff before each retry, so instances recovering together spread out");
+/// Cap on the total time spent on one scheduled run, retries included.
+pub const RUN_BUDGET_SECONDS: EnvVar = EnvVar::new("RUN_BUDGET_SECONDS", None, "Give up retrying once a run and its retries have taken this many seconds, so runs cannot overlap (0 or unset disables)");
+/// Proxy exported to the speedtest process.
+pub const SPEEDTEST_PROXY: EnvVar = EnvVar::new("SPEEDTEST_PROXY", None, "Proxy URL exported to the speedtest process as HTTP(S)_PROXY");
+/// Whether `--secure` is passed.
+pub const SPEEDTEST_SECURE: EnvVar = EnvVar::new("SPEEDTEST_SECURE", Some("false"), "Use HTTPS when talking to speedtest servers");
+/// Whether `--no-download` is passed.
+pub const SPEEDTEST_NO_DOWNLOAD: EnvVar = EnvVar::new("SPEEDTEST_NO_DOWNLOAD", Some("false"), "Skip the download phase");
+/// Whether `--no-upload` is passed.
+pub const SPEEDTEST_NO_UPLOAD: EnvVar = EnvVar::new("SPEEDTEST_NO_UPLOAD", Some("false"), "Skip the upload phase");
+/// Extra arguments for the speedtest command.
+pub const SPEEDTEST_EXTRA_ARGS: EnvVar = EnvVar::new("SPEEDTEST_EXTRA_ARGS", None, "Extra whitespace-separated arguments for the speedtest command");
+/// Name recorded in each result.
+pub const NODE_NAME: EnvVar = EnvVar::new("NODE_NAME", None, "Name recorded in each result (defaults to the hostname)");
+/// Tags attached to each result.
+pub const RESULT_TAGS: EnvVar = EnvVar::new("RESULT_TAGS", None, "Comma-separated key=value tags attached to each result");
+/// Nominal link speed of the local interface.
+pub const NIC_SPEED_MBPS: EnvVar = EnvVar::new("NIC_SPEED_MBPS", None, "Nominal local link speed in Mbps");
+/// Interface whose link speed is detected.
+pub const NIC_INTERFACE: EnvVar = EnvVar::new("NIC_INTERFACE", None, "Interface whose link speed is read from sysfs when NIC_SPEED_MBPS is unset");
+/// Backends run by `/compare/backends`.
+pub const COMPARE_BACKENDS: EnvVar = EnvVar::new("COMPARE_BACKENDS", Some("legacy,fast"), "Comma-separated backends run by /compare/backends");
+/// Per-backend timeout for `/compare/backends`.
+pub const COMPARE_TIMEOUT_SECS: EnvVar = EnvVar::new("COMPARE_TIMEOUT_SECS", Some("60"), "Per-backend timeout in seconds for /compare/backends");
+/// Whether the best server is auto-tuned.
+pub const AUTO_TUNE: EnvVar = EnvVar::new("AUTO_TUNE", Some("false"), "Probe the closest servers and pin the lowest-latency one");
+/// File recording the auto-tuned server.
+pub const AUTO_TUNE_STATE_FILE: EnvVar = EnvVar::new("AUTO_TUNE_STATE_FILE", Some("speedtest-autotune.json"), "File recording the auto-tuned server");
+/// Hours before the auto-tuned server is re-probed.
+pub const AUTO_TUNE_REFRESH_HOURS: EnvVar = EnvVar::new("AUTO_TUNE_REFRESH_HOURS", Some("168"), "Hours before the auto-tuned server is re-probed");
+/// Number of servers probed when auto-tuning.
+pub const AUTO_TUNE_CANDIDATES: EnvVar = EnvVar::new("AUTO_TUNE_CANDIDATES", Some("5"), "Number of closest servers probed when auto-tuning");
+/// File the latest result is persisted to.
+pub const SNAPSHOT_FILE: EnvVar = EnvVar::new("SNAPSHOT_FILE", None, "File the latest result is saved to and restored from at startup");
+/// File the runtime counters are saved to and restored from.
+pub const STATE_FILE: EnvVar = EnvVar::new("STATE_FILE", Some("speedtest-state.json"), "File the /stats/runtime counters are saved to every minute and at shutdown, and restored from at startup; empty to disable");
+/// JSON Lines file every result is appended to.
+pub const RESULTS_LOG: EnvVar = EnvVar::new("RESULTS_LOG", None, "File every result is appended to as one JSON object per line");
+/// Whether the results log is rolled into a dated gzip archive every day.
+pub const RESULTS_LOG_ARCHIVE: EnvVar = EnvVar::new("RESULTS_LOG_ARCHIVE", Some("false"), "Move the previous day's RESULTS_LOG into history/YYYY-MM-DD.ndjson.gz after midnight and start a fresh file");
+/// SQLite database every result is saved to (requires the `sqlite` feature).
+pub const DB_PATH: EnvVar = EnvVar::new("DB_PATH", None, "SQLite database every result is saved to, and the latest restored from at startup; needs a build with the sqlite feature");
+/// Upper bounds of the bufferbloat grades.
+pub const BUFFERBLOAT_THRESHOLDS: EnvVar = EnvVar::new("BUFFERBLOAT_THRESHOLDS", Some("30,60,200,400"), "Highest bufferbloat in ms graded A, B, C and D; anything higher is an F");
+/// Upper bounds in seconds of the `speedtest_run_duration_seconds` histogram buckets.
+pub const RUN_DURATION_BUCKETS: EnvVar = EnvVar::new("RUN_DURATION_BUCKETS", Some("5,10,15,20,30,45,60,90,120,180,300"), "Comma-separated increasing upper bounds in seconds of the run duration histogram buckets in /metrics");
+/// Host whose latency is measured separately from the speedtest server.
+pub const PING_TARGET: EnvVar = EnvVar::new("PING_TARGET", None, "host:port whose TCP connect latency is recorded as external_ping_ms (port defaults to 443)");
+/// How latency to `PING_TARGET` is measured: `tcp` or `icmp`.
+pub const PROBE_METHOD: EnvVar = EnvVar::new("PROBE_METHOD", Some("tcp"), "How PING_TARGET latency is measured: tcp (connect time) or icmp (system ping command)");
+/// Directory raw backend output is captured into.
+pub const CAPTURE_DIR: EnvVar = EnvVar::new("CAPTURE_DIR", None, "Directory every raw speedtest output is saved to, for building test fixtures");
+/// Command whose JSON output is attached to each result.
+pub const METADATA_HOOK: EnvVar = EnvVar::new("METADATA_HOOK", None, "Command run after each speedtest whose JSON object output is stored as metadata");
+/// Dead-man's-switch URL pinged after each successful run.
+pub const HEARTBEAT_URL: EnvVar = EnvVar::new("HEARTBEAT_URL", None, "URL pinged after every successful run");
+/// Webhook receiving every successful result.
+pub const WEBHOOK_URL: EnvVar = EnvVar::new("WEBHOOK_URL", None, "URL receiving every successful result as a JSON POST");
+/// Webhook receiving results with a slow download.
+pub const ALERT_WEBHOOK_URL: EnvVar = EnvVar::new("ALERT_WEBHOOK_URL", None, "URL receiving the result as a JSON POST when the download speed is below ALERT_MIN_DOWNLOAD_MBPS");
+/// Download speed below which the alert webhook is called.
+pub const ALERT_MIN_DOWNLOAD_MBPS: EnvVar = EnvVar::new("ALERT_MIN_DOWNLOAD_MBPS", None, "Download speed in Mbps below which ALERT_WEBHOOK_URL is called");
+
+/// Download speed below which `/health` is unhealthy.
+pub const HEALTH_MIN_DOWNLOAD_MBPS: EnvVar = EnvVar::new("HEALTH_MIN_DOWNLOAD_MBPS", None, "Download speed in Mbps below which /health returns 503");
+/// Ping above which `/health` is unhealthy.
+pub const HEALTH_MAX_PING_MS: EnvVar = EnvVar::new("HEALTH_MAX_PING_MS", None, "Ping in ms above which /health returns 503");
+/// Time without a successful run after which `/health` is unhealthy.
+pub const UNHEALTHY_AFTER_SECONDS: EnvVar = EnvVar::new("UNHEALTHY_AFTER_SECONDS", None, "Seconds without a successful run after which /health returns 503");
+
+/// Every supported environment variable, in template order.
+pub const ENV_VARS: &[EnvVar] = &[
+    RUN_MODE,
+    RUST_LOG,
+    BIND_ADDRESS,
+    BIND_PORT,
+    METRICS_BIND_PORT,
+    BIND_RETRY_SECONDS,
+    BIND_MAX_RETRIES,
+    ALLOW_CIDRS,
+    DEBUG_TOKEN,
+    MAINTENANCE_TOKEN,
+    COMPARE_TOKEN,
+    HISTORY_CLEAR_TOKEN,
+    API_TOKEN,
+    RUN_TOKEN,
+    TEST_ENDPOINTS_ENABLED,
+    MAX_INGEST_BYTES,
+    SERVER_HEADER,
+    METRICS_UNITS,
+    INTERVAL_MINUTES,
+    SCHEDULE,
+    OUTAGE_PROBE_INTERVAL_SECONDS,
+    CLEAR_AFTER_SECONDS,
+    HISTORY_SIZE,
+    SPEEDTEST_BACKEND,
+    REJECT_INCOMPLETE,
+    FAILURE_LOG_EVERY,
+    ISOLATE_SCHEDULER,
+    STRICT_VERSION,
+    SPEEDTEST_SERVER_IDS,
+    SPEEDTEST_CLI_TIMEOUT_SECS,
+    CLI_STALL_SECONDS,
+    SPEEDTEST_TIMEOUT_SECS,
+    SPEEDTEST_RETRIES,
+    SPEEDTEST_RETRY_BASE_MS,
+    RETRY_JITTER,
+    RUN_BUDGET_SECONDS,
+    SPEEDTEST_PROXY,
+    SPEEDTEST_SECURE,
+    SPEEDTEST_NO_DOWNLOAD,
+    SPEEDTEST_NO_UPLOAD,
+    SPEEDTEST_EXTRA_ARGS,
+    NODE_NAME,
+    RESULT_TAGS,
+    NIC_SPEED_MBPS,
+    NIC_INTERFACE,
+    METADATA_HOOK,
+    BUFFERBLOAT_THRESHOLDS,
+    RUN_DURATION_BUCKETS,
+    PING_TARGET,
+    PROBE_METHOD,
+    SNAPSHOT_FILE,
+    STATE_FILE,
+    RESULTS_LOG,
+    RESULTS_LOG_ARCHIVE,
+    DB_PATH,
+    CAPTURE_DIR,
+    COMPARE_BACKENDS,
+    COMPARE_TIMEOUT_SECS,
+    AUTO_TUNE,
+    AUTO_TUNE_STATE_FILE,
+    AUTO_TUNE_REFRESH_HOURS,
+    AUTO_TUNE_CANDIDATES,
+    HEARTBEAT_URL,
+    WEBHOOK_URL,
+    ALERT_WEBHOOK_URL,
+    ALERT_MIN_DOWNLOAD_MBPS,
+    HEALTH_MIN_DOWNLOAD_MBPS,
+    HEALTH_MAX_PING_MS,
+    UNHEALTHY_AFTER_SECONDS,
+];
+
+/// Returns the registered variable named `name`, if any.
+pub fn lookup(name: &str) -> Option<&'static EnvVar> {
+    ENV_VARS.iter().find(|var| var.name == name)
+}
+
+/// Returns the Levenshtein edit distance between `a` and `b`.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::config::edit_distance;
+/// assert_eq!(edit_distance("INTERVAL_MINUTE", "INTERVAL_MINUTES"), 1);
+/// assert_eq!(edit_distance("kitten", "sitting"), 3);
+/// ```
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Suggests the registered variable `name` was probably meant to be.
+///
+/// Returns `None` if `name` is registered or is not close to any registered name.
+/// Names are compared case-insensitively, allowing one edit per six characters of
+/// the registered name (between one and three).
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::config::suggest_name;
+/// assert_eq!(suggest_name("INTERVAL_MINUTE"), Some("INTERVAL_MINUTES"));
+/// assert_eq!(suggest_name("bind_port"), Some("BIND_PORT"));
+/// assert_eq!(suggest_name("BIND_PORT"), None);
+/// assert_eq!(suggest_name("PATH"), None);
+/// ```
+pub fn suggest_name(name: &str) -> Option<&'static str> {
+    if lookup(name).is_some() {
+        return None;
+    }
+    let upper = name.to_ascii_uppercase();
+    ENV_VARS
+        .iter()
+        .map(|var| (var.name, edit_distance(&upper, var.name)))
+        .filter(|(known, distance)| *distance <= (known.len() / 6).clamp(1, 3))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| known)
+}
+
+/// Renders a complete `.env` template from [`ENV_VARS`].
+///
+/// Variables with a default are emitted with that value; variables without one are
+/// emitted commented out.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::config::env_template;
+/// let template = env_template();
+/// assert!(template.contains("# Port to serve endpoints on\nBIND_PORT=8080\n"));
+/// ```
+pub fn env_template() -> String {
+    ENV_VARS
+        .iter()
+        .map(|var| match var.default {
+            Some(default) => format!("# {}\n{}={}\n", var.description, var.name, default),
+            None => format!("# {}\n#{}=\n", var.description, var.name),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One entry of `BIND_ADDRESS`: somewhere the HTTP server listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindTarget {
+    /// A TCP address, with the IPv6 brackets removed from `host`.
+    Tcp { host: String, port: u16 },
+
+    /// A Unix domain socket, written `unix:/path/to.sock`.
+    Unix(PathBuf),
+}
+
+impl BindTarget {
+    /// Parses a comma-separated list of targets, ignoring empty entries.
+    ///
+    /// An entry is `unix:` followed by a socket path, or a host with an optional
+    /// `:port`; IPv6 addresses with a port are bracketed (`[::1]:8080`), and a bare
+    /// IPv6 address such as `::` takes `default_port` like any host without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first invalid entry, or if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use speedtest_statuspage::config::BindTarget;
+    /// let targets = BindTarget::parse_list("0.0.0.0, [::]:9090, unix:/run/speed.sock", 8080).unwrap();
+    /// assert_eq!(targets[0], BindTarget::Tcp { host: "0.0.0.0".to_string(), port: 8080 });
+    /// assert_eq!(targets[1], BindTarget::Tcp { host: "::".to_string(), port: 9090 });
+    /// assert_eq!(targets[2], BindTarget::Unix("/run/speed.sock".into()));
+    /// ```
+    pub fn parse_list(list: &str, default_port: u16) -> Result<Vec<Self>, String> {
+        let targets = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| BindTarget::parse(entry, default_port))
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.is_empty() {
+            return Err("no addresses given".to_string());
+        }
+        Ok(targets)
+    }
+
+    /// Parses a single trimmed, non-empty entry.
+    fn parse(entry: &str, default_port: u16) -> Result<Self, String> {
+        if let Some(path) = entry.strip_prefix("unix:") {
+            return match path {
+                "" => Err(format!("entry {:?} has no socket path", entry)),
+                path => Ok(BindTarget::Unix(PathBuf::from(path))),
+            };
+        }
+        if entry.contains(char::is_whitespace) {
+            return Err(format!("entry {:?} is not a valid address", entry));
+        }
+
+        let (host, port) = if let Some(bracketed) = entry.strip_prefix('[') {
+            let (host, rest) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("entry {:?} has an unclosed '['", entry))?;
+            match rest {
+                "" => (host, None),
+                rest => (host, Some(rest.strip_prefix(':').unwrap_or(rest))),
+            }
+        } else {
+            match entry.split_once(':') {
+                Some((host, port)) if !port.contains(':') => (host, Some(port)),
+                _ => (entry, None),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("entry {:?} has no host", entry));
+        }
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| format!("entry {:?} has an invalid port", entry))?,
+            None => default_port,
+        };
+        Ok(BindTarget::Tcp { host: host.to_string(), port })
+    }
+}
+
+impl fmt::Display for BindTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindTarget::Tcp { host, port } if host.contains(':') => write!(f, "[{}]:{}", host, port),
+            BindTarget::Tcp { host, port } => write!(f, "{}:{}", host, port),
+            BindTarget::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// Returns every distinct TCP host of `targets` on `port`, in order.
+fn metrics_targets(targets: &[BindTarget], port: u16) -> Vec<BindTarget> {
+    let mut metrics: Vec<BindTarget> = Vec::new();
+    for target in targets {
+        if let BindTarget::Tcp { host, .. } = target {
+            let target = BindTarget::Tcp { host: host.clone(), port };
+            if !metrics.contains(&target) {
+                metrics.push(target);
+            }
+        }
+    }
+    metrics
+}
+
+/// Validated server settings, read once at startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Addresses to serve endpoints on, as written in `BIND_ADDRESS`.
+    pub bind_address: String,
+
+    /// Port to serve endpoints on (`BIND_PORT`), for addresses without their own.
+    pub bind_port: u16,
+
+    /// Where to listen, parsed from `BIND_ADDRESS` and `BIND_PORT`.
+    pub bind_targets: Vec<BindTarget>,
+
+    /// Where the separate `/metrics` and `/health` listener binds: every TCP host of
+    /// `BIND_ADDRESS` on `METRICS_BIND_PORT`. Empty, serving them on the main
+    /// listener, while `METRICS_BIND_PORT` is unset.
+    pub metrics_bind_targets: Vec<BindTarget>,
+
+    /// Time between scheduled speedtests (`INTERVAL_MINUTES`).
+    pub interval: Duration,
+}
+
+/// Error returned by [`Config::from_env`], listing every invalid variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// One message per invalid variable, naming it and the rejected value.
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration: {}", self.errors.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::from_lookup(|_| None).expect("registered defaults must be valid")
+    }
+}
+
+impl Config {
+    /// Builds the configuration from the process environment.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    /// Builds the configuration using `lookup` to resolve each variable, falling
+    /// back to the registered defaults for unset ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ConfigError`] naming every variable that is set but invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use speedtest_statuspage::config::Config;
+    /// let cfg = Config::from_lookup(|key| (key == "INTERVAL_MINUTES").then(|| "5".to_string())).unwrap();
+    /// assert_eq!(cfg.bind_port, 8080);
+    /// assert_eq!(cfg.interval, Duration::from_secs(300));
+    ///
+    /// let err = Config::from_lookup(|key| (key == "BIND_PORT").then(|| "http".to_string())).unwrap_err();
+    /// assert_eq!(err.errors, ["BIND_PORT must be a port number from 0 to 65535, got \"http\""]);
+    /// ```
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut errors = Vec::new();
+        let mut read = |var: &EnvVar, expected: &str, valid: fn(&str) -> bool| {
+            let value = lookup(var.name).or_else(|| var.default.map(String::from)).unwrap_or_default();
+            let value = value.trim().to_string();
+            if !valid(&value) {
+                errors.push(format!("{} must be {}, got {:?}", var.name, expected, value));
+            }
+            value
+        };
+
+        let bind_address = read(&BIND_ADDRESS, "a non-empty address", |s| !s.is_empty());
+        let bind_port = read(&BIND_PORT, "a port number from 0 to 65535", |s| s.parse::<u16>().is_ok());
+        let interval = read(&INTERVAL_MINUTES, "a positive whole number of minutes", |s| {
+            s.parse::<u64>().is_ok_and(|minutes| minutes > 0 && minutes.checked_mul(60).is_some())
+        });
+        let bind_targets = match (bind_address.is_empty(), bind_port.parse::<u16>()) {
+            (false, Ok(port)) => BindTarget::parse_list(&bind_address, port).unwrap_or_else(|e| {
+                errors.push(format!("BIND_ADDRESS {}", e));
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        let metrics_port = lookup(METRICS_BIND_PORT.name).map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let metrics_bind_targets = match metrics_port.as_deref().map(str::parse::<u16>) {
+            None => Vec::new(),
+            Some(Err(_)) => {
+                errors.push(format!(
+                    "{} must be a port number from 0 to 65535, got {:?}",
+                    METRICS_BIND_PORT.name,
+                    metrics_port.unwrap_or_default()
+                ));
+                Vec::new()
+            }
+            Some(Ok(port)) => {
+                let targets = metrics_targets(&bind_targets, port);
+                if targets.is_empty() && !bind_targets.is_empty() {
+                    errors.push(format!("{} needs a TCP address in BIND_ADDRESS", METRICS_BIND_PORT.name));
+                }
+                targets
+            }
+        };
+
+        if !errors.is_empty() {
+            return Err(ConfigError { errors });
+        }
+        Ok(Config {
+            bind_address,
+            bind_port: bind_port.parse().expect("validated above"),
+            bind_targets,
+            metrics_bind_targets,
+            interval: Duration::from_secs(interval.parse::<u64>().expect("validated above") * 60),
+        })
+    }
+}
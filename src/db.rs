@@ -0,0 +1,172 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Persistence of every result to SQLite, behind the `sqlite` feature.
+//!
+//! When `DB_PATH` is set, the database is opened at startup and a `results` table
+//! created if missing. Every successful result is inserted as a row, and the most
+//! recent row is loaded into the cache at startup so that `/speed` works
+//! immediately after a restart.
+//!
+//! Alongside the columns for querying (timestamp, speeds, ping, server id and ISP),
+//! each row keeps the whole result as JSON, which is what gets restored. Database
+//! errors are logged and never affect a run.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::{error, warn};
+use crate::config;
+use crate::models::{migrate_result, SpeedTestResult};
+
+/// A SQLite database of speedtest results.
+pub struct ResultStore {
+    conn: Mutex<Connection>,
+}
+
+impl ResultStore {
+    /// Opens the database at `path`, or an in-memory one for `:memory:`, creating
+    /// the `results` table if missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or the table created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::db::ResultStore;
+    /// use speedtest_statuspage::SpeedTestResult;
+    ///
+    /// let store = ResultStore::open(":memory:").unwrap();
+    /// assert_eq!(store.latest().unwrap(), None);
+    /// store.insert(&SpeedTestResult { download_mbps: 94.5, ..Default::default() }).unwrap();
+    /// assert_eq!(store.latest().unwrap().unwrap().download_mbps, 94.5);
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                download_mbps REAL NOT NULL,
+                upload_mbps REAL NOT NULL,
+                ping_ms REAL NOT NULL,
+                server_id TEXT NOT NULL,
+                isp TEXT NOT NULL,
+                result TEXT NOT NULL
+            )",
+        )?;
+        Ok(ResultStore { conn: Mutex::new(conn) })
+    }
+
+    /// Inserts `result` as a new row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the row cannot be written.
+    pub fn insert(&self, result: &SpeedTestResult) -> rusqlite::Result<()> {
+        let json = serde_json::to_string(result).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.lock().unwrap_or_else(|e| e.into_inner()).execute(
+            "INSERT INTO results (timestamp, download_mbps, upload_mbps, ping_ms, server_id, isp, result)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                result.timestamp,
+                result.download_mbps,
+                result.upload_mbps,
+                result.ping_ms,
+                result.server.id,
+                result.client.isp,
+                json
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Deletes every row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rows cannot be deleted.
+    pub fn clear(&self) -> rusqlite::Result<()> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner()).execute("DELETE FROM results", [])?;
+        Ok(())
+    }
+
+    /// Returns the most recently inserted result, if any.
+    ///
+    /// Results written by older releases are upgraded with [`migrate_result`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be read or the stored JSON is invalid.
+    pub fn latest(&self) -> rusqlite::Result<Option<SpeedTestResult>> {
+        let json: Option<String> = self
+            .conn
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .query_row("SELECT result FROM results ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+            .optional()?;
+        json.map(|json| {
+            serde_json::from_str(&json)
+                .and_then(migrate_result)
+                .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+        })
+        .transpose()
+    }
+}
+
+/// Returns the configured database path, if any.
+pub fn db_path() -> Option<PathBuf> {
+    config::DB_PATH.raw().filter(|s| !s.trim().is_empty()).map(PathBuf::from)
+}
+
+/// The database at `DB_PATH`, opened on first use; `None` when unset or unusable.
+static STORE: Lazy<Option<ResultStore>> = Lazy::new(|| {
+    let path = db_path()?;
+    ResultStore::open(&path)
+        .map_err(|e| error!("Failed to open database {}: {}; results will not be persisted", path.display(), e))
+        .ok()
+});
+
+/// Inserts `result` into the database at `DB_PATH`, if configured. Failures are logged.
+pub fn record_result(result: &SpeedTestResult) {
+    if let Some(store) = STORE.as_ref()
+        && let Err(e) = store.insert(result)
+    {
+        error!("Failed to save speedtest result from {} to the database: {}", result.timestamp, e);
+    }
+}
+
+/// Deletes every result from the database at `DB_PATH`, if configured. Failures are logged.
+pub fn clear_results() {
+    if let Some(store) = STORE.as_ref()
+        && let Err(e) = store.clear()
+    {
+        error!("Failed to clear the database: {}", e);
+    }
+}
+
+/// Opens the database at `DB_PATH`, if configured, and returns its most recent result.
+pub fn load_latest() -> Option<SpeedTestResult> {
+    STORE
+        .as_ref()?
+        .latest()
+        .map_err(|e| warn!("Ignoring stored results: {}", e))
+        .ok()
+        .flatten()
+}
@@ -0,0 +1,114 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Diagnostics for mistyped or missing configuration.
+//!
+//! `GET /debug/env` reports which registered variables are set, which are unset,
+//! and which set variables look like typos of registered ones. Only variable names
+//! are reported, never values. The endpoint requires `Authorization: Bearer` with
+//! the value of `DEBUG_TOKEN` and responds 404 when no token is configured.
+
+use std::env;
+use actix_web::{get, HttpRequest, HttpResponse, Responder};
+use serde::Serialize;
+use tracing::warn;
+use crate::access;
+use crate::config::{self, ENV_VARS};
+
+/// A set variable which looks like a misspelling of a registered one.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PossibleTypo {
+    /// Name of the variable as set.
+    pub name: String,
+
+    /// Registered variable it is probably meant to be.
+    pub suggestion: &'static str,
+}
+
+/// Report returned by `/debug/env`.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvReport {
+    /// Registered variables which are set.
+    pub recognized_set: Vec<&'static str>,
+
+    /// Registered variables which are unset and fall back to their default.
+    pub recognized_unset: Vec<&'static str>,
+
+    /// Set variables which are close to, but not exactly, a registered name.
+    pub possible_typos: Vec<PossibleTypo>,
+}
+
+/// Classifies the names of the set environment variables against the registry.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::debug::diagnose_env;
+///
+/// let report = diagnose_env(["BIND_PORT", "INTERVAL_MINUTE", "HOME"]);
+/// assert_eq!(report.recognized_set, ["BIND_PORT"]);
+/// assert_eq!(report.possible_typos[0].suggestion, "INTERVAL_MINUTES");
+/// assert!(report.recognized_unset.contains(&"INTERVAL_MINUTES"));
+/// ```
+pub fn diagnose_env<I, S>(set_names: I) -> EnvReport
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let set_names: Vec<String> = set_names.into_iter().map(|name| name.as_ref().to_string()).collect();
+    let mut report = EnvReport::default();
+    for var in ENV_VARS {
+        if set_names.iter().any(|name| name == var.name) {
+            report.recognized_set.push(var.name);
+        } else {
+            report.recognized_unset.push(var.name);
+        }
+    }
+    let mut typos: Vec<PossibleTypo> = set_names
+        .into_iter()
+        .filter_map(|name| config::suggest_name(&name).map(|suggestion| PossibleTypo { name, suggestion }))
+        .collect();
+    typos.sort_by(|a, b| a.name.cmp(&b.name));
+    report.possible_typos = typos;
+    report
+}
+
+/// Diagnoses the current process environment.
+pub fn diagnose_process_env() -> EnvReport {
+    diagnose_env(env::vars_os().filter_map(|(name, _)| name.into_string().ok()))
+}
+
+/// Logs a warning for every set variable which looks like a typo.
+pub fn warn_possible_typos() {
+    for typo in diagnose_process_env().possible_typos {
+        warn!("{} is not a recognized variable; did you mean {}?", typo.name, typo.suggestion);
+    }
+}
+
+/// HTTP GET endpoint `/debug/env` returns an [`EnvReport`] for the process environment.
+///
+/// Returns HTTP 404 Not Found unless `DEBUG_TOKEN` is set, and HTTP 401
+/// Unauthorized unless the request carries `Authorization: Bearer <DEBUG_TOKEN>`.
+#[get("/debug/env")]
+pub async fn debug_env(req: HttpRequest) -> impl Responder {
+    let Some(token) = config::DEBUG_TOKEN.raw().filter(|t| !t.is_empty()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Some(unauthorized) = access::require_bearer(&req, &token) {
+        return unauthorized;
+    }
+    HttpResponse::Ok().json(diagnose_process_env())
+}
@@ -0,0 +1,178 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Netflix Fast.com backend using the `fast` binary from `fast-cli`.
+//!
+//! Selected with `SPEEDTEST_BACKEND=fast`.
+//!
+//! ## Reduced field set
+//!
+//! Fast.com reports download/upload speed, idle and loaded latency and the
+//! transferred volume only.
+//! It provides no server or ISP metadata, so [`SpeedTestResult::server`] is left at
+//! its default, [`SpeedTestResult::client`] only carries the IP address, and
+//! [`SpeedTestResult::share`] is always `None`. The timestamp is taken at parse time.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use crate::models::SpeedUnit;
+use crate::{watchdog, ClientInfo, RunnerConfig, SpeedTestResult, SpeedtestError, SpeedtestRunner};
+
+/// The raw response from `fast --upload --json`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::fast::FastComResponse;
+///
+/// let json = r#"{"downloadSpeed": 450, "uploadSpeed": 250, "downloadUnit": "Mbps",
+///     "downloaded": 230, "uploadUnit": "Mbps", "uploaded": 150, "latency": 7,
+///     "bufferBloat": 15, "userLocation": "Oslo, NO", "userIp": "192.0.2.1"}"#;
+/// let parsed: FastComResponse = serde_json::from_str(json).unwrap();
+/// assert_eq!(parsed.download_speed, 450.0);
+/// assert_eq!(parsed.upload_unit.as_deref(), Some("Mbps"));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FastComResponse {
+    /// Download speed expressed in [`download_unit`](Self::download_unit).
+    pub download_speed: f64,
+
+    /// Upload speed expressed in [`upload_unit`](Self::upload_unit), if measured.
+    #[serde(default)]
+    pub upload_speed: Option<f64>,
+
+    /// Unit of the download speed, e.g. `Mbps`.
+    pub download_unit: String,
+
+    /// Megabytes downloaded during the test.
+    #[serde(default)]
+    pub downloaded: Option<f64>,
+
+    /// Unit of the upload speed, e.g. `Mbps`.
+    #[serde(default)]
+    pub upload_unit: Option<String>,
+
+    /// Megabytes uploaded during the test.
+    #[serde(default)]
+    pub uploaded: Option<f64>,
+
+    /// Unloaded latency in milliseconds.
+    #[serde(default)]
+    pub latency: Option<f64>,
+
+    /// Latency under load in milliseconds, measured while downloading.
+    #[serde(default)]
+    pub buffer_bloat: Option<f64>,
+
+    /// Approximate location of the client as reported by Fast.com.
+    #[serde(default)]
+    pub user_location: Option<String>,
+
+    /// Public IP address of the client.
+    #[serde(default)]
+    pub user_ip: Option<String>,
+}
+
+/// Maps a fast-cli unit name onto a [`SpeedUnit`].
+///
+/// Unknown units are assumed to be `Mbps`, the unit fast-cli uses in practice.
+pub fn parse_unit(unit: &str) -> SpeedUnit {
+    match unit.trim().to_ascii_lowercase().as_str() {
+        "bps" => SpeedUnit::Bps,
+        "kbps" => SpeedUnit::Kbps,
+        "gbps" => SpeedUnit::Gbps,
+        _ => SpeedUnit::Mbps,
+    }
+}
+
+/// Converts a speed in the given fast-cli unit (`Kbps`, `Mbps` or `Gbps`) into bits per second.
+///
+/// See [`parse_unit`] for how the unit is interpreted.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::fast::to_bps;
+/// assert_eq!(to_bps(1.5, "Gbps"), 1_500_000_000.0);
+/// assert_eq!(to_bps(800.0, "Kbps"), 800_000.0);
+/// ```
+pub fn to_bps(value: f64, unit: &str) -> f64 {
+    parse_unit(unit).to_bps(value)
+}
+
+/// fast-cli labels each speed with its unit (`downloadUnit`/`uploadUnit`).
+impl From<FastComResponse> for SpeedTestResult {
+    fn from(data: FastComResponse) -> Self {
+        let download_bps = to_bps(data.download_speed, &data.download_unit);
+        let upload_bps = data
+            .upload_speed
+            .map(|speed| to_bps(speed, data.upload_unit.as_deref().unwrap_or("Mbps")))
+            .unwrap_or(0.0);
+
+        SpeedTestResult {
+            bytes_received: data.downloaded.map(|mb| (mb * 1_000_000.0) as u64).unwrap_or(0),
+            bytes_sent: data.uploaded.map(|mb| (mb * 1_000_000.0) as u64).unwrap_or(0),
+            download_bps,
+            upload_bps,
+            download_mbps: download_bps / 1_000_000.0,
+            upload_mbps: upload_bps / 1_000_000.0,
+            ping_ms: data.latency.unwrap_or(0.0),
+            download_latency_ms: data.buffer_bloat,
+            client: ClientInfo {
+                ip: data.user_ip.unwrap_or_default(),
+                ..Default::default()
+            },
+            server: Default::default(),
+            share: None,
+            timestamp: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Runner for the Netflix Fast.com backend, invoking `fast --upload --json`.
+///
+/// Only [`RunnerConfig::proxy`] and [`RunnerConfig::extra_args`] apply; the
+/// speedtest.net specific options are ignored.
+pub struct FastComRunner;
+
+#[async_trait]
+impl SpeedtestRunner for FastComRunner {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        let mut command = Command::new("fast");
+        command.args(["--upload", "--json"]).args(&cfg.extra_args);
+        if let Some(proxy) = &cfg.proxy {
+            command.env("HTTP_PROXY", proxy).env("HTTPS_PROXY", proxy);
+        }
+
+        let output = watchdog::run_watched(&mut command, "fast", cfg.stall_timeout).await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(SpeedtestError::CommandFailed(format!("fast failed: {}", stderr)))
+        }
+    }
+
+    fn parse_output(&self, raw: &str) -> Result<SpeedTestResult, String> {
+        serde_json::from_str::<FastComResponse>(raw)
+            .map(SpeedTestResult::from)
+            .map_err(|e| e.to_string())
+    }
+}
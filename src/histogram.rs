@@ -0,0 +1,130 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Cumulative histograms in the Prometheus text exposition format.
+//!
+//! A [`Histogram`] counts observations into buckets with fixed upper bounds and
+//! renders them as the `_bucket`, `_sum` and `_count` series of a Prometheus
+//! histogram. The run duration histogram in `/metrics` is built on it (see
+//! [`crate::stats::render_run_durations`]).
+
+use std::str::FromStr;
+
+/// Upper bounds of histogram buckets, strictly increasing and finite.
+///
+/// Parses from a comma-separated list such as `5,10,30,60`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::histogram::Bounds;
+///
+/// let bounds: Bounds = "5, 10,30".parse().unwrap();
+/// assert_eq!(bounds.0, vec![5.0, 10.0, 30.0]);
+/// assert!("10,5".parse::<Bounds>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bounds(pub Vec<f64>);
+
+impl FromStr for Bounds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bounds = s
+            .split(',')
+            .map(str::trim)
+            .filter(|bound| !bound.is_empty())
+            .map(|bound| bound.parse::<f64>().map_err(|_| format!("invalid bucket bound '{}'", bound)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if bounds.is_empty() {
+            return Err("no bucket bounds given".to_string());
+        }
+        if bounds.iter().any(|bound| !bound.is_finite()) || bounds.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(format!("bucket bounds '{}' must be finite and increasing", s.trim()));
+        }
+        Ok(Bounds(bounds))
+    }
+}
+
+/// Observations counted into buckets, as exported by a Prometheus histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative; the last entry is the `+Inf` bucket.
+    counts: Vec<u64>,
+    sum: f64,
+}
+
+impl Histogram {
+    /// Creates an empty histogram with the given bucket bounds.
+    pub fn new(bounds: Bounds) -> Self {
+        let counts = vec![0; bounds.0.len() + 1];
+        Histogram { bounds: bounds.0, counts, sum: 0.0 }
+    }
+
+    /// Counts `value` into the first bucket whose bound is at least `value`.
+    ///
+    /// Non-finite values are ignored.
+    pub fn observe(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let bucket = self.bounds.iter().position(|bound| value <= *bound).unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+
+    /// Returns the number of observations.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Renders the histogram as `name`, with `# HELP` and `# TYPE` lines.
+    ///
+    /// The `_bucket` series are cumulative, ending with `le="+Inf"`, followed by
+    /// `_sum` and `_count`. Until the first observation only the `# HELP` and
+    /// `# TYPE` lines are rendered, like the gauges of an empty `/metrics`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::histogram::Histogram;
+    ///
+    /// let mut histogram = Histogram::new("1,5".parse().unwrap());
+    /// histogram.observe(0.5);
+    /// histogram.observe(3.0);
+    /// assert_eq!(
+    ///     histogram.render("latency_seconds", "Latency."),
+    ///     "# HELP latency_seconds Latency.\n# TYPE latency_seconds histogram\n\
+    ///      latency_seconds_bucket{le=\"1\"} 1\nlatency_seconds_bucket{le=\"5\"} 2\n\
+    ///      latency_seconds_bucket{le=\"+Inf\"} 2\nlatency_seconds_sum 3.5\nlatency_seconds_count 2\n"
+    /// );
+    /// ```
+    pub fn render(&self, name: &str, help: &str) -> String {
+        let mut out = format!("# HELP {} {}\n# TYPE {} histogram\n", name, help, name);
+        if self.count() == 0 {
+            return out;
+        }
+        let les = self.bounds.iter().map(f64::to_string).chain(std::iter::once("+Inf".to_string()));
+        let mut cumulative = 0;
+        for (le, count) in les.zip(&self.counts) {
+            cumulative += count;
+            out.push_str(&format!("{}_bucket{{le=\"{}\"}} {}\n", name, le, cumulative));
+        }
+        out.push_str(&format!("{}_sum {}\n{}_count {}\n", name, self.sum, name, cumulative));
+        out
+    }
+}
@@ -0,0 +1,198 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Comparison of measured speeds against the ISP's reported averages.
+//!
+//! speedtest.net reports the average speeds other customers of the same ISP see
+//! (`ispdlavg`/`ispulavg` in [`ClientInfo`](crate::models::ClientInfo)). The
+//! `/speed/vs-isp-avg` endpoint compares the cached result against them, and
+//! `/speed/percentile-vs-isp` estimates where the result ranks among those customers.
+
+use actix_web::{get, HttpResponse, Responder};
+use serde::Serialize;
+use crate::{get_last_result, SpeedTestResult};
+
+/// Measured speeds alongside the ISP's reported averages.
+///
+/// Averages the ISP did not report, and the differences depending on them, are `None`
+/// (serialized as `null`); `isp_average_available` is `false` when neither is known.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IspComparison {
+    /// Measured download speed in Mbps.
+    pub download_mbps: f64,
+
+    /// Measured upload speed in Mbps.
+    pub upload_mbps: f64,
+
+    /// ISP's reported average download speed in Mbps.
+    pub isp_download_avg_mbps: Option<f64>,
+
+    /// ISP's reported average upload speed in Mbps.
+    pub isp_upload_avg_mbps: Option<f64>,
+
+    /// Percent by which the measured download exceeds (positive) or falls short of
+    /// (negative) the ISP average.
+    pub download_percent_difference: Option<f64>,
+
+    /// Percent by which the measured upload exceeds or falls short of the ISP average.
+    pub upload_percent_difference: Option<f64>,
+
+    /// Whether the ISP reported any average at all.
+    pub isp_average_available: bool,
+}
+
+/// Returns the percentage difference of `measured` relative to `reference`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::isp::percent_difference;
+///
+/// assert_eq!(percent_difference(75.0, 50.0), Some(50.0));
+/// assert_eq!(percent_difference(25.0, 50.0), Some(-50.0));
+/// assert_eq!(percent_difference(25.0, 0.0), None);
+/// ```
+pub fn percent_difference(measured: f64, reference: f64) -> Option<f64> {
+    crate::percent_of(measured, reference).map(|percent| percent - 100.0)
+}
+
+/// Compares `result` against the ISP averages reported with it.
+pub fn compare_to_isp_average(result: &SpeedTestResult) -> IspComparison {
+    let isp_download = result.client.isp_download_avg_mbps();
+    let isp_upload = result.client.isp_upload_avg_mbps();
+    IspComparison {
+        download_mbps: result.download_mbps,
+        upload_mbps: result.upload_mbps,
+        isp_download_avg_mbps: isp_download,
+        isp_upload_avg_mbps: isp_upload,
+        download_percent_difference: isp_download.and_then(|avg| percent_difference(result.download_mbps, avg)),
+        upload_percent_difference: isp_upload.and_then(|avg| percent_difference(result.upload_mbps, avg)),
+        isp_average_available: isp_download.is_some() || isp_upload.is_some(),
+    }
+}
+
+/// HTTP GET endpoint `/speed/vs-isp-avg` compares the cached result with the ISP's averages.
+///
+/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+#[get("/speed/vs-isp-avg")]
+pub async fn isp_average_endpoint() -> impl Responder {
+    match get_last_result() {
+        Some(result) => HttpResponse::Ok().json(compare_to_isp_average(&result)),
+        None => HttpResponse::ServiceUnavailable().body("Speedtest result not available yet."),
+    }
+}
+
+/// Spread assumed for the speeds of an ISP's customers, as the standard deviation
+/// of their natural logarithm.
+///
+/// With `0.5`, two thirds of customers are assumed to get between 0.6 and 1.65
+/// times the average.
+pub const ISP_SPEED_LOG_SPREAD: f64 = 0.5;
+
+/// Error function, using Abramowitz and Stegun formula 7.1.26 (error below 1.5e-7).
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = [1.061_405_429, -1.453_152_027, 1.421_413_741, -0.284_496_736, 0.254_829_592]
+        .iter()
+        .fold(0.0, |acc, a| (acc + a) * t);
+    let y = 1.0 - poly * (-x * x).exp();
+    if x < 0.0 { -y } else { y }
+}
+
+/// Estimates the percentage of an ISP's customers with a lower speed than `measured`.
+///
+/// speedtest.net only reports the average speed of the ISP's customers, so the
+/// speeds are modelled as log-normally distributed around that average with a
+/// spread of [`ISP_SPEED_LOG_SPREAD`]: the estimate is `Φ(ln(measured / average) / σ)`,
+/// where `Φ` is the standard normal distribution function. Matching the average
+/// is the 50th percentile, twice the average about the 92nd and half of it about
+/// the 8th. This is a rough estimate, not a measured ranking.
+///
+/// The estimate is rounded to one decimal place. Returns `None` when either value
+/// is negative or not finite, or the average is zero.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::isp::estimate_percentile;
+///
+/// assert_eq!(estimate_percentile(50.0, 50.0), Some(50.0));
+/// assert!(estimate_percentile(100.0, 50.0).unwrap() > 91.0);
+/// assert_eq!(estimate_percentile(0.0, 50.0), Some(0.0));
+/// assert_eq!(estimate_percentile(10.0, 0.0), None);
+/// ```
+pub fn estimate_percentile(measured: f64, isp_average: f64) -> Option<f64> {
+    if !measured.is_finite() || measured < 0.0 || !isp_average.is_finite() || isp_average <= 0.0 {
+        return None;
+    }
+    if measured == 0.0 {
+        return Some(0.0);
+    }
+    let z = (measured / isp_average).ln() / ISP_SPEED_LOG_SPREAD;
+    let percentile = 50.0 * (1.0 + erf(z / std::f64::consts::SQRT_2));
+    Some(((percentile * 10.0).round() / 10.0).clamp(0.0, 100.0))
+}
+
+/// Estimated rank of a result among the ISP's customers, served by
+/// `/speed/percentile-vs-isp`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IspPercentile {
+    /// Whether the ISP reported an average download speed to compare against.
+    pub sufficient_data: bool,
+
+    /// Estimated percentage of customers with a slower download (see [`estimate_percentile`]).
+    pub download_percentile: Option<f64>,
+
+    /// Estimated percentage of customers with a slower upload.
+    pub upload_percentile: Option<f64>,
+
+    /// Human-readable summary, e.g. "You're in the top 10% of Example ISP customers for download speed".
+    pub summary: String,
+}
+
+/// Estimates where `result` ranks among the customers of its ISP.
+///
+/// Without a usable ISP download average the percentiles are `None` and the
+/// summary explains that there is insufficient data.
+pub fn percentile_vs_isp(result: &SpeedTestResult) -> IspPercentile {
+    let percentile = |measured, average: Option<f64>| average.and_then(|avg| estimate_percentile(measured, avg));
+    let download_percentile = percentile(result.download_mbps, result.client.isp_download_avg_mbps());
+    let upload_percentile = percentile(result.upload_mbps, result.client.isp_upload_avg_mbps());
+    let isp = match result.client.isp.trim() {
+        "" => "your ISP's",
+        isp => isp,
+    };
+    let summary = match download_percentile {
+        Some(percentile) => {
+            let top = (100.0 - percentile).round().max(1.0);
+            format!("You're in the top {}% of {} customers for download speed", top, isp)
+        }
+        None => "Insufficient data: the ISP's average download speed was not reported".to_string(),
+    };
+    IspPercentile { sufficient_data: download_percentile.is_some(), download_percentile, upload_percentile, summary }
+}
+
+/// HTTP GET endpoint `/speed/percentile-vs-isp` estimates where the cached result
+/// ranks among the ISP's customers (see [`percentile_vs_isp`]).
+///
+/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+#[get("/speed/percentile-vs-isp")]
+pub async fn isp_percentile_endpoint() -> impl Responder {
+    match get_last_result() {
+        Some(result) => HttpResponse::Ok().json(percentile_vs_isp(&result)),
+        None => HttpResponse::ServiceUnavailable().body("Speedtest result not available yet."),
+    }
+}
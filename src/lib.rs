@@ -15,23 +15,67 @@
 // This file may not be copied, modified, or distributed except according to those terms.
 
 pub mod models;
+pub mod access;
+pub mod auth;
+pub mod autotune;
+pub mod branding;
+pub mod capture;
+pub mod compare;
+pub mod config;
+#[cfg(feature = "sqlite")]
+pub mod db;
+pub mod debug;
+pub mod fast;
+pub mod histogram;
+pub mod isp;
+pub mod limits;
+pub mod maintenance;
+pub mod metadata;
+pub mod nic;
+pub mod notify;
+pub mod ookla;
+pub mod ping;
+pub mod results_log;
+pub mod routes;
+pub mod run;
+pub mod schedule;
+pub mod snapshot;
+pub mod sparkline;
+pub mod stats;
+pub mod tags;
+pub mod testmode;
+pub mod units;
+pub mod version;
+pub mod watchdog;
 
+use std::collections::{HashMap, VecDeque};
 use std::env;
-use std::process::Stdio;
-use std::sync::Mutex;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
 use std::time::{Duration, Instant};
-use actix_web::{get, HttpResponse, Responder};
+use actix_web::http::header;
+use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
 use async_trait::async_trait;
+use futures::FutureExt;
 use once_cell::sync::Lazy;
 use tokio::process::Command;
+use tokio::sync::watch;
 use tokio::time;
+use tracing::{error, info, warn};
+pub use fast::FastComRunner;
 pub use models::*;
 
 /// Global cached speedtest result and the instant it was cached.
 ///
-/// Wrapped in a mutex for thread-safe mutable access.
+/// Wrapped in a read-write lock so that concurrent readers, such as `/speed`
+/// requests, do not block each other; only the scheduler's writes are exclusive.
 /// Initially empty until the first speedtest run.
-pub static LAST_RESULT: Lazy<Mutex<Option<(SpeedTestResult, Instant)>>> = Lazy::new(|| Mutex::new(None));
+pub static LAST_RESULT: Lazy<RwLock<Option<(SpeedTestResult, Instant)>>> = Lazy::new(|| RwLock::new(None));
 
 /// Retrieves the last cached speedtest result, if available.
 ///
@@ -51,6 +95,7 @@ pub static LAST_RESULT: Lazy<Mutex<Option<(SpeedTestResult, Instant)>>> = Lazy::
 /// #     server: Default::default(),
 /// #     share: None,
 /// #     timestamp: "2025-08-07T12:34:56Z".to_string(),
+/// #     ..Default::default()
 /// # };
 /// set_last_result_for_test(dummy_result.clone());
 ///
@@ -61,9 +106,8 @@ pub static LAST_RESULT: Lazy<Mutex<Option<(SpeedTestResult, Instant)>>> = Lazy::
 /// clear_last_result_for_test();
 /// assert!(get_last_result().is_none());
 /// ```
-
 pub fn get_last_result() -> Option<SpeedTestResult> {
-    let cache = LAST_RESULT.lock().unwrap();
+    let cache = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
     cache.as_ref().map(|(result, _)| result.clone())
 }
 
@@ -85,15 +129,143 @@ pub fn get_last_result() -> Option<SpeedTestResult> {
 /// #     server: Default::default(),
 /// #     share: None,
 /// #     timestamp: "2025-08-07T12:34:56Z".to_string(),
+/// #     ..Default::default()
 /// # };
 /// set_last_result_for_test(dummy_result.clone());
 /// let cached = get_last_result().unwrap();
 /// assert_eq!(cached.bytes_received, 100);
 /// ```
-
 pub fn set_last_result_for_test(result: SpeedTestResult) {
-    let mut cache = LAST_RESULT.lock().unwrap();
-    *cache = Some((result, Instant::now()));
+    store_last_result(result).expect("failed to write cached speedtest result");
+}
+
+/// Sets the cached speedtest result as if it had been measured at `at`.
+///
+/// Lets tests exercise age-dependent behaviour by backdating a result instead of
+/// sleeping. Available in unit tests and, for downstream crates, behind the
+/// `testutil` feature.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::{Duration, Instant};
+/// # use speedtest_statuspage::{SpeedTestResult, set_last_result_at, get_last_result_age};
+/// let a_minute_ago = Instant::now() - Duration::from_secs(60);
+/// set_last_result_at(SpeedTestResult::default(), a_minute_ago);
+/// assert!(get_last_result_age().unwrap() >= Duration::from_secs(60));
+/// ```
+#[cfg(any(test, feature = "testutil"))]
+pub fn set_last_result_at(result: SpeedTestResult, at: Instant) {
+    let mut cache = LAST_RESULT.write().unwrap_or_else(|e| e.into_inner());
+    *cache = Some((result, at));
+}
+
+/// Returns how long ago the cached speedtest result was stored, if any.
+pub fn get_last_result_age() -> Option<Duration> {
+    let cache = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
+    cache.as_ref().map(|(_, cached_at)| cached_at.elapsed())
+}
+
+/// Empties the cache if the cached result is older than `max_age`.
+///
+/// Returns `true` if a result was cleared.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::{Duration, Instant};
+/// # use speedtest_statuspage::{expire_last_result, get_last_result, set_last_result_at, SpeedTestResult};
+/// set_last_result_at(SpeedTestResult::default(), Instant::now() - Duration::from_secs(120));
+/// assert!(expire_last_result(Duration::from_secs(60)));
+/// assert_eq!(get_last_result(), None);
+/// ```
+pub fn expire_last_result(max_age: Duration) -> bool {
+    let mut cache = LAST_RESULT.write().unwrap_or_else(|e| e.into_inner());
+    if cache.as_ref().is_some_and(|(_, cached_at)| cached_at.elapsed() > max_age) {
+        *cache = None;
+        true
+    } else {
+        false
+    }
+}
+
+/// Reads `CLEAR_AFTER_SECONDS`, the age after which the cached result is forgotten.
+///
+/// Returns `None` when unset or zero, which keeps results until replaced.
+pub fn clear_after() -> Option<Duration> {
+    config::CLEAR_AFTER_SECONDS
+        .parse::<u64>()
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Background task clearing the cached result once it is older than `max_age`, so
+/// that the endpoints report no data instead of stale numbers.
+///
+/// The age is checked every second.
+pub async fn run_result_expiry(max_age: Duration) {
+    let mut ticker = time::interval(Duration::from_secs(1));
+    loop {
+        ticker.tick().await;
+        if expire_last_result(max_age) {
+            info!("Cleared cached speedtest result older than {}s", max_age.as_secs());
+        }
+    }
+}
+
+/// Error returned when a write into [`LAST_RESULT`] did not take effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheError {
+    /// The cache lock was poisoned by a panicking writer. The poison has been
+    /// cleared, but the write was not applied.
+    Poisoned,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Poisoned => write!(f, "speedtest cache lock was poisoned"),
+        }
+    }
+}
+
+impl std::error::Error for CacheError {}
+
+/// Attempts a single write of `result` into [`LAST_RESULT`].
+///
+/// If the lock is poisoned, the poison is cleared so that subsequent writes can
+/// succeed, and [`CacheError::Poisoned`] is returned to signal that this write
+/// was **not** stored.
+pub fn try_store_last_result(result: SpeedTestResult) -> Result<(), CacheError> {
+    match LAST_RESULT.write() {
+        Ok(mut cache) => {
+            *cache = Some((result, Instant::now()));
+            Ok(())
+        }
+        Err(_) => {
+            LAST_RESULT.clear_poison();
+            Err(CacheError::Poisoned)
+        }
+    }
+}
+
+/// Writes `result` into [`LAST_RESULT`], re-attempting once after recovering
+/// from a poisoned lock.
+///
+/// Returns `Ok(())` only when the value was actually stored.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::{SpeedTestResult, store_last_result, get_last_result};
+/// store_last_result(SpeedTestResult::default()).unwrap();
+/// assert!(get_last_result().is_some());
+/// ```
+pub fn store_last_result(result: SpeedTestResult) -> Result<(), CacheError> {
+    try_store_last_result(result.clone()).or_else(|e| {
+        warn!("{}; retrying cache write after recovery", e);
+        try_store_last_result(result)
+    })
 }
 
 /// Clears the cached speedtest result.
@@ -107,119 +279,1816 @@ pub fn set_last_result_for_test(result: SpeedTestResult) {
 /// clear_last_result_for_test();
 /// assert!(get_last_result().is_none());
 /// ```
-
 pub fn clear_last_result_for_test() {
-    let mut cache = LAST_RESULT.lock().unwrap();
+    let mut cache = LAST_RESULT.write().unwrap_or_else(|e| e.into_inner());
     *cache = None;
 }
 
+/// Past speedtest results, oldest first, each with the instant it was recorded.
+///
+/// Holds at most `HISTORY_SIZE` entries; the oldest is dropped once full.
+pub static HISTORY: Lazy<Mutex<VecDeque<(SpeedTestResult, Instant)>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Appends `result` to [`HISTORY`], dropping the oldest entries beyond `HISTORY_SIZE`.
+fn push_history(result: SpeedTestResult) {
+    let capacity: usize = config::HISTORY_SIZE.get();
+    let mut entries = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    entries.push_back((result, Instant::now()));
+    while entries.len() > capacity {
+        entries.pop_front();
+    }
+}
+
+/// Returns the retained speedtest results, oldest first.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::{SpeedTestResult, get_history, push_history_for_test, clear_history_for_test};
+/// clear_history_for_test();
+/// push_history_for_test(SpeedTestResult { download_mbps: 1.0, ..Default::default() });
+/// push_history_for_test(SpeedTestResult { download_mbps: 2.0, ..Default::default() });
+///
+/// let history = get_history();
+/// assert_eq!(history.len(), 2);
+/// assert_eq!(history[1].download_mbps, 2.0);
+/// ```
+pub fn get_history() -> Vec<SpeedTestResult> {
+    let entries = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+    entries.iter().map(|(result, _)| result.clone()).collect()
+}
+
+/// Appends a result to the history as if a run had produced it. Used for testing purposes.
+pub fn push_history_for_test(result: SpeedTestResult) {
+    push_history(result);
+}
+
+/// Fills the history from the tail of the `RESULTS_LOG` file, if configured.
+///
+/// Reads at most `HISTORY_SIZE` results (see [`results_log::load_recent`]) and
+/// returns how many were added. A missing log is not an error; an unreadable one is
+/// logged.
+pub fn restore_history() -> usize {
+    let Some(path) = results_log::results_log_path() else {
+        return 0;
+    };
+    let results = match results_log::load_recent(&path, config::HISTORY_SIZE.get()) {
+        Ok(results) => results,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return 0,
+        Err(e) => {
+            warn!("Failed to read history from {}: {}", path.display(), e);
+            return 0;
+        }
+    };
+    let restored = results.len();
+    for result in results {
+        push_history(result);
+    }
+    restored
+}
+
+/// Empties the history.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::{SpeedTestResult, get_history, push_history_for_test, clear_history_for_test};
+/// push_history_for_test(SpeedTestResult::default());
+/// clear_history_for_test();
+/// assert!(get_history().is_empty());
+/// ```
+pub fn clear_history_for_test() {
+    HISTORY.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// A user-supplied check run on every result before it is cached.
+pub type ResultValidator = Box<dyn Fn(&SpeedTestResult) -> Result<(), String> + Send + Sync>;
+
+static RESULT_VALIDATOR: RwLock<Option<ResultValidator>> = RwLock::new(None);
+
+/// Registers `f` to be consulted by [`run_speedtest_and_cache_with_runner`] before
+/// each result is cached, replacing any previous validator.
+///
+/// Returning `Err` discards the result, logging the given reason.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{clear_result_validator, set_result_validator};
+///
+/// set_result_validator(|result| match result.server.id.as_str() {
+///     "666" => Err("server 666 is blacklisted".to_string()),
+///     _ => Ok(()),
+/// });
+/// # clear_result_validator();
+/// ```
+pub fn set_result_validator(f: impl Fn(&SpeedTestResult) -> Result<(), String> + Send + Sync + 'static) {
+    *RESULT_VALIDATOR.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(f));
+}
+
+/// Removes the validator registered with [`set_result_validator`].
+pub fn clear_result_validator() {
+    *RESULT_VALIDATOR.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Runs the registered validator, if any, on `result`.
+fn validate_result(result: &SpeedTestResult) -> Result<(), String> {
+    match RESULT_VALIDATOR.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(validator) => validator(result),
+        None => Ok(()),
+    }
+}
+
+/// Body of the `/speed` response: the cached result plus values derived from it.
+#[derive(Serialize, Debug)]
+pub struct SpeedResponse<'a> {
+    /// The cached result, serialized inline.
+    #[serde(flatten)]
+    pub result: &'a SpeedTestResult,
+
+    /// Connection health from 0 to 100 (see [`SpeedTestResult::quality_score`]).
+    pub score: u8,
+
+    /// Whole seconds since the result was cached, as of the request.
+    pub age_seconds: u64,
+
+    /// Whether maintenance mode is on; only serialized when it is.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub maintenance: bool,
+
+    /// Speeds in the unit asked for with `?unit=`, serialized inline when present.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub scaled: Option<ScaledSpeeds>,
+}
+
+/// Download and upload speeds rescaled into a requested [`units::Unit`].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ScaledSpeeds {
+    /// Name of the unit, e.g. `Gbps`.
+    pub unit: &'static str,
+
+    /// Download speed in `unit`.
+    pub download: f64,
+
+    /// Upload speed in `unit`.
+    pub upload: f64,
+}
+
+impl<'a> SpeedResponse<'a> {
+    /// Builds the response for `result`, cached at `cached_at`.
+    ///
+    /// The age is measured when this is called, so build the response per request.
+    pub fn new(result: &'a SpeedTestResult, cached_at: Instant) -> Self {
+        SpeedResponse {
+            result,
+            score: result.quality_score(&ScoreWeights::default()),
+            age_seconds: cached_at.elapsed().as_secs(),
+            maintenance: maintenance::is_active(),
+            scaled: None,
+        }
+    }
+
+    /// Adds the download and upload speeds converted into `unit`.
+    pub fn in_unit(mut self, unit: units::Unit) -> Self {
+        self.scaled = Some(ScaledSpeeds {
+            unit: unit.name(),
+            download: units::bps_to(self.result.download_bps, unit),
+            upload: units::bps_to(self.result.upload_bps, unit),
+        });
+        self
+    }
+}
+
+/// Reads the `?unit=` query parameter, if present.
+fn requested_unit(req: &HttpRequest) -> Result<Option<units::Unit>, String> {
+    let query = web::Query::<HashMap<String, String>>::from_query(req.query_string()).map_err(|e| e.to_string())?;
+    query.get("unit").map(|unit| unit.parse()).transpose()
+}
+
+/// Content type of MessagePack responses.
+pub const MSGPACK_CONTENT_TYPE: &str = "application/msgpack";
+
+/// Returns `true` if the client asked for MessagePack via `?format=msgpack` or an
+/// `Accept: application/msgpack` (or `application/x-msgpack`) header.
+pub fn wants_msgpack(req: &HttpRequest) -> bool {
+    let by_query = web::Query::<HashMap<String, String>>::from_query(req.query_string())
+        .is_ok_and(|query| query.get("format").is_some_and(|f| f.eq_ignore_ascii_case("msgpack")));
+    let by_accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| {
+            accept.split(',').any(|media| {
+                let media = media.split(';').next().unwrap_or("").trim();
+                media.eq_ignore_ascii_case(MSGPACK_CONTENT_TYPE) || media.eq_ignore_ascii_case("application/x-msgpack")
+            })
+        });
+    by_query || by_accept
+}
+
 /// HTTP GET endpoint `/speed` returns the last cached speedtest result as JSON.
 ///
-/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+/// The result is extended with `score` and `age_seconds` fields (see
+/// [`SpeedResponse`]). With `?unit=` (see [`units`]) it also carries `unit`,
+/// `download` and `upload` in that unit; an unknown unit gets HTTP 400 Bad Request.
+/// Clients asking for MessagePack (see [`wants_msgpack`]) receive the same fields
+/// as a MessagePack map instead. Returns HTTP 503 Service Unavailable if no result
+/// is cached yet.
 #[get("/speed")]
-pub async fn speedtest() -> impl Responder {
-    let cache = LAST_RESULT.lock().unwrap();
-    if let Some((cached_result, _timestamp)) = &*cache {
-        HttpResponse::Ok().json(cached_result)
+pub async fn speedtest(req: HttpRequest) -> impl Responder {
+    let unit = match requested_unit(&req) {
+        Ok(unit) => unit,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let cache = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_result, cached_at)) = &*cache {
+        let mut body = SpeedResponse::new(cached_result, *cached_at);
+        if let Some(unit) = unit {
+            body = body.in_unit(unit);
+        }
+        let mut response = HttpResponse::Ok();
+        response.insert_header((header::VARY, "Accept"));
+        if wants_msgpack(&req) {
+            match rmp_serde::to_vec_named(&body) {
+                Ok(bytes) => response.content_type(MSGPACK_CONTENT_TYPE).body(bytes),
+                Err(e) => HttpResponse::InternalServerError().body(format!("Failed to encode result: {}", e)),
+            }
+        } else {
+            response.json(body)
+        }
     } else {
         HttpResponse::ServiceUnavailable().body("Speedtest result not available yet.")
     }
 }
 
-/// Reads the environment variable `INTERVAL_MINUTES` or returns a default of 10 minutes.
+/// Renders the cached result's `field` as a plain-text integer, or 503 if empty.
+fn raw_speed_response(field: impl Fn(&SpeedTestResult) -> f64) -> HttpResponse {
+    match get_last_result() {
+        Some(result) => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body((field(&result) as u64).to_string()),
+        None => HttpResponse::ServiceUnavailable().body("Speedtest result not available yet."),
+    }
+}
+
+/// Thresholds which turn `/health` unhealthy when the cached result breaches
+/// them or no run has succeeded for too long.
 ///
-/// The duration represents how frequently speedtests are run.
-pub fn min_frequency_duration() -> Duration {
-    let minutes = env::var("INTERVAL_MINUTES")
-        .ok()
-        .and_then(|s| s.parse::<u64>().ok())
-        .unwrap_or(10); // default: 10 minutes
-    Duration::from_secs(minutes * 60)
+/// Register as app data; without it, or with every threshold `None`, `/health`
+/// only reports liveness.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HealthThresholds {
+    /// Download speed in Mbps below which the check fails.
+    pub min_download_mbps: Option<f64>,
+
+    /// Ping in milliseconds above which the check fails.
+    pub max_ping_ms: Option<f64>,
+
+    /// Time without a successful run after which the check fails, so that a wedged
+    /// scheduler gets the process restarted.
+    pub unhealthy_after: Option<Duration>,
 }
 
-/// Trait to abstract running the speedtest command.
+impl HealthThresholds {
+    /// Reads `HEALTH_MIN_DOWNLOAD_MBPS`, `HEALTH_MAX_PING_MS` and
+    /// `UNHEALTHY_AFTER_SECONDS`; unset, zero or non-numeric variables disable their
+    /// check.
+    pub fn from_env() -> Self {
+        let threshold = |var: config::EnvVar| var.parse::<f64>().filter(|value| value.is_finite());
+        HealthThresholds {
+            min_download_mbps: threshold(config::HEALTH_MIN_DOWNLOAD_MBPS),
+            max_ping_ms: threshold(config::HEALTH_MAX_PING_MS),
+            unhealthy_after: config::UNHEALTHY_AFTER_SECONDS
+                .parse::<u64>()
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+        }
+    }
+
+    /// Returns a description of the failure if `since_success`, the time since the
+    /// last successful run (see [`stats::since_last_success`]), exceeds
+    /// `unhealthy_after`.
+    pub fn stall_check(&self, since_success: Duration) -> Option<String> {
+        self.unhealthy_after
+            .filter(|limit| since_success > *limit)
+            .map(|limit| format!("no successful run for {}s, limit {}s", since_success.as_secs(), limit.as_secs()))
+    }
+
+    /// Returns a description of every threshold `result` breaches, empty if none.
+    pub fn failed_checks(&self, result: &SpeedTestResult) -> Vec<String> {
+        let mut failed = Vec::new();
+        if let Some(min) = self.min_download_mbps.filter(|min| result.download_mbps < *min) {
+            failed.push(format!("download {:.2} Mbps is below {min} Mbps", result.download_mbps));
+        }
+        if let Some(max) = self.max_ping_ms.filter(|max| result.ping_ms > *max) {
+            failed.push(format!("ping {:.2} ms is above {max} ms", result.ping_ms));
+        }
+        failed
+    }
+}
+
+/// Body of the `/health` response.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HealthResponse {
+    /// `"ok"`, or `"unhealthy"` when a [`HealthThresholds`] check fails.
+    pub status: &'static str,
+
+    /// Whether a speedtest result is cached, i.e. whether `/speed` would return 200.
+    pub has_result: bool,
+
+    /// Seconds since startup (see [`stats::uptime`]).
+    pub uptime_secs: u64,
+
+    /// The failed checks, as described by [`HealthThresholds::stall_check`] and
+    /// [`HealthThresholds::failed_checks`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed_checks: Vec<String>,
+}
+
+/// HTTP GET endpoint `/health` reports that the process is alive and, when
+/// [`HealthThresholds`] are registered, that runs succeed and the connection meets
+/// them.
 ///
-/// Allows mocking speedtest execution for testing.
-#[async_trait]
-pub trait SpeedtestRunner: Send + Sync {
-    /// Runs speedtest and returns the raw JSON string output on success.
-    async fn run_speedtest(&self) -> Result<String, String>;
+/// Returns HTTP 200 even before the first speedtest completes, so liveness probes
+/// can tell a warming-up process from a dead one; `has_result` serves readiness
+/// probes. Returns HTTP 503 with `failed_checks` when no run has succeeded within
+/// `unhealthy_after` or the cached result breaches a threshold.
+#[get("/health")]
+pub async fn health(req: HttpRequest) -> impl Responder {
+    let cached = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
+    let mut failed_checks = Vec::new();
+    if let Some(thresholds) = req.app_data::<web::Data<HealthThresholds>>() {
+        failed_checks.extend(thresholds.stall_check(stats::since_last_success()));
+        if let Some((result, _)) = cached.as_ref() {
+            failed_checks.extend(thresholds.failed_checks(result));
+        }
+    }
+    let body = HealthResponse {
+        status: if failed_checks.is_empty() { "ok" } else { "unhealthy" },
+        has_result: cached.is_some(),
+        uptime_secs: stats::uptime().as_secs(),
+        failed_checks,
+    };
+    if body.failed_checks.is_empty() {
+        HttpResponse::Ok().json(body)
+    } else {
+        HttpResponse::ServiceUnavailable().json(body)
+    }
 }
 
-/// Real speedtest runner implementation using the `speedtest-cli` binary.
-pub struct RealSpeedtestRunner;
+/// HTTP GET endpoint `/speed/raw-download` returns the download speed in bits per
+/// second as a plain-text integer, for consumers which cannot parse floats.
+///
+/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+#[get("/speed/raw-download")]
+pub async fn raw_download() -> impl Responder {
+    raw_speed_response(|result| result.download_bps)
+}
 
-#[async_trait]
-impl SpeedtestRunner for RealSpeedtestRunner {
-    async fn run_speedtest(&self) -> Result<String, String> {
-        let output = Command::new("speedtest-cli")
-            .arg("--json")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await
-            .map_err(|e| format!("Failed to run speedtest-cli: {}", e))?;
+/// HTTP GET endpoint `/speed/raw-upload` returns the upload speed in bits per second
+/// as a plain-text integer.
+///
+/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+#[get("/speed/raw-upload")]
+pub async fn raw_upload() -> impl Responder {
+    raw_speed_response(|result| result.upload_bps)
+}
 
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            Err(format!("speedtest-cli failed: {}", stderr))
+/// Header row of the CSV export, matching the columns of [`to_csv_row`].
+pub const CSV_HEADER: &str = "timestamp,download_mbps,upload_mbps,ping_ms,jitter_ms,bytes_received,bytes_sent,server_name,isp";
+
+/// Quotes `field` for CSV if it contains a comma, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Formats `result` as a CSV row in the columns of [`CSV_HEADER`], without a
+/// trailing line break. An unknown `jitter_ms` is left empty.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{to_csv_row, SpeedTestResult};
+///
+/// let mut result = SpeedTestResult { download_mbps: 94.5, ping_ms: 8.0, ..Default::default() };
+/// result.timestamp = "2025-08-07T12:34:56Z".to_string();
+/// result.client.isp = "Example, Inc.".to_string();
+/// assert_eq!(to_csv_row(&result), "2025-08-07T12:34:56Z,94.5,0,8,,0,0,,\"Example, Inc.\"");
+/// ```
+pub fn to_csv_row(result: &SpeedTestResult) -> String {
+    [
+        csv_field(&result.timestamp),
+        result.download_mbps.to_string(),
+        result.upload_mbps.to_string(),
+        result.ping_ms.to_string(),
+        result.jitter_ms.map(|jitter| jitter.to_string()).unwrap_or_default(),
+        result.bytes_received.to_string(),
+        result.bytes_sent.to_string(),
+        csv_field(&result.server.name),
+        csv_field(&result.client.isp),
+    ]
+    .join(",")
+}
+
+/// HTTP GET endpoint `/speed.csv` returns the cached result as CSV: a header line
+/// and one row (see [`to_csv_row`]), served as a `speed.csv` download.
+///
+/// Returns HTTP 503 Service Unavailable if no result is cached yet.
+#[get("/speed.csv")]
+pub async fn speed_csv() -> impl Responder {
+    match get_last_result() {
+        Some(result) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((header::CONTENT_DISPOSITION, "attachment; filename=\"speed.csv\""))
+            .body(format!("{}\n{}\n", CSV_HEADER, to_csv_row(&result))),
+        None => HttpResponse::ServiceUnavailable().body("Speedtest result not available yet."),
+    }
+}
+
+/// Content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Units of the download and upload gauges exported by `/metrics`, selected via
+/// `METRICS_UNITS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetricsUnits {
+    /// Megabits per second, as `speedtest_download_mbps` (`mbps`, the default).
+    #[default]
+    Mbps,
+
+    /// Bits per second, the Prometheus base unit, as
+    /// `speedtest_download_bits_per_second` (`bps`).
+    Bps,
+}
+
+impl MetricsUnits {
+    /// Returns the name used to select these units.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetricsUnits::Mbps => "mbps",
+            MetricsUnits::Bps => "bps",
+        }
+    }
+
+    /// Reads `METRICS_UNITS`, falling back to [`MetricsUnits::Mbps`] with a warning
+    /// on unknown values.
+    pub fn from_env() -> Self {
+        match config::METRICS_UNITS.raw() {
+            Some(s) => s.parse().unwrap_or_else(|e| {
+                warn!("{}; falling back to {}", e, MetricsUnits::default().name());
+                MetricsUnits::default()
+            }),
+            None => MetricsUnits::default(),
         }
     }
 }
 
-/// Runs the speedtest using the provided runner, parses the JSON output, and caches the result.
+impl std::str::FromStr for MetricsUnits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mbps" => Ok(MetricsUnits::Mbps),
+            "bps" => Ok(MetricsUnits::Bps),
+            other => Err(format!("unknown metrics units '{}'", other)),
+        }
+    }
+}
+
+/// Gauges exported by `/metrics`: name, help text and value, if known.
+type Gauge = (&'static str, &'static str, Option<f64>);
+
+/// The download and upload gauges in `units`.
+fn speed_gauges(result: Option<&SpeedTestResult>, units: MetricsUnits) -> [Gauge; 2] {
+    match units {
+        MetricsUnits::Mbps => [
+            ("speedtest_download_mbps", "Download speed in megabits per second.", result.map(|r| r.download_mbps)),
+            ("speedtest_upload_mbps", "Upload speed in megabits per second.", result.map(|r| r.upload_mbps)),
+        ],
+        MetricsUnits::Bps => [
+            (
+                "speedtest_download_bits_per_second",
+                "Download speed in bits per second.",
+                result.map(|r| r.download_bps),
+            ),
+            ("speedtest_upload_bits_per_second", "Upload speed in bits per second.", result.map(|r| r.upload_bps)),
+        ],
+    }
+}
+
+fn prometheus_gauges(result: Option<&SpeedTestResult>, units: MetricsUnits) -> [Gauge; 10] {
+    let [download, upload] = speed_gauges(result, units);
+    [
+        download,
+        upload,
+        ("speedtest_ping_ms", "Ping to the speedtest server in milliseconds.", result.map(|r| r.ping_ms)),
+        ("speedtest_jitter_ms", "Ping jitter in milliseconds, if measured.", result.and_then(|r| r.jitter_ms)),
+        (
+            "speedtest_packet_loss_percent",
+            "Percentage of packets lost, if measured.",
+            result.and_then(|r| r.packet_loss),
+        ),
+        (
+            "speedtest_bufferbloat_ms",
+            "Increase of the loaded latency over the idle ping in milliseconds, if measured.",
+            result.and_then(|r| r.bufferbloat_ms),
+        ),
+        (
+            "speedtest_bufferbloat_grade",
+            "Bufferbloat grade from 1 (A) to 5 (F), if measured.",
+            result.and_then(|r| r.bufferbloat_grade).map(|grade| f64::from(grade.number())),
+        ),
+        ("speedtest_bytes_received", "Bytes received during the speedtest.", result.map(|r| r.bytes_received as f64)),
+        ("speedtest_bytes_sent", "Bytes sent during the speedtest.", result.map(|r| r.bytes_sent as f64)),
+        (
+            "speedtest_last_run_timestamp_seconds",
+            "Unix time at which the speedtest ran.",
+            result
+                .and_then(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).ok())
+                .map(|at| at.timestamp() as f64),
+        ),
+    ]
+}
+
+/// Escapes a Prometheus label value.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Renders the `{node="...",tag="..."}` label set of `result`, empty if there are no labels.
+fn prometheus_labels(result: &SpeedTestResult) -> String {
+    let mut labels = Vec::new();
+    if !result.node.is_empty() {
+        labels.push(format!("node=\"{}\"", escape_label_value(&result.node)));
+    }
+    labels.extend(
+        result
+            .tags
+            .iter()
+            .filter(|(key, _)| key.as_str() != "node")
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape_label_value(value))),
+    );
+    if labels.is_empty() { String::new() } else { format!("{{{}}}", labels.join(",")) }
+}
+
+fn render_metrics(result: Option<&SpeedTestResult>, units: MetricsUnits) -> String {
+    let labels = result.map(prometheus_labels).unwrap_or_default();
+    let mut out = String::new();
+    for (name, help, value) in prometheus_gauges(result, units) {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n", name, help, name));
+        if let Some(value) = value {
+            out.push_str(&format!("{}{} {}\n", name, labels, value));
+        }
+    }
+    out
+}
+
+/// Renders `result` in the Prometheus text exposition format.
 ///
-/// Logs errors to stderr if the command or parsing fails.
-pub async fn run_speedtest_and_cache_with_runner(runner: &dyn SpeedtestRunner) {
-    match runner.run_speedtest().await {
-        Ok(stdout) => match serde_json::from_str::<SpeedTestResponse>(&stdout) {
-            Ok(data) => {
-                let result = SpeedTestResult {
-                    bytes_received: data.bytes_received,
-                    bytes_sent: data.bytes_sent,
-                    download_bps: data.download,
-                    upload_bps: data.upload,
-                    download_mbps: data.download / 1_000_000.0,
-                    upload_mbps: data.upload / 1_000_000.0,
-                    ping_ms: data.ping,
-                    client: data.client,
-                    server: data.server,
-                    share: data.share,
-                    timestamp: data.timestamp,
-                };
-
-                let mut cache = LAST_RESULT.lock().unwrap();
-                *cache = Some((result.clone(), Instant::now()));
-                println!("Speedtest updated at {}", result.timestamp);
-            }
-            Err(e) => eprintln!("Failed to parse speedtest-cli JSON: {}", e),
-        },
-        Err(e) => eprintln!("{}", e),
+/// Every gauge is labelled with the result's `node` and its [`tags`]. The
+/// `speedtest_last_run_timestamp_seconds` sample is left out if the timestamp is
+/// not RFC 3339.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{render_prometheus, SpeedTestResult};
+///
+/// let result = SpeedTestResult { download_mbps: 94.5, node: "pi".to_string(), ..Default::default() };
+/// let text = render_prometheus(&result);
+/// assert!(text.contains("# TYPE speedtest_download_mbps gauge\n"));
+/// assert!(text.contains("speedtest_download_mbps{node=\"pi\"} 94.5\n"));
+/// ```
+pub fn render_prometheus(result: &SpeedTestResult) -> String {
+    render_metrics(Some(result), MetricsUnits::Mbps)
+}
+
+/// Like [`render_prometheus`], with the download and upload gauges in `units`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{render_prometheus_in, MetricsUnits, SpeedTestResult};
+///
+/// let result = SpeedTestResult { download_bps: 94_500_000.0, ..Default::default() };
+/// let text = render_prometheus_in(&result, MetricsUnits::Bps);
+/// assert!(text.contains("speedtest_download_bits_per_second 94500000\n"));
+/// assert!(!text.contains("speedtest_download_mbps"));
+/// ```
+pub fn render_prometheus_in(result: &SpeedTestResult, units: MetricsUnits) -> String {
+    render_metrics(Some(result), units)
+}
+
+/// HTTP GET endpoint `/metrics` exposes the cached result to Prometheus.
+///
+/// Without a cached result only the `# HELP` and `# TYPE` lines are returned, still
+/// with HTTP 200, so the scrape target is not reported as down while warming up.
+/// The download and upload gauges are in the units selected by `METRICS_UNITS`.
+/// The `speedtest_run_duration_seconds` histogram accumulates the durations of all
+/// cached runs since startup (see [`stats::render_run_durations`]).
+#[get("/metrics")]
+pub async fn metrics() -> impl Responder {
+    let mut body = {
+        let cache = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
+        render_metrics(cache.as_ref().map(|(result, _)| result), MetricsUnits::from_env())
+    };
+    body.push_str(&stats::render_run_durations());
+    HttpResponse::Ok().content_type(PROMETHEUS_CONTENT_TYPE).body(body)
+}
+
+/// Query parameters accepted by [`history_endpoint`].
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryQuery {
+    /// Maximum number of results returned.
+    pub limit: Option<usize>,
+}
+
+/// HTTP GET endpoint `/history` returns the retained results as a JSON array,
+/// newest first.
+///
+/// `?limit=N` caps the number returned; a non-numeric limit is rejected with
+/// HTTP 400. An empty history is returned as `[]` with HTTP 200.
+#[get("/history")]
+pub async fn history_endpoint(query: web::Query<HistoryQuery>) -> impl Responder {
+    let mut results = get_history();
+    results.reverse();
+    if let Some(limit) = query.limit {
+        results.truncate(limit);
     }
+    HttpResponse::Ok().json(results)
 }
 
-/// Background async task which schedules periodic speedtest runs.
+/// HTTP GET endpoint `/history.csv` returns the retained results as CSV, newest
+/// first like [`history_endpoint`]: the [`CSV_HEADER`] line and one row per result
+/// (see [`to_csv_row`]), served as a `history.csv` download.
 ///
-/// The interval between runs is configured by the `INTERVAL_MINUTES` env variable.
-pub async fn spawn_speedtest_scheduler() {
-    let interval = min_frequency_duration();
-    let runner = RealSpeedtestRunner;
+/// `?limit=N` caps the number of rows. The rows are formatted as the body is
+/// streamed rather than buffered up front. An empty history gives just the header.
+#[get("/history.csv")]
+pub async fn history_csv(query: web::Query<HistoryQuery>) -> impl Responder {
+    let mut results = get_history();
+    results.reverse();
+    if let Some(limit) = query.limit {
+        results.truncate(limit);
+    }
+    let lines = std::iter::once(CSV_HEADER.to_string())
+        .chain(results.into_iter().map(|result| to_csv_row(&result)))
+        .map(|line| Ok::<_, std::convert::Infallible>(web::Bytes::from(line + "\n")));
+    let body = futures::stream::iter(lines);
+    HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((header::CONTENT_DISPOSITION, "attachment; filename=\"history.csv\""))
+        .streaming(body)
+}
 
-    // Run one immediately on startup
-    run_speedtest_and_cache_with_runner(&runner).await;
+/// Empties the history and, in builds with the `sqlite` feature, the results table
+/// of the database at `DB_PATH`.
+///
+/// Clearing an empty history is a no-op. The `RESULTS_LOG` file is append-only and
+/// left alone.
+pub async fn clear_history() {
+    HISTORY.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    #[cfg(feature = "sqlite")]
+    if let Err(e) = tokio::task::spawn_blocking(db::clear_results).await {
+        error!("Clearing the database panicked: {}", e);
+    }
+}
 
-    let mut ticker = time::interval(interval);
-    loop {
-        ticker.tick().await;
-        run_speedtest_and_cache_with_runner(&runner).await;
+/// HTTP POST endpoint `/history/clear` empties the history (see [`clear_history`]).
+///
+/// Returns HTTP 204 No Content. Responds HTTP 404 Not Found unless
+/// `HISTORY_CLEAR_TOKEN` is set, and HTTP 401 Unauthorized unless the request
+/// carries `Authorization: Bearer <HISTORY_CLEAR_TOKEN>`.
+#[post("/history/clear")]
+pub async fn clear_history_endpoint(req: HttpRequest) -> impl Responder {
+    let Some(token) = config::HISTORY_CLEAR_TOKEN.raw().filter(|t| !t.is_empty()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Some(unauthorized) = access::require_bearer(&req, &token) {
+        return unauthorized;
+    }
+    clear_history().await;
+    info!("History cleared");
+    HttpResponse::NoContent().finish()
+}
+
+/// Reads the environment variable `INTERVAL_MINUTES` or returns a default of 10 minutes.
+///
+/// The duration represents how frequently speedtests are run. An invalid
+/// configuration falls back to the default; `main` refuses to start with one (see
+/// [`config::Config::from_env`]).
+pub fn min_frequency_duration() -> Duration {
+    config::Config::from_env().unwrap_or_default().interval
+}
+
+/// Returns `value` as a percentage of `total`.
+///
+/// Returns `None` when `total` is not a positive, finite number.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::percent_of;
+/// assert_eq!(percent_of(25.0, 200.0), Some(12.5));
+/// assert_eq!(percent_of(25.0, 0.0), None);
+/// ```
+pub fn percent_of(value: f64, total: f64) -> Option<f64> {
+    (total.is_finite() && total > 0.0).then(|| value / total * 100.0)
+}
+
+/// Resolves the node name from a configured value, falling back to `hostname`.
+///
+/// Blank values are ignored at each step; `"unknown"` is returned when neither is
+/// available.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::resolve_node_name;
+/// assert_eq!(resolve_node_name(Some("probe-1".to_string()), || Some("host".to_string())), "probe-1");
+/// assert_eq!(resolve_node_name(None, || Some("host\n".to_string())), "host");
+/// assert_eq!(resolve_node_name(Some(" ".to_string()), || None), "unknown");
+/// ```
+pub fn resolve_node_name(configured: Option<String>, hostname: impl FnOnce() -> Option<String>) -> String {
+    let non_blank = |s: String| {
+        let trimmed = s.trim();
+        (!trimmed.is_empty()).then(|| trimmed.to_string())
+    };
+    configured
+        .and_then(non_blank)
+        .or_else(|| hostname().and_then(non_blank))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Returns the system hostname from `HOSTNAME`, `/proc/sys/kernel/hostname` or `/etc/hostname`.
+pub fn system_hostname() -> Option<String> {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+}
+
+/// Reads the environment variable `NODE_NAME` or falls back to the system hostname.
+pub fn node_name() -> String {
+    resolve_node_name(config::NODE_NAME.raw(), system_hostname)
+}
+
+/// Parses a boolean flag value, accepting `true`/`1`/`yes`/`on` (case-insensitive).
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::parse_flag;
+/// assert!(parse_flag("TRUE"));
+/// assert!(parse_flag("1"));
+/// assert!(!parse_flag("no"));
+/// ```
+pub fn parse_flag(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "true" | "1" | "yes" | "on")
+}
+
+/// Reads a boolean flag from the environment variable `name`, defaulting to `false`.
+pub fn env_flag(name: &str) -> bool {
+    env::var(name).map(|s| parse_flag(&s)).unwrap_or(false)
+}
+
+/// Reads the environment variable `REJECT_INCOMPLETE` as a boolean flag.
+///
+/// When enabled, results failing [`SpeedTestResult::is_complete`] are discarded
+/// instead of being cached. Defaults to `false`.
+pub fn reject_incomplete() -> bool {
+    config::REJECT_INCOMPLETE.flag()
+}
+
+/// Error returned when a speedtest could not be run, or its result could not be
+/// cached by [`run_speedtest_and_cache_with_runner`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpeedtestError {
+    /// The speedtest command could not be started or exited unsuccessfully.
+    CommandFailed(String),
+
+    /// The speedtest command produced no output for the given period and was killed.
+    Stalled(Duration),
+
+    /// The speedtest run took longer than the given period and was killed.
+    TimedOut(Duration),
+
+    /// The named speedtest binary is not installed or not on `PATH`.
+    BinaryNotFound(String),
+
+    /// The speedtest output could not be parsed into a result.
+    ParseFailed(String),
+
+    /// The result was implausible, incomplete or rejected by the validator.
+    Rejected(String),
+
+    /// The result could not be written into the cache.
+    CacheFailed(CacheError),
+}
+
+impl SpeedtestError {
+    /// Returns whether running the speedtest again may succeed.
+    ///
+    /// Failed, stalled and rejected runs are usually caused by transient network or
+    /// server problems and are retryable; a missing binary is fatal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// use speedtest_statuspage::SpeedtestError;
+    ///
+    /// assert!(SpeedtestError::Stalled(Duration::from_secs(60)).is_retryable());
+    /// assert!(!SpeedtestError::BinaryNotFound("fast".to_string()).is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SpeedtestError::BinaryNotFound(_) => false,
+            SpeedtestError::CommandFailed(_)
+            | SpeedtestError::Stalled(_)
+            | SpeedtestError::TimedOut(_)
+            | SpeedtestError::ParseFailed(_)
+            | SpeedtestError::Rejected(_)
+            | SpeedtestError::CacheFailed(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for SpeedtestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpeedtestError::CommandFailed(msg) => write!(f, "{}", msg),
+            SpeedtestError::Stalled(period) => {
+                write!(f, "speedtest produced no output for {}s and was killed", period.as_secs())
+            }
+            SpeedtestError::TimedOut(limit) => {
+                write!(f, "speedtest did not finish within {}s and was killed", limit.as_secs())
+            }
+            SpeedtestError::BinaryNotFound(binary) => write!(f, "{} not found; is it installed and on PATH?", binary),
+            SpeedtestError::ParseFailed(msg) => write!(f, "Failed to parse speedtest output: {}", msg),
+            SpeedtestError::Rejected(reason) => write!(f, "speedtest result rejected: {}", reason),
+            SpeedtestError::CacheFailed(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SpeedtestError {}
+
+/// Per-run configuration handed to a [`SpeedtestRunner`].
+///
+/// Built once from the environment by [`spawn_speedtest_scheduler`] so that runners
+/// never read the process environment themselves, which keeps them testable.
+///
+/// | Field         | Environment variable          |
+/// |---------------|-------------------------------|
+/// | `server_ids`  | `SPEEDTEST_SERVER_IDS` (comma-separated) |
+/// | `timeout_secs`| `SPEEDTEST_CLI_TIMEOUT_SECS`  |
+/// | `proxy`       | `SPEEDTEST_PROXY`             |
+/// | `secure`      | `SPEEDTEST_SECURE`            |
+/// | `no_download` | `SPEEDTEST_NO_DOWNLOAD`       |
+/// | `no_upload`   | `SPEEDTEST_NO_UPLOAD`         |
+/// | `extra_args`  | `SPEEDTEST_EXTRA_ARGS` (whitespace-separated) |
+/// | `stall_timeout` | `CLI_STALL_SECONDS`         |
+/// | `run_timeout` | `SPEEDTEST_TIMEOUT_SECS`      |
+/// | `retries`     | `SPEEDTEST_RETRIES`           |
+/// | `retry_base`  | `SPEEDTEST_RETRY_BASE_MS`     |
+/// | `retry_jitter`| `RETRY_JITTER`                |
+/// | `run_budget`  | `RUN_BUDGET_SECONDS`          |
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnerConfig {
+    /// Server ids to test against, passed as repeated `--server` arguments.
+    pub server_ids: Vec<String>,
+
+    /// HTTP timeout in seconds passed to the CLI via `--timeout`.
+    pub timeout_secs: Option<u64>,
+
+    /// Proxy URL exported to the CLI process as `HTTP_PROXY`/`HTTPS_PROXY`.
+    pub proxy: Option<String>,
+
+    /// Use HTTPS when talking to the speedtest servers (`--secure`).
+    pub secure: bool,
+
+    /// Skip the download phase (`--no-download`).
+    pub no_download: bool,
+
+    /// Skip the upload phase (`--no-upload`).
+    pub no_upload: bool,
+
+    /// Additional arguments appended verbatim to the command line.
+    pub extra_args: Vec<String>,
+
+    /// Kill the CLI if it writes nothing to stdout for this long (see [`watchdog`]).
+    pub stall_timeout: Option<Duration>,
+
+    /// Kill the CLI if the whole run takes longer than this (see [`run_timed`]).
+    pub run_timeout: Option<Duration>,
+
+    /// Times a run failing with a retryable error is retried (see [`run_with_retries`]).
+    pub retries: u32,
+
+    /// Delay before the first retry, doubled for each further retry.
+    pub retry_base: Duration,
+
+    /// Wait a random part of each retry delay (see [`jittered_delay`]).
+    pub retry_jitter: bool,
+
+    /// Total time all attempts of one run may take (see [`run_within_budget`]).
+    pub run_budget: Option<Duration>,
+}
+
+impl RunnerConfig {
+    /// Builds the configuration from the process environment.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| env::var(key).ok())
+    }
+
+    /// Builds the configuration using `lookup` to resolve each variable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use speedtest_statuspage::RunnerConfig;
+    /// let cfg = RunnerConfig::from_lookup(|key| match key {
+    ///     "SPEEDTEST_SERVER_IDS" => Some("1234, 5678".to_string()),
+    ///     "SPEEDTEST_SECURE" => Some("true".to_string()),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(cfg.server_ids, vec!["1234", "5678"]);
+    /// assert!(cfg.secure);
+    /// ```
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let flag = |key: &str| lookup(key).map(|s| parse_flag(&s)).unwrap_or(false);
+        let with_default = |var: &config::EnvVar| lookup(var.name).or_else(|| var.default.map(String::from));
+        RunnerConfig {
+            server_ids: lookup(config::SPEEDTEST_SERVER_IDS.name)
+                .map(|s| {
+                    s.split(',')
+                        .map(str::trim)
+                        .filter(|id| !id.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            timeout_secs: lookup(config::SPEEDTEST_CLI_TIMEOUT_SECS.name).and_then(|s| s.trim().parse().ok()),
+            proxy: lookup(config::SPEEDTEST_PROXY.name).filter(|s| !s.trim().is_empty()),
+            secure: flag(config::SPEEDTEST_SECURE.name),
+            no_download: flag(config::SPEEDTEST_NO_DOWNLOAD.name),
+            no_upload: flag(config::SPEEDTEST_NO_UPLOAD.name),
+            extra_args: lookup(config::SPEEDTEST_EXTRA_ARGS.name)
+                .map(|s| s.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+            stall_timeout: lookup(config::CLI_STALL_SECONDS.name)
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+            run_timeout: with_default(&config::SPEEDTEST_TIMEOUT_SECS)
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+            retries: with_default(&config::SPEEDTEST_RETRIES)
+                .and_then(|s| s.trim().parse().ok())
+                .unwrap_or(0),
+            retry_base: with_default(&config::SPEEDTEST_RETRY_BASE_MS)
+                .and_then(|s| s.trim().parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or_default(),
+            retry_jitter: flag(config::RETRY_JITTER.name),
+            run_budget: lookup(config::RUN_BUDGET_SECONDS.name)
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .filter(|secs| *secs > 0)
+                .map(Duration::from_secs),
+        }
+    }
+
+    /// Builds the `speedtest-cli` argument list for this configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use speedtest_statuspage::RunnerConfig;
+    /// let cfg = RunnerConfig {
+    ///     server_ids: vec!["1234".to_string()],
+    ///     no_upload: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(cfg.build_args(), vec!["--json", "--server", "1234", "--no-upload"]);
+    /// ```
+    pub fn build_args(&self) -> Vec<String> {
+        let mut args = vec!["--json".to_string()];
+        for id in &self.server_ids {
+            args.push("--server".to_string());
+            args.push(id.clone());
+        }
+        if let Some(secs) = self.timeout_secs {
+            args.push("--timeout".to_string());
+            args.push(secs.to_string());
+        }
+        if self.secure {
+            args.push("--secure".to_string());
+        }
+        if self.no_download {
+            args.push("--no-download".to_string());
+        }
+        if self.no_upload {
+            args.push("--no-upload".to_string());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
+
+/// Trait to abstract running the speedtest command.
+///
+/// Allows mocking speedtest execution for testing.
+#[async_trait]
+pub trait SpeedtestRunner: Send + Sync {
+    /// Runs speedtest with the given configuration and returns the raw JSON string output on success.
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError>;
+
+    /// Parses the raw output returned by [`run_speedtest`](Self::run_speedtest).
+    ///
+    /// Defaults to the `speedtest-cli --json` schema ([`SpeedTestResponse`]).
+    fn parse_output(&self, raw: &str) -> Result<SpeedTestResult, String> {
+        serde_json::from_str::<SpeedTestResponse>(raw)
+            .map(SpeedTestResult::from)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl<R: SpeedtestRunner + ?Sized> SpeedtestRunner for Box<R> {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        (**self).run_speedtest(cfg).await
+    }
+
+    fn parse_output(&self, raw: &str) -> Result<SpeedTestResult, String> {
+        (**self).parse_output(raw)
+    }
+}
+
+/// The speedtest backends which can be selected via `SPEEDTEST_BACKEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// The Python `speedtest-cli` binary (`legacy`, the default).
+    #[default]
+    Legacy,
+
+    /// The Netflix Fast.com `fast` binary from `fast-cli` (`fast`).
+    Fast,
+
+    /// The official Ookla `speedtest` binary (`ookla`).
+    Ookla,
+}
+
+impl Backend {
+    /// Returns the name used to select this backend.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::Legacy => "legacy",
+            Backend::Fast => "fast",
+            Backend::Ookla => "ookla",
+        }
+    }
+
+    /// Returns the executable invoked by this backend.
+    pub fn binary(&self) -> &'static str {
+        match self {
+            Backend::Legacy => "speedtest-cli",
+            Backend::Fast => "fast",
+            Backend::Ookla => "speedtest",
+        }
+    }
+
+    /// Creates the runner implementing this backend.
+    pub fn runner(&self) -> Box<dyn SpeedtestRunner> {
+        match self {
+            Backend::Legacy => Box::new(RealSpeedtestRunner),
+            Backend::Fast => Box::new(FastComRunner),
+            Backend::Ookla => Box::new(ookla::OoklaSpeedtestRunner),
+        }
+    }
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "legacy" | "speedtest-cli" => Ok(Backend::Legacy),
+            "fast" | "fast-cli" => Ok(Backend::Fast),
+            "ookla" | "speedtest" => Ok(Backend::Ookla),
+            other => Err(format!("unknown speedtest backend '{}'", other)),
+        }
+    }
+}
+
+/// Reads the environment variable `SPEEDTEST_BACKEND` or returns [`Backend::Legacy`].
+///
+/// Unknown values are logged and fall back to the default.
+pub fn speedtest_backend() -> Backend {
+    match config::SPEEDTEST_BACKEND.raw() {
+        Some(s) => s.parse().unwrap_or_else(|e| {
+            warn!("{}; falling back to {}", e, Backend::default().name());
+            Backend::default()
+        }),
+        None => Backend::default(),
+    }
+}
+
+/// Real speedtest runner implementation using the `speedtest-cli` binary.
+pub struct RealSpeedtestRunner;
+
+#[async_trait]
+impl SpeedtestRunner for RealSpeedtestRunner {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        let mut command = Command::new("speedtest-cli");
+        command.args(cfg.build_args());
+        if let Some(proxy) = &cfg.proxy {
+            command.env("HTTP_PROXY", proxy).env("HTTPS_PROXY", proxy);
+        }
+
+        let output = watchdog::run_watched(&mut command, "speedtest-cli", cfg.stall_timeout).await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(SpeedtestError::CommandFailed(format!("speedtest-cli failed: {}", stderr)))
+        }
+    }
+}
+
+/// Runs `runner` once, giving up after [`RunnerConfig::run_timeout`].
+///
+/// On timeout the run is dropped, which kills the speedtest process, so that a hung
+/// CLI cannot stall the scheduler.
+///
+/// # Errors
+///
+/// Returns the runner's error, or [`SpeedtestError::TimedOut`] if the run took too long.
+pub async fn run_timed(runner: &dyn SpeedtestRunner, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+    run_limited(runner, cfg, cfg.run_timeout).await
+}
+
+/// Runs `runner` once, giving up after `limit`.
+async fn run_limited(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    limit: Option<Duration>,
+) -> Result<String, SpeedtestError> {
+    match limit {
+        Some(limit) => time::timeout(limit, runner.run_speedtest(cfg))
+            .await
+            .unwrap_or(Err(SpeedtestError::TimedOut(limit))),
+        None => runner.run_speedtest(cfg).await,
+    }
+}
+
+/// Returns the delay before retry number `retry` (starting at 1): `base` doubled for
+/// every earlier retry.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use speedtest_statuspage::retry_delay;
+/// let base = Duration::from_secs(2);
+/// assert_eq!(retry_delay(base, 1), Duration::from_secs(2));
+/// assert_eq!(retry_delay(base, 3), Duration::from_secs(8));
+/// ```
+pub fn retry_delay(base: Duration, retry: u32) -> Duration {
+    base.saturating_mul(1 << retry.saturating_sub(1).min(16))
+}
+
+/// Applies full jitter to `backoff`: scales it by `random`, a value in `[0, 1)`
+/// drawn by the caller, so the result lies in `[0, backoff]`.
+///
+/// Out-of-range values are clamped, and NaN counts as zero.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use speedtest_statuspage::jittered_delay;
+/// let backoff = Duration::from_secs(8);
+/// assert_eq!(jittered_delay(backoff, 0.25), Duration::from_secs(2));
+/// assert_eq!(jittered_delay(backoff, 7.0), backoff);
+/// ```
+pub fn jittered_delay(backoff: Duration, random: f64) -> Duration {
+    let random = if random.is_nan() { 0.0 } else { random.clamp(0.0, 1.0) };
+    backoff.mul_f64(random)
+}
+
+/// Returns a random value in `[0, 1)` for [`jittered_delay`].
+///
+/// Drawn from the randomly seeded hasher of the standard library, which is plenty
+/// to spread retries out without pulling in an RNG crate.
+fn jitter_sample() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+    let bits = std::collections::hash_map::RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Runs `runner` up to `attempts` times until it succeeds.
+///
+/// Only errors which are [retryable](SpeedtestError::is_retryable) are retried; a
+/// fatal error is returned at once. Each retried failure is logged, and retries
+/// back off exponentially from [`RunnerConfig::retry_base`] (see [`retry_delay`]),
+/// with [full jitter](jittered_delay) if [`RunnerConfig::retry_jitter`] is set.
+/// Every attempt is limited by [`run_timed`], and all of them together by
+/// [`RunnerConfig::run_budget`] (see [`run_within_budget`]).
+///
+/// # Errors
+///
+/// Returns the first fatal error, or the last error once the attempts or the
+/// budget are used up.
+pub async fn run_with_retries(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    attempts: u32,
+) -> Result<String, SpeedtestError> {
+    run_within_budget(runner, cfg, attempts, Instant::now).await
+}
+
+/// Like [`run_with_retries`], reading the time from `now` to track
+/// [`RunnerConfig::run_budget`].
+///
+/// The budget starts with the first attempt. Each attempt is cut short once the
+/// budget runs out, and no retry is started whose delay would reach past it; the
+/// last error is returned instead, so a run never spills into the next one.
+///
+/// # Errors
+///
+/// As [`run_with_retries`].
+pub async fn run_within_budget(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    attempts: u32,
+    now: impl Fn() -> Instant,
+) -> Result<String, SpeedtestError> {
+    run_attempts(runner, cfg, attempts, now).await.map(|(stdout, _)| stdout)
+}
+
+/// Body of [`run_within_budget`], also returning how long the successful attempt
+/// took, excluding the failed attempts and retry delays before it.
+async fn run_attempts(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    attempts: u32,
+    now: impl Fn() -> Instant,
+) -> Result<(String, Duration), SpeedtestError> {
+    let deadline = cfg.run_budget.map(|budget| now() + budget);
+    let mut attempt = 1;
+    loop {
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(now()));
+        let limit = match (cfg.run_timeout, remaining) {
+            (Some(timeout), Some(remaining)) => Some(timeout.min(remaining)),
+            (timeout, remaining) => timeout.or(remaining),
+        };
+        let attempt_started = now();
+        match run_limited(runner, cfg, limit).await {
+            Ok(stdout) => return Ok((stdout, now().saturating_duration_since(attempt_started))),
+            Err(e) if e.is_retryable() && attempt < attempts => {
+                let mut delay = retry_delay(cfg.retry_base, attempt);
+                if cfg.retry_jitter {
+                    delay = jittered_delay(delay, jitter_sample());
+                }
+                if deadline.is_some_and(|deadline| now() + delay >= deadline) {
+                    warn!(
+                        "Speedtest attempt {}/{} failed: {}; run budget of {}s used up, not retrying",
+                        attempt,
+                        attempts,
+                        e,
+                        cfg.run_budget.unwrap_or_default().as_secs()
+                    );
+                    return Err(e);
+                }
+                warn!(
+                    "Speedtest attempt {}/{} failed: {}; retrying in {}ms",
+                    attempt,
+                    attempts,
+                    e,
+                    delay.as_millis()
+                );
+                time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// How a failed run should be logged, as decided by [`failure_log_decision`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureLog {
+    /// Log the failure in full.
+    Full,
+
+    /// Log a "still failing (N times)" summary.
+    Summary,
+
+    /// Do not log this occurrence.
+    Suppressed,
+}
+
+/// Decides how to log the `consecutive`-th identical failure in a row.
+///
+/// The first occurrence is always logged in full; afterwards only every
+/// `log_every`-th occurrence is logged as a summary. A `log_every` of `0` or `1`
+/// disables suppression.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::{failure_log_decision, FailureLog};
+/// assert_eq!(failure_log_decision(1, 10), FailureLog::Full);
+/// assert_eq!(failure_log_decision(2, 10), FailureLog::Suppressed);
+/// assert_eq!(failure_log_decision(10, 10), FailureLog::Summary);
+/// ```
+pub fn failure_log_decision(consecutive: u64, log_every: u64) -> FailureLog {
+    if consecutive <= 1 || log_every <= 1 {
+        FailureLog::Full
+    } else if consecutive.is_multiple_of(log_every) {
+        FailureLog::Summary
+    } else {
+        FailureLog::Suppressed
+    }
+}
+
+/// Reads the environment variable `FAILURE_LOG_EVERY` or returns a default of 10.
+pub fn failure_log_every() -> u64 {
+    config::FAILURE_LOG_EVERY.get()
+}
+
+/// The current streak of identical failures: its length and the failure message.
+static FAILURE_STREAK: Lazy<Mutex<(u64, String)>> = Lazy::new(|| Mutex::new((0, String::new())));
+
+/// Returns the number of consecutive identical failures since the last success.
+pub fn consecutive_failures() -> u64 {
+    FAILURE_STREAK.lock().unwrap_or_else(|e| e.into_inner()).0
+}
+
+/// Records a failure and logs it according to [`failure_log_decision`].
+///
+/// A failure with a different message than the previous one starts a new streak.
+fn log_failure(message: String) {
+    let count = {
+        let mut streak = FAILURE_STREAK.lock().unwrap_or_else(|e| e.into_inner());
+        if streak.1 == message {
+            streak.0 += 1;
+        } else {
+            *streak = (1, message.clone());
+        }
+        streak.0
+    };
+    match failure_log_decision(count, failure_log_every()) {
+        FailureLog::Full => error!("{}", message),
+        FailureLog::Summary => error!("{} (still failing, {} times)", message, count),
+        FailureLog::Suppressed => {}
+    }
+}
+
+/// Ends the current failure streak, logging how long it lasted.
+fn reset_failures() {
+    let mut streak = FAILURE_STREAK.lock().unwrap_or_else(|e| e.into_inner());
+    if streak.0 > 1 {
+        info!("Speedtest recovered after {} consecutive failures", streak.0);
+    }
+    *streak = (0, String::new());
+}
+
+/// Runs the speedtest using the provided runner, parses the JSON output, and caches the result.
+///
+/// Logs errors if the command or parsing fails; repeated identical failures
+/// are rate-limited by `FAILURE_LOG_EVERY`. Physically implausible results (see
+/// [`SpeedTestResult::is_plausible`]) are always discarded, and incomplete results are
+/// discarded when the `REJECT_INCOMPLETE` flag is set, as are results rejected by the
+/// validator registered with [`set_result_validator`]. The outcome is sent to the configured
+/// notification channels (see [`notify::configured_notifiers`]).
+///
+/// Returns the newly cached result, or the reason the run did not produce one. Every
+/// run is counted in [`stats`].
+pub async fn run_speedtest_and_cache_with_runner(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+) -> Result<SpeedTestResult, SpeedtestError> {
+    run_speedtest_and_cache_as(runner, cfg, Origin::Scheduled).await
+}
+
+/// Like [`run_speedtest_and_cache_with_runner`], stamping the result with `origin`.
+pub async fn run_speedtest_and_cache_as(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    origin: Origin,
+) -> Result<SpeedTestResult, SpeedtestError> {
+    let outcome = run_and_cache(runner, cfg, origin).await;
+    stats::record_run(outcome.is_ok());
+    outcome
+}
+
+/// Body of [`run_speedtest_and_cache_as`].
+async fn run_and_cache(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    origin: Origin,
+) -> Result<SpeedTestResult, SpeedtestError> {
+    match run_attempts(runner, cfg, cfg.retries.saturating_add(1), Instant::now).await {
+        Ok((stdout, took)) => match runner.parse_output(&stdout) {
+            Ok(mut result) => {
+                result.test_duration_ms = Some(took.as_millis().try_into().unwrap_or(u64::MAX));
+                result.node = node_name();
+                result.tags = tags::result_tags();
+                result.schema_version = SCHEMA_VERSION;
+                result.origin = origin;
+                if let Some(nic_speed) = nic::nic_speed_mbps() {
+                    result.set_nic_speed(nic_speed);
+                }
+                result.set_bufferbloat(&config::BUFFERBLOAT_THRESHOLDS.get());
+                metadata::collect_metadata(&mut result).await;
+                if let Some(target) = ping::ping_target() {
+                    ping::record_external_ping(&mut result, ping::ProbeMethod::from_env().probe().as_ref(), &target).await;
+                }
+
+                if !result.is_plausible() {
+                    error!(
+                        "Rejecting implausible speedtest result from {} (download {} bps, upload {} bps): \
+                         this is almost certainly a unit-conversion bug in the backend",
+                        result.timestamp, result.download_bps, result.upload_bps
+                    );
+                    return Err(SpeedtestError::Rejected("implausible result".to_string()));
+                }
+
+                if reject_incomplete() && !result.is_complete() {
+                    warn!("Discarding incomplete speedtest result from {}", result.timestamp);
+                    return Err(SpeedtestError::Rejected("incomplete result".to_string()));
+                }
+
+                if let Err(reason) = validate_result(&result) {
+                    warn!(
+                        "Discarding speedtest result from {} rejected by validator: {}",
+                        result.timestamp, reason
+                    );
+                    return Err(SpeedtestError::Rejected(reason));
+                }
+
+                reset_failures();
+                let timestamp = result.timestamp.clone();
+                match store_last_result(result.clone()) {
+                    Ok(()) => {
+                        info!(
+                            timestamp = %timestamp,
+                            download_mbps = result.download_mbps,
+                            upload_mbps = result.upload_mbps,
+                            ping_ms = result.ping_ms,
+                            server = %result.server.name,
+                            "Speedtest updated"
+                        );
+                        stats::record_bytes(result.bytes_received.saturating_add(result.bytes_sent));
+                        stats::record_run_duration(took);
+                        push_history(result.clone());
+                        // File writes, the log fsync and SQLite all block, so keep them off the executor
+                        let stored = result.clone();
+                        let persisted = tokio::task::spawn_blocking(move || {
+                            if let Some(path) = snapshot::snapshot_path() {
+                                snapshot::persist_snapshot(&path, &stored);
+                            }
+                            results_log::record_result(&stored);
+                            #[cfg(feature = "sqlite")]
+                            db::record_result(&stored);
+                        });
+                        if let Err(e) = persisted.await {
+                            error!("Persisting the speedtest result panicked: {}", e);
+                        }
+                        notify::dispatch(notify::AlertEvent::RunSucceeded(Box::new(result.clone())));
+                        Ok(result)
+                    }
+                    Err(e) => {
+                        error!("Failed to cache speedtest result from {}: {}", timestamp, e);
+                        Err(SpeedtestError::CacheFailed(e))
+                    }
+                }
+            }
+            Err(e) => {
+                let err = SpeedtestError::ParseFailed(e.to_string());
+                log_failure(err.to_string());
+                notify::dispatch(notify::AlertEvent::RunFailed(err.to_string()));
+                Err(err)
+            }
+        },
+        Err(e) => {
+            log_failure(e.to_string());
+            notify::dispatch(notify::AlertEvent::RunFailed(e.to_string()));
+            Err(e)
+        }
+    }
+}
+
+/// Held for the duration of every speedtest run, so scheduled runs and runs
+/// triggered with `POST /run` never overlap.
+pub static RUN_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Number of scheduled runs which panicked and were recovered from.
+static SCHEDULER_PANICS: AtomicU64 = AtomicU64::new(0);
+
+/// Returns how many scheduled runs have panicked since startup.
+pub fn scheduler_panic_count() -> u64 {
+    SCHEDULER_PANICS.load(Ordering::Relaxed)
+}
+
+/// Runs a single speedtest, catching a panic so that the caller can carry on.
+///
+/// Returns `true` if the run cached a result. A panicking run returns `false`; the
+/// panic is logged and counted in [`scheduler_panic_count`]. Waits for any run
+/// triggered with `POST /run` to finish first (see [`RUN_LOCK`]).
+pub async fn run_guarded(runner: &dyn SpeedtestRunner, cfg: &RunnerConfig) -> bool {
+    let _running = RUN_LOCK.lock().await;
+    match AssertUnwindSafe(run_speedtest_and_cache_with_runner(runner, cfg)).catch_unwind().await {
+        Ok(outcome) => outcome.is_ok(),
+        Err(panic) => {
+            let count = SCHEDULER_PANICS.fetch_add(1, Ordering::Relaxed) + 1;
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            error!("Speedtest run panicked ({} so far): {}", count, message);
+            false
+        }
+    }
+}
+
+/// Reads `OUTAGE_PROBE_INTERVAL_SECONDS`, the shortened interval used after a failed run.
+///
+/// Returns `None` when unset or zero, which disables outage probing.
+pub fn outage_probe_interval() -> Option<Duration> {
+    config::OUTAGE_PROBE_INTERVAL_SECONDS
+        .parse::<u64>()
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Whether the scheduler is running at its normal pace or probing for recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulerMode {
+    /// Runs every configured interval.
+    #[default]
+    Normal,
+
+    /// The last run failed; runs every outage probe interval until one succeeds.
+    OutageProbe,
+}
+
+/// Selects the mode and interval for the next run.
+///
+/// A failed run switches to [`SchedulerMode::OutageProbe`] when an `outage_probe`
+/// interval is configured, and a successful run restores [`SchedulerMode::Normal`].
+/// The probe interval is never longer than `normal`. Transitions are logged.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use speedtest_statuspage::{next_schedule, SchedulerMode};
+/// let normal = Duration::from_secs(600);
+/// let probe = Some(Duration::from_secs(30));
+///
+/// let (mode, interval) = next_schedule(false, SchedulerMode::Normal, normal, probe);
+/// assert_eq!((mode, interval), (SchedulerMode::OutageProbe, Duration::from_secs(30)));
+///
+/// let (mode, interval) = next_schedule(true, mode, normal, probe);
+/// assert_eq!((mode, interval), (SchedulerMode::Normal, normal));
+/// ```
+pub fn next_schedule(
+    succeeded: bool,
+    mode: SchedulerMode,
+    normal: Duration,
+    outage_probe: Option<Duration>,
+) -> (SchedulerMode, Duration) {
+    match (succeeded, outage_probe) {
+        (false, Some(probe)) => {
+            if mode == SchedulerMode::Normal {
+                warn!("Speedtest failed; probing every {}s until it recovers", probe.min(normal).as_secs());
+            }
+            (SchedulerMode::OutageProbe, probe.min(normal))
+        }
+        _ => {
+            if mode == SchedulerMode::OutageProbe && succeeded {
+                info!("Speedtest recovered; resuming the normal interval");
+            }
+            (SchedulerMode::Normal, normal)
+        }
+    }
+}
+
+/// Runs the speedtest at the intervals given by `schedule`, starting immediately.
+///
+/// The interval is looked up in local time after every run, so crossing into a
+/// window with a different interval takes effect from the next run. Each run is
+/// guarded by [`run_guarded`], so a panic in one run does not stop the scheduler.
+/// When a `tuner` is given, each run is pinned to its chosen server. With an
+/// `outage_probe` interval, failed runs are retried at that shorter interval until
+/// one succeeds (see [`next_schedule`]). Runs are skipped while [`maintenance`]
+/// mode is on.
+///
+/// The scheduler stops once `true` is sent on `shutdown` or its sender is dropped.
+/// A run in progress is always finished first. Returns the number of runs made.
+pub async fn run_scheduler(
+    runner: &dyn SpeedtestRunner,
+    cfg: &RunnerConfig,
+    schedule: &schedule::Schedule,
+    outage_probe: Option<Duration>,
+    mut tuner: Option<autotune::AutoTuner>,
+    mut shutdown: watch::Receiver<bool>,
+) -> u64 {
+    let mut mode = SchedulerMode::Normal;
+    let mut interval = schedule.interval_at(chrono::Local::now().time());
+    let mut runs = 0;
+    // The first tick completes immediately, so one run happens on startup
+    let mut ticker = time::interval(interval);
+    loop {
+        if *shutdown.borrow_and_update() {
+            break;
+        }
+        tokio::select! {
+            _ = ticker.tick() => {}
+            changed = shutdown.changed() => match changed {
+                Ok(()) => continue,
+                Err(_) => break,
+            },
+        }
+        if maintenance::is_active() {
+            info!("Skipping speedtest: maintenance mode is on");
+            continue;
+        }
+        let run_cfg = match tuner.as_mut() {
+            Some(tuner) => tuner.config_for_run(runner, cfg).await,
+            None => cfg.clone(),
+        };
+        let succeeded = run_guarded(runner, &run_cfg).await;
+        runs += 1;
+
+        let normal = schedule.interval_at(chrono::Local::now().time());
+        let (next_mode, next_interval) = next_schedule(succeeded, mode, normal, outage_probe);
+        if next_mode != mode || normal != interval {
+            ticker = time::interval_at(time::Instant::now() + next_interval, next_interval);
+            mode = next_mode;
+            interval = normal;
+        }
+    }
+    info!("Scheduler stopped after {} runs", runs);
+    runs
+}
+
+/// Background async task which schedules periodic speedtest runs.
+///
+/// Runs follow `schedule` (see [`schedule::Schedule::from_env`]), the backend is
+/// chosen by `SPEEDTEST_BACKEND`, and the runner configuration is built once from
+/// the environment via [`RunnerConfig::from_env`]. With `AUTO_TUNE=true` each run is
+/// pinned to the server chosen by [`autotune::AutoTuner`].
+///
+/// With `OUTAGE_PROBE_INTERVAL_SECONDS` set, failed runs are retried at that shorter
+/// interval until one succeeds.
+///
+/// With `CAPTURE_DIR` set, every raw output is also saved there (see [`capture`]).
+///
+/// With `SNAPSHOT_FILE` set, the last saved result is restored into the cache before
+/// the first run. Otherwise, with `DB_PATH` set in a build with the `sqlite`
+/// feature, the latest result in the database is (see the `db` module). With
+/// `RESULTS_LOG` set, the history is refilled from the end of the log (see
+/// [`restore_history`]).
+///
+/// Returns once `shutdown` fires (see [`run_scheduler`]), with the number of runs made.
+pub async fn spawn_speedtest_scheduler(schedule: schedule::Schedule, shutdown: watch::Receiver<bool>) -> u64 {
+    let backend = speedtest_backend();
+    let runner = capture::with_capture_from_env(backend.runner());
+    let cfg = RunnerConfig::from_env();
+    let tuner = autotune::AutoTuner::from_env(backend, &cfg);
+
+    let restored = snapshot::snapshot_path()
+        .and_then(|path| snapshot::load_snapshot(&path))
+        .map(|result| (result, "snapshot"));
+    #[cfg(feature = "sqlite")]
+    let restored = restored.or_else(|| db::load_latest().map(|result| (result, "database")));
+    #[cfg(not(feature = "sqlite"))]
+    if config::DB_PATH.raw().is_some_and(|path| !path.trim().is_empty()) {
+        warn!("Ignoring {}: this build does not include the sqlite feature", config::DB_PATH.name);
+    }
+    if let Some((result, source)) = restored {
+        let timestamp = result.timestamp.clone();
+        match store_last_result(result) {
+            Ok(()) => info!("Restored speedtest result from {} {}", timestamp, source),
+            Err(e) => error!("Failed to restore {} from {}: {}", source, timestamp, e),
+        }
+    }
+    let restored = restore_history();
+    if restored > 0 {
+        info!("Restored {} results into the history", restored);
+    }
+
+    run_scheduler(runner.as_ref(), &cfg, &schedule, outage_probe_interval(), tuner, shutdown).await
+}
+
+/// Calls `bind` until it succeeds, retrying up to `max_retries` times `delay` apart.
+///
+/// Used at boot, when binding can fail transiently because the interface for
+/// `BIND_ADDRESS` is not up yet. Each failed attempt is logged.
+///
+/// # Errors
+///
+/// Returns the last error once the retries are exhausted.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use speedtest_statuspage::retry_bind;
+/// # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+/// let mut failures = 2;
+/// let bound = retry_bind(
+///     || if failures > 0 { failures -= 1; Err("cannot assign requested address") } else { Ok(8080) },
+///     3,
+///     Duration::ZERO,
+/// ).await;
+/// assert_eq!(bound, Ok(8080));
+/// # });
+/// ```
+pub async fn retry_bind<T, E: fmt::Display>(
+    mut bind: impl FnMut() -> Result<T, E>,
+    max_retries: u32,
+    delay: Duration,
+) -> Result<T, E> {
+    let mut retries = 0;
+    loop {
+        match bind() {
+            Ok(bound) => return Ok(bound),
+            Err(e) if retries < max_retries => {
+                retries += 1;
+                warn!("Failed to bind: {}; retrying in {}s ({}/{})", e, delay.as_secs(), retries, max_retries);
+                time::sleep(delay).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Runs `future` to completion on a dedicated single-threaded Tokio runtime on its own thread.
+///
+/// Used with `ISOLATE_SCHEDULER=true` so that speedtest subprocess handling cannot
+/// affect request serving on the Actix runtime. The shared cache is guarded by a
+/// `std::sync::RwLock`, so it is safe to access from both runtimes.
+///
+/// # Errors
+///
+/// Returns an error if the runtime or the thread cannot be created.
+pub fn spawn_on_dedicated_runtime<F>(name: &str, future: F) -> std::io::Result<thread::JoinHandle<F::Output>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || runtime.block_on(future))
+}
+
+/// Starts `scheduler` on a dedicated runtime (see [`spawn_on_dedicated_runtime`])
+/// when `ISOLATE_SCHEDULER=true`, or as a task on the current runtime otherwise.
+///
+/// Notifications [dispatched](notify::dispatch) by the scheduler are spawned on its
+/// runtime, and a dedicated runtime cancels them when it is dropped, so there
+/// `scheduler` is followed by [`notify::flush`] before the runtime shuts down. The
+/// returned handle finishes once the scheduler has returned and, when isolated, its
+/// notifications have been delivered.
+///
+/// # Errors
+///
+/// Returns an error if the dedicated runtime or its thread cannot be created.
+pub fn start_scheduler<F>(scheduler: F) -> std::io::Result<tokio::task::JoinHandle<()>>
+where
+    F: Future + Send + 'static,
+{
+    if config::ISOLATE_SCHEDULER.flag() {
+        let thread = spawn_on_dedicated_runtime("speedtest-scheduler", async move {
+            scheduler.await;
+            notify::flush().await;
+        })?;
+        Ok(tokio::task::spawn_blocking(move || {
+            let _ = thread.join();
+        }))
+    } else {
+        Ok(tokio::spawn(async move {
+            scheduler.await;
+        }))
     }
 }
 
 /// Async function to get the cached speedtest result or return an error if not available.
 pub async fn get_cached_speedtest_result() -> Result<SpeedTestResult, String> {
-    let cache = LAST_RESULT.lock().unwrap();
+    let cache = LAST_RESULT.read().unwrap_or_else(|e| e.into_inner());
     if let Some((cached_result, _)) = &*cache {
         Ok(cached_result.clone())
     } else {
@@ -0,0 +1,41 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Request body size limits for the write endpoints.
+//!
+//! `POST /test/set` and `POST /maintenance` read request bodies; without a limit a
+//! client could POST a payload large enough to exhaust a small device's memory.
+//! [`configure`] caps both JSON and raw bodies at `MAX_INGEST_BYTES`, answering
+//! HTTP 413 Payload Too Large beyond it.
+
+use actix_web::web;
+use crate::config;
+
+/// Reads `MAX_INGEST_BYTES`, the largest request body accepted, in bytes.
+pub fn max_ingest_bytes() -> usize {
+    config::MAX_INGEST_BYTES.get()
+}
+
+/// Returns an app configuration limiting JSON and raw request bodies to `limit`
+/// bytes.
+///
+/// Pass [`max_ingest_bytes`].
+pub fn configure(limit: usize) -> impl Fn(&mut web::ServiceConfig) + Clone {
+    move |cfg| {
+        cfg.app_data(web::JsonConfig::default().limit(limit))
+            .app_data(web::PayloadConfig::new(limit));
+    }
+}
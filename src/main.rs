@@ -14,35 +14,177 @@
 // or the MIT license <http://opensource.org/licenses/MIT>, at your option.
 // This file may not be copied, modified, or distributed except according to those terms.
 
-mod models;
+use std::time::Duration;
+use actix_web::middleware::from_fn;
+use actix_web::{web, App, HttpServer};
+use tokio::sync::watch;
+use speedtest_statuspage::access::{enforce_allow_list, AllowList};
+use speedtest_statuspage::auth::{require_api_token, ApiToken};
+use speedtest_statuspage::config::BindTarget;
+use speedtest_statuspage::branding::{set_server_header, ServerHeader};
+use speedtest_statuspage::routes::{self, Listener};
+use speedtest_statuspage::run::ManualRun;
+use speedtest_statuspage::schedule::Schedule;
+use speedtest_statuspage::stats;
+use speedtest_statuspage::{clear_after, config, debug, limits, notify, results_log, retry_bind, run_result_expiry, spawn_speedtest_scheduler, speedtest_backend, start_scheduler, testmode, version, HealthThresholds};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
-use actix_web::{App, HttpServer};
-use dotenvy;
-use std::env;
-use speedtest_statuspage::{spawn_speedtest_scheduler, speedtest};
+/// Installs a `tracing` subscriber printing events at the levels `RUST_LOG` selects.
+///
+/// An invalid filter is reported and replaced with the default.
+fn init_logging() {
+    let (filter, invalid) = match EnvFilter::try_new(config::RUST_LOG.get::<String>()) {
+        Ok(filter) => (filter, None),
+        Err(e) => (EnvFilter::new(config::RUST_LOG.default.unwrap_or_default()), Some(e)),
+    };
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+    if let Some(e) = invalid {
+        warn!("Ignoring invalid {}: {}", config::RUST_LOG.name, e);
+    }
+}
 
 /// Main entrypoint starts the Actix-web server and the periodic speedtest runner.
 ///
-/// Binds to `BIND_ADDRESS` and `BIND_PORT` environment variables or defaults.
+/// Binds to every address in `BIND_ADDRESS`, using `BIND_PORT` for those without
+/// their own port, or to the defaults. With `METRICS_BIND_PORT` set, `/metrics` and
+/// `/health` are served only by a second server on that port.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if `BIND_PORT` cannot be parsed as a valid `u16`.
+/// Returns an error naming every address the server cannot bind after
+/// `BIND_MAX_RETRIES` retries, if
+/// `BIND_ADDRESS`, `BIND_PORT`, `METRICS_BIND_PORT`, `INTERVAL_MINUTES`, `ALLOW_CIDRS`, `SCHEDULE` or
+/// `SERVER_HEADER` is invalid, or if `STRICT_VERSION=true` and the speedtest binary is
+/// older than the supported minimum.
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    stats::mark_started();
     dotenvy::dotenv().ok();
+    init_logging();
+
+    if config::RUN_MODE.get::<String>() == "print-env-template" {
+        print!("{}", config::env_template());
+        return Ok(());
+    }
+
+    debug::warn_possible_typos();
+
+    let config::Config { bind_targets, metrics_bind_targets, .. } =
+        config::Config::from_env().map_err(std::io::Error::other)?;
+    let separate_metrics = !metrics_bind_targets.is_empty();
+    let allow_list = web::Data::new(AllowList::from_env().map_err(std::io::Error::other)?);
+    let server_header = web::Data::new(ServerHeader::from_env().map_err(std::io::Error::other)?);
+    let manual_run = web::Data::new(ManualRun::from_env());
+    let api_token = web::Data::new(ApiToken::from_env());
+    let health_thresholds = web::Data::new(HealthThresholds::from_env());
+    let schedule = Schedule::from_env().map_err(std::io::Error::other)?;
+    let test_endpoints = config::TEST_ENDPOINTS_ENABLED.flag();
+    let max_ingest_bytes = limits::max_ingest_bytes();
+    if test_endpoints {
+        warn!("TEST_ENDPOINTS_ENABLED is set; anyone who can reach the server can overwrite its results");
+    }
+
+    if let Err(e) = version::check_backend_version(speedtest_backend()).await {
+        return Err(std::io::Error::other(e));
+    }
+
+    let state_path = stats::state_path();
+    stats::restore_state();
+    if let Some(path) = state_path.clone() {
+        tokio::spawn(stats::run_state_saver(path));
+    }
+
+    // Spawn the periodic speedtest updater, optionally on its own runtime
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    let mut scheduler = start_scheduler(spawn_speedtest_scheduler(schedule, shutdown_rx))?;
 
-    let bind_address = env::var("BIND_ADDRESS").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let bind_port_str = env::var("BIND_PORT").unwrap_or_else(|_| "8080".to_string());
-    let bind_port: u16 = bind_port_str.parse().expect("BIND_PORT must be a valid u16");
+    if let Some(max_age) = clear_after() {
+        tokio::spawn(run_result_expiry(max_age));
+    }
+    if let Some(log) = results_log::results_log_path().filter(|_| config::RESULTS_LOG_ARCHIVE.flag()) {
+        tokio::spawn(results_log::run_archival(log));
+    }
 
-    // Spawn the periodic speedtest updater
-    tokio::spawn(spawn_speedtest_scheduler());
+    for target in &bind_targets {
+        match target {
+            BindTarget::Tcp { .. } => info!("Starting server at http://{}/speed", target),
+            BindTarget::Unix(_) => info!("Starting server at {}", target),
+        }
+    }
+    for target in &metrics_bind_targets {
+        info!("Serving /metrics and /health at http://{}/metrics", target);
+    }
 
-    println!("Starting server at http://{}:{}/speed", bind_address, bind_port);
+    let app = move |listener: Listener| {
+        App::new()
+            .app_data(allow_list.clone())
+            .app_data(server_header.clone())
+            .app_data(manual_run.clone())
+            .app_data(api_token.clone())
+            .app_data(health_thresholds.clone())
+            .wrap(from_fn(require_api_token))
+            .wrap(from_fn(enforce_allow_list))
+            .wrap(from_fn(set_server_header))
+            .configure(routes::configure(listener))
+            .configure(limits::configure(max_ingest_bytes))
+            .configure(testmode::configure(test_endpoints && listener != Listener::Metrics))
+    };
+    // Binds every target, carrying on past failures so that all of them are reported
+    let bind_all = |targets: &[BindTarget], listener: Listener| {
+        let app = app.clone();
+        let app = move || app(listener);
+        let mut server = HttpServer::new(app.clone());
+        let mut errors = Vec::new();
+        for target in targets {
+            let bound = match target {
+                BindTarget::Tcp { host, port } => server.bind((host.as_str(), *port)),
+                #[cfg(unix)]
+                BindTarget::Unix(path) => server.bind_uds(path),
+                #[cfg(not(unix))]
+                BindTarget::Unix(_) => Err(std::io::Error::from(std::io::ErrorKind::Unsupported)),
+            };
+            server = bound.unwrap_or_else(|e| {
+                errors.push(format!("could not bind {}: {}", target, e));
+                HttpServer::new(app.clone())
+            });
+        }
+        if errors.is_empty() { Ok(server) } else { Err(std::io::Error::other(errors.join("; "))) }
+    };
+    let max_retries = config::BIND_MAX_RETRIES.get();
+    let retry_delay = Duration::from_secs(config::BIND_RETRY_SECONDS.get());
+    let server = retry_bind(|| bind_all(&bind_targets, Listener::Main { separate_metrics }), max_retries, retry_delay)
+        .await?
+        .run();
+    let metrics_server = if separate_metrics {
+        Some(retry_bind(|| bind_all(&metrics_bind_targets, Listener::Metrics), max_retries, retry_delay).await?.run())
+    } else {
+        None
+    };
+    let handles = [Some(server.handle()), metrics_server.as_ref().map(|server| server.handle())];
+    let metrics_server = async move {
+        match metrics_server {
+            Some(server) => server.await,
+            None => std::future::pending().await,
+        }
+    };
 
-    HttpServer::new(|| App::new().service(speedtest))
-        .bind((bind_address.as_str(), bind_port))?
-        .run()
-        .await
+    // The servers stop themselves on SIGINT/SIGTERM, and each stops the other; the
+    // scheduler then finishes any in-flight run before exiting.
+    let outcome = tokio::select! {
+        outcome = server => outcome,
+        outcome = metrics_server => outcome,
+        _ = &mut scheduler => return Err(std::io::Error::other("speedtest scheduler exited unexpectedly")),
+    };
+    for handle in handles.into_iter().flatten() {
+        handle.stop(true).await;
+    }
+    info!("Server stopped; waiting for the scheduler and pending notifications to finish");
+    let _ = shutdown.send(true);
+    let _ = scheduler.await;
+    if let Some(path) = state_path {
+        stats::save_state(&path);
+    }
+    notify::flush().await;
+    outcome
 }
\ No newline at end of file
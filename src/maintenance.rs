@@ -0,0 +1,154 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Maintenance mode, for planned outages.
+//!
+//! While maintenance mode is on, the scheduler skips its runs and no alerts are
+//! sent, but the server keeps serving the last result, flagged with
+//! `"maintenance": true` on `/speed`. It is toggled with `POST /maintenance`, which
+//! requires `Authorization: Bearer` with the value of `MAINTENANCE_TOKEN`, and stays
+//! on until toggled off or until its optional expiry passes.
+
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use crate::{access, config};
+
+/// Shared maintenance state.
+///
+/// The expiry is kept as milliseconds since the Unix epoch, `0` meaning none.
+#[derive(Debug)]
+pub struct Maintenance {
+    enabled: AtomicBool,
+    until_ms: AtomicI64,
+}
+
+impl Maintenance {
+    /// Creates the state with maintenance off.
+    pub const fn new() -> Self {
+        Maintenance { enabled: AtomicBool::new(false), until_ms: AtomicI64::new(0) }
+    }
+
+    /// Turns maintenance on, optionally switching it off again after `expires_in`.
+    pub fn enable(&self, expires_in: Option<Duration>) {
+        let until = expires_in.map_or(0, |d| now_ms().saturating_add(d.as_millis().try_into().unwrap_or(i64::MAX)));
+        self.until_ms.store(until, Ordering::SeqCst);
+        self.enabled.store(true, Ordering::SeqCst);
+    }
+
+    /// Turns maintenance off.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.until_ms.store(0, Ordering::SeqCst);
+    }
+
+    /// Returns whether maintenance is on and has not expired.
+    pub fn is_active(&self) -> bool {
+        if !self.enabled.load(Ordering::SeqCst) {
+            return false;
+        }
+        let until = self.until_ms.load(Ordering::SeqCst);
+        if until != 0 && now_ms() >= until {
+            if self.enabled.swap(false, Ordering::SeqCst) {
+                info!("Maintenance mode expired; resuming speedtests");
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Returns the expiry as an RFC 3339 timestamp, if maintenance is on and expires.
+    pub fn expires_at(&self) -> Option<String> {
+        let until = self.until_ms.load(Ordering::SeqCst);
+        if !self.is_active() || until == 0 {
+            return None;
+        }
+        chrono::DateTime::from_timestamp_millis(until).map(|at| at.to_rfc3339())
+    }
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// The process-wide maintenance state consulted by the scheduler and notifiers.
+pub static MAINTENANCE: Maintenance = Maintenance::new();
+
+/// Returns whether the process is in maintenance mode.
+pub fn is_active() -> bool {
+    MAINTENANCE.is_active()
+}
+
+/// Body of a `POST /maintenance` request.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenanceRequest {
+    /// Whether maintenance mode should be on.
+    pub enabled: bool,
+
+    /// Minutes after which maintenance mode switches itself off, if any.
+    #[serde(default)]
+    pub expires_in_minutes: Option<u64>,
+}
+
+/// Current maintenance state, returned by `POST /maintenance`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceStatus {
+    /// Whether maintenance mode is on.
+    pub enabled: bool,
+
+    /// When maintenance mode switches itself off, if it does.
+    pub expires_at: Option<String>,
+}
+
+/// HTTP POST endpoint `/maintenance` turns maintenance mode on or off.
+///
+/// Returns the resulting [`MaintenanceStatus`]. Responds HTTP 404 Not Found unless
+/// `MAINTENANCE_TOKEN` is set, and HTTP 401 Unauthorized unless the request carries
+/// `Authorization: Bearer <MAINTENANCE_TOKEN>`; the body is only parsed after that,
+/// answering HTTP 400 Bad Request if it is not a [`MaintenanceRequest`].
+#[post("/maintenance")]
+pub async fn maintenance_endpoint(req: HttpRequest, body: web::Bytes) -> impl Responder {
+    let Some(token) = config::MAINTENANCE_TOKEN.raw().filter(|t| !t.is_empty()) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Some(unauthorized) = access::require_bearer(&req, &token) {
+        return unauthorized;
+    }
+    let body: MaintenanceRequest = match serde_json::from_slice(&body) {
+        Ok(body) => body,
+        Err(e) => return HttpResponse::BadRequest().body(format!("Invalid maintenance request: {}", e)),
+    };
+
+    if body.enabled {
+        MAINTENANCE.enable(body.expires_in_minutes.map(|minutes| Duration::from_secs(minutes.saturating_mul(60))));
+        info!("Maintenance mode on; pausing speedtests and alerts");
+    } else {
+        MAINTENANCE.disable();
+        info!("Maintenance mode off; resuming speedtests");
+    }
+    HttpResponse::Ok().json(MaintenanceStatus {
+        enabled: MAINTENANCE.is_active(),
+        expires_at: MAINTENANCE.expires_at(),
+    })
+}
@@ -0,0 +1,134 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Ambient metadata captured by a user-supplied hook.
+//!
+//! When `METADATA_HOOK` is set, the command is run through `sh -c` after every
+//! speedtest. Its stdout must be a JSON object, whose keys are merged into the
+//! result's `metadata` field. Malformed output is logged and the result is stored
+//! without metadata.
+
+use std::process::Stdio;
+use std::time::Duration;
+use serde_json::Value;
+use tokio::process::Command;
+use tokio::time;
+use tracing::warn;
+use crate::config;
+use crate::models::SpeedTestResult;
+
+/// Maximum time a metadata hook may run before it is abandoned.
+pub const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Parses hook output, which must be a single JSON object.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::metadata::parse_hook_output;
+///
+/// let metadata = parse_hook_output(r#"{"temp_c": 21.5}"#).unwrap();
+/// assert_eq!(metadata["temp_c"], 21.5);
+/// assert!(parse_hook_output("[1, 2]").is_err());
+/// ```
+pub fn parse_hook_output(stdout: &str) -> Result<Value, String> {
+    match serde_json::from_str::<Value>(stdout.trim()) {
+        Ok(value @ Value::Object(_)) => Ok(value),
+        Ok(_) => Err("metadata hook output is not a JSON object".to_string()),
+        Err(e) => Err(format!("metadata hook output is not valid JSON: {}", e)),
+    }
+}
+
+/// Merges hook output into `result.metadata`.
+///
+/// Keys from the hook replace any existing keys of the same name. If the output
+/// cannot be parsed the error is logged, `result` is left unchanged and `false` is
+/// returned.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::SpeedTestResult;
+/// use speedtest_statuspage::metadata::merge_hook_output;
+///
+/// let mut result = SpeedTestResult::default();
+/// assert!(merge_hook_output(&mut result, r#"{"location": "loft"}"#));
+/// assert_eq!(result.metadata["location"], "loft");
+///
+/// assert!(!merge_hook_output(&mut result, "not json"));
+/// assert_eq!(result.metadata["location"], "loft");
+/// ```
+pub fn merge_hook_output(result: &mut SpeedTestResult, stdout: &str) -> bool {
+    match parse_hook_output(stdout) {
+        Ok(Value::Object(fields)) => {
+            if !result.metadata.is_object() {
+                result.metadata = Value::Object(Default::default());
+            }
+            if let Value::Object(existing) = &mut result.metadata {
+                existing.extend(fields);
+            }
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            warn!("Ignoring metadata: {}", e);
+            false
+        }
+    }
+}
+
+/// Runs `command` through `sh -c` and returns its stdout.
+///
+/// Fails if the command cannot be started, exits unsuccessfully or runs longer
+/// than [`HOOK_TIMEOUT`].
+pub async fn run_hook(command: &str) -> Result<String, String> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    let output = time::timeout(HOOK_TIMEOUT, child)
+        .await
+        .map_err(|_| format!("metadata hook timed out after {}s", HOOK_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("Failed to run metadata hook: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "metadata hook exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Runs `METADATA_HOOK`, if configured, and merges its output into `result`.
+pub async fn collect_metadata(result: &mut SpeedTestResult) {
+    let Some(command) = config::METADATA_HOOK.raw().filter(|s| !s.trim().is_empty()) else {
+        return;
+    };
+    match run_hook(&command).await {
+        Ok(stdout) => {
+            merge_hook_output(result, &stdout);
+        }
+        Err(e) => warn!("Ignoring metadata: {}", e),
+    }
+}
@@ -15,6 +15,10 @@
 // This file may not be copied, modified, or distributed except according to those terms.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use tracing::warn;
 
 /// Information about the client running the speedtest.
 ///
@@ -41,7 +45,7 @@ use serde::{Deserialize, Serialize};
 /// assert_eq!(client.country, "UK");
 /// assert_eq!(client.ip, "192.0.2.1");
 /// ```
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct ClientInfo {
     /// Client's country code or name.
     pub country: String,
@@ -74,6 +78,39 @@ pub struct ClientInfo {
     pub rating: String,
 }
 
+/// Parses a speedtest.net ISP average, reported in kilobits per second, into Mbps.
+///
+/// Missing averages are reported as `"0"` or an empty string and yield `None`.
+fn parse_isp_average(kbps: &str) -> Option<f64> {
+    kbps.trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v > 0.0)
+        .map(|v| v / 1000.0)
+}
+
+impl ClientInfo {
+    /// Returns the ISP's reported average download speed in Mbps, if known.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::ClientInfo;
+    ///
+    /// let client = ClientInfo { ispdlavg: "52000".to_string(), ..Default::default() };
+    /// assert_eq!(client.isp_download_avg_mbps(), Some(52.0));
+    /// assert_eq!(ClientInfo::default().isp_download_avg_mbps(), None);
+    /// ```
+    pub fn isp_download_avg_mbps(&self) -> Option<f64> {
+        parse_isp_average(&self.ispdlavg)
+    }
+
+    /// Returns the ISP's reported average upload speed in Mbps, if known.
+    pub fn isp_upload_avg_mbps(&self) -> Option<f64> {
+        parse_isp_average(&self.ispulavg)
+    }
+}
+
 /// Information about the server used in the speedtest.
 ///
 /// Represents metadata about the test server including location,
@@ -100,7 +137,7 @@ pub struct ClientInfo {
 /// assert_eq!(server.cc, "GB");
 /// assert_eq!(server.latency, 10.5);
 /// ```
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct ServerInfo {
     /// Server country code.
     pub cc: String,
@@ -198,10 +235,10 @@ pub struct ServerInfo {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SpeedTestResponse {
     /// Number of bytes received during the test.
-    pub bytes_received: usize,
+    pub bytes_received: u64,
 
     /// Number of bytes sent during the test.
-    pub bytes_sent: usize,
+    pub bytes_sent: u64,
 
     /// Client information.
     pub client: ClientInfo,
@@ -223,6 +260,162 @@ pub struct SpeedTestResponse {
 
     /// Upload speed in bits per second.
     pub upload: f64,
+
+    /// Variation of the ping in milliseconds, reported by newer CLIs only.
+    #[serde(default, alias = "jitter")]
+    pub jitter_ms: Option<f64>,
+}
+
+/// Unit a backend reports its speeds in.
+///
+/// Every conversion into [`SpeedTestResult`] names its backend's unit explicitly, so
+/// that a backend reporting Mbps is never mistaken for one reporting bps. The same
+/// units can be requested from `/speed` (see [`crate::units`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpeedUnit {
+    /// Bits per second, as reported by `speedtest-cli`.
+    Bps,
+    /// Kilobits per second.
+    Kbps,
+    /// Megabits per second.
+    Mbps,
+    /// Gigabits per second.
+    Gbps,
+    /// Megabytes per second, i.e. 8 megabits per second.
+    MBps,
+}
+
+impl SpeedUnit {
+    /// Converts `value` in this unit into bits per second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::SpeedUnit;
+    ///
+    /// assert_eq!(SpeedUnit::Mbps.to_bps(94.5), 94_500_000.0);
+    /// assert_eq!(SpeedUnit::Bps.to_bps(94.5), 94.5);
+    /// ```
+    pub fn to_bps(self, value: f64) -> f64 {
+        match self {
+            SpeedUnit::Bps => value,
+            SpeedUnit::Kbps => value * 1_000.0,
+            SpeedUnit::Mbps => value * 1_000_000.0,
+            SpeedUnit::Gbps => value * 1_000_000_000.0,
+            SpeedUnit::MBps => value * 8_000_000.0,
+        }
+    }
+
+    /// Returns the conventional name of the unit, e.g. `Mbps` or `MBps`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SpeedUnit::Bps => "bps",
+            SpeedUnit::Kbps => "Kbps",
+            SpeedUnit::Mbps => "Mbps",
+            SpeedUnit::Gbps => "Gbps",
+            SpeedUnit::MBps => "MBps",
+        }
+    }
+}
+
+impl fmt::Display for SpeedUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SpeedUnit {
+    type Err = String;
+
+    /// Parses a unit name. Bit units are case-insensitive (`gbps`, `Gbps`), so
+    /// megabytes per second must be written with a capital `B`, as `MBps` or `MB/s`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "MBps" || s == "MB/s" {
+            return Ok(SpeedUnit::MBps);
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "bps" => Ok(SpeedUnit::Bps),
+            "kbps" => Ok(SpeedUnit::Kbps),
+            "mbps" => Ok(SpeedUnit::Mbps),
+            "gbps" => Ok(SpeedUnit::Gbps),
+            _ => Err(format!("unknown unit '{}'; expected bps, Kbps, Mbps, Gbps or MBps", s)),
+        }
+    }
+}
+
+/// Fastest speed, in bits per second, accepted as a genuine measurement (10 Tbps).
+///
+/// Anything faster is physically implausible for a speedtest and almost certainly
+/// the result of a unit-conversion bug.
+pub const MAX_PLAUSIBLE_BPS: f64 = 10_000_000_000_000.0;
+
+/// Returns `true` if `bps` is a finite speed no faster than [`MAX_PLAUSIBLE_BPS`].
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::models::is_plausible_bps;
+///
+/// assert!(is_plausible_bps(940_000_000.0));
+/// // 940 Mbps mistakenly scaled as if it were reported in Mbps twice over
+/// assert!(!is_plausible_bps(940_000_000.0 * 1_000_000.0));
+/// ```
+pub fn is_plausible_bps(bps: f64) -> bool {
+    bps.is_finite() && bps <= MAX_PLAUSIBLE_BPS
+}
+
+/// Replaces a negative or non-finite measurement with `0`.
+///
+/// `speedtest-cli` occasionally emits a negative or `NaN` value, and `NaN` serializes
+/// to `null`, which breaks strict JSON consumers.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::models::sanitize_speed;
+///
+/// assert_eq!(sanitize_speed(94.5), 94.5);
+/// assert_eq!(sanitize_speed(-1.0), 0.0);
+/// assert_eq!(sanitize_speed(f64::NAN), 0.0);
+/// ```
+pub fn sanitize_speed(v: f64) -> f64 {
+    if v.is_finite() && v > 0.0 { v } else { 0.0 }
+}
+
+/// Applies [`sanitize_speed`], logging when `value` had to be replaced.
+fn sanitize_logged(field: &str, value: f64) -> f64 {
+    let sanitized = sanitize_speed(value);
+    if sanitized.to_bits() != value.to_bits() && value != 0.0 {
+        warn!("speedtest-cli reported invalid {} {}; using 0", field, value);
+    }
+    sanitized
+}
+
+/// Converts the raw `speedtest-cli` response into a [`SpeedTestResult`].
+///
+/// Speeds are reported in bits per second by `speedtest-cli` and are additionally
+/// scaled to megabits per second.
+impl From<SpeedTestResponse> for SpeedTestResult {
+    fn from(data: SpeedTestResponse) -> Self {
+        let download = SpeedUnit::Bps.to_bps(sanitize_logged("download", data.download));
+        let upload = SpeedUnit::Bps.to_bps(sanitize_logged("upload", data.upload));
+        SpeedTestResult {
+            bytes_received: data.bytes_received,
+            bytes_sent: data.bytes_sent,
+            download_bps: download,
+            upload_bps: upload,
+            download_mbps: download / 1_000_000.0,
+            upload_mbps: upload / 1_000_000.0,
+            ping_ms: sanitize_logged("ping", data.ping),
+            jitter_ms: data.jitter_ms.filter(|jitter| jitter.is_finite() && *jitter >= 0.0),
+            client: data.client,
+            server: data.server,
+            share: data.share,
+            timestamp: data.timestamp,
+            ..Default::default()
+        }
+    }
 }
 
 /// A processed and cached speedtest result ready for API serving.
@@ -232,6 +425,12 @@ pub struct SpeedTestResponse {
 ///
 /// It is used to cache and respond with speedtest data efficiently.
 ///
+/// # Equality
+///
+/// `PartialEq` compares every field exactly, including the `f64` speeds. Two results
+/// are only equal if their floats are bit-for-bit identical (a serde round-trip
+/// preserves them), and a result containing `NaN` is never equal to itself.
+///
 /// # Examples
 ///
 /// ```
@@ -272,18 +471,19 @@ pub struct SpeedTestResponse {
 ///     },
 ///     share: None,
 ///     timestamp: "2025-08-07T12:00:00Z".to_string(),
+///     ..Default::default()
 /// };
 ///
 /// assert_eq!(result.download_mbps, 50.0);
 /// assert_eq!(result.client.isp, "Example ISP");
 /// ```
-#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct SpeedTestResult {
     /// Number of bytes received.
-    pub bytes_received: usize,
+    pub bytes_received: u64,
 
     /// Number of bytes sent.
-    pub bytes_sent: usize,
+    pub bytes_sent: u64,
 
     /// Download speed in bits per second.
     pub download_bps: f64,
@@ -311,4 +511,504 @@ pub struct SpeedTestResult {
 
     /// Timestamp of the speedtest.
     pub timestamp: String,
+
+    /// Negotiated speed of the local network interface in megabits per second, if known.
+    #[serde(default)]
+    pub nic_speed_mbps: Option<f64>,
+
+    /// Download speed as a percentage of [`nic_speed_mbps`](Self::nic_speed_mbps), if known.
+    #[serde(default)]
+    pub percent_of_nic: Option<f64>,
+
+    /// Name of the machine which produced the result (`NODE_NAME` or the hostname).
+    #[serde(default)]
+    pub node: String,
+
+    /// Arbitrary `key=value` tags from `RESULT_TAGS`, for grouping results.
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+
+    /// Arbitrary context captured by `METADATA_HOOK` at run time (`null` if none).
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+
+    /// Variation of the ping in milliseconds, if the backend measures it.
+    #[serde(default)]
+    pub jitter_ms: Option<f64>,
+
+    /// Percentage of packets lost during the test, if the backend measures it.
+    #[serde(default)]
+    pub packet_loss: Option<f64>,
+
+    /// Latency in milliseconds to `PING_TARGET`, measured separately from the
+    /// speedtest server's `ping_ms` (see [`crate::ping`]).
+    #[serde(default)]
+    pub external_ping_ms: Option<f64>,
+
+    /// Latency in milliseconds while downloading, if the backend measures it.
+    #[serde(default)]
+    pub download_latency_ms: Option<f64>,
+
+    /// Latency in milliseconds while uploading, if the backend measures it.
+    #[serde(default)]
+    pub upload_latency_ms: Option<f64>,
+
+    /// Increase of the worst loaded latency over the idle `ping_ms`
+    /// (see [`set_bufferbloat`](Self::set_bufferbloat)).
+    #[serde(default)]
+    pub bufferbloat_ms: Option<f64>,
+
+    /// Grade of [`bufferbloat_ms`](Self::bufferbloat_ms) from A to F.
+    #[serde(default)]
+    pub bufferbloat_grade: Option<BufferbloatGrade>,
+
+    /// Wall-clock time in milliseconds the successful attempt took, leaving out
+    /// any failed attempts and retry delays before it.
+    #[serde(default)]
+    pub test_duration_ms: Option<u64>,
+
+    /// Code path which produced the result.
+    #[serde(default)]
+    pub origin: Origin,
+
+    /// Version of this struct's layout the result was written with
+    /// (see [`SCHEMA_VERSION`] and [`migrate_result`]).
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+/// Current layout version of [`SpeedTestResult`], stamped on every new result.
+///
+/// - `1`: the original fields, written without a version.
+/// - `2`: adds `nic_speed_mbps`, `percent_of_nic`, `node`, `metadata` and
+///   `schema_version`.
+/// - `3`: adds `tags`.
+/// - `4`: adds `external_ping_ms`.
+/// - `5`: adds `download_latency_ms`, `upload_latency_ms`, `bufferbloat_ms` and
+///   `bufferbloat_grade`.
+/// - `6`: adds `origin`.
+/// - `7`: adds `jitter_ms`.
+/// - `8`: adds `packet_loss`.
+/// - `9`: adds `test_duration_ms`.
+pub const SCHEMA_VERSION: u32 = 9;
+
+/// Upgrades a stored result written by any earlier [`SCHEMA_VERSION`] to the current
+/// struct.
+///
+/// Results without a `schema_version` are treated as version 1. Fields added since
+/// the stored version take their defaults, and the upgraded result is stamped with
+/// the current version.
+///
+/// # Errors
+///
+/// Returns an error if `value` is not a valid result of any known version.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::models::{migrate_result, SpeedTestResult, SCHEMA_VERSION};
+///
+/// let mut v1 = serde_json::to_value(SpeedTestResult::default()).unwrap();
+/// for added in ["nic_speed_mbps", "percent_of_nic", "node", "metadata", "schema_version"] {
+///     v1.as_object_mut().unwrap().remove(added);
+/// }
+/// let result = migrate_result(v1).unwrap();
+/// assert_eq!(result.schema_version, SCHEMA_VERSION);
+/// assert_eq!(result.node, "");
+/// ```
+pub fn migrate_result(mut value: serde_json::Value) -> Result<SpeedTestResult, serde_json::Error> {
+    let version = value.get("schema_version").and_then(serde_json::Value::as_u64).unwrap_or(1);
+    if let Some(fields) = value.as_object_mut() {
+        // Versions 2 to 9 only added fields, which deserialize to their defaults.
+        if version < u64::from(SCHEMA_VERSION) {
+            fields.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+        }
+    }
+    serde_json::from_value(value)
+}
+
+impl SpeedTestResult {
+    /// Returns `true` when every core metric of the result is plausible.
+    ///
+    /// A result is considered complete when the download speed, upload speed
+    /// and ping are all finite and strictly greater than zero. Partial failures
+    /// of `speedtest-cli` typically report one of these as `0`, which would
+    /// otherwise be cached as if it were a genuine measurement.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::SpeedTestResult;
+    ///
+    /// let mut result = SpeedTestResult {
+    ///     download_bps: 50_000_000.0,
+    ///     upload_bps: 10_000_000.0,
+    ///     ping_ms: 20.0,
+    ///     ..Default::default()
+    /// };
+    /// assert!(result.is_complete());
+    ///
+    /// result.download_bps = 0.0;
+    /// assert!(!result.is_complete());
+    /// ```
+    pub fn is_complete(&self) -> bool {
+        [self.download_bps, self.upload_bps, self.ping_ms]
+            .iter()
+            .all(|v| v.is_finite() && *v > 0.0)
+    }
+
+    /// Records the local interface speed and the download speed as a percentage of it.
+    ///
+    /// This distinguishes a slow ISP from a local link that is itself the bottleneck.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::SpeedTestResult;
+    ///
+    /// let mut result = SpeedTestResult { download_mbps: 94.0, ..Default::default() };
+    /// result.set_nic_speed(100.0);
+    /// assert_eq!(result.nic_speed_mbps, Some(100.0));
+    /// assert_eq!(result.percent_of_nic, Some(94.0));
+    /// ```
+    pub fn set_nic_speed(&mut self, nic_speed_mbps: f64) {
+        self.nic_speed_mbps = Some(nic_speed_mbps);
+        self.percent_of_nic = crate::percent_of(self.download_mbps, nic_speed_mbps);
+    }
+
+    /// Computes [`bufferbloat_ms`](Self::bufferbloat_ms) and its grade from the loaded
+    /// latencies.
+    ///
+    /// The bufferbloat is the increase of the higher of the two loaded latencies over
+    /// the idle ping, never below zero. Both fields are left `None` when no loaded
+    /// latency was measured or the idle ping is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::{BufferbloatGrade, BufferbloatThresholds, SpeedTestResult};
+    ///
+    /// let mut result = SpeedTestResult {
+    ///     ping_ms: 10.0,
+    ///     download_latency_ms: Some(45.0),
+    ///     upload_latency_ms: Some(80.0),
+    ///     ..Default::default()
+    /// };
+    /// result.set_bufferbloat(&BufferbloatThresholds::default());
+    /// assert_eq!(result.bufferbloat_ms, Some(70.0));
+    /// assert_eq!(result.bufferbloat_grade, Some(BufferbloatGrade::C));
+    /// ```
+    pub fn set_bufferbloat(&mut self, thresholds: &BufferbloatThresholds) {
+        let loaded = match (self.download_latency_ms, self.upload_latency_ms) {
+            (Some(download), Some(upload)) => Some(download.max(upload)),
+            (download, upload) => download.or(upload),
+        };
+        self.bufferbloat_ms = loaded
+            .filter(|loaded| loaded.is_finite() && self.ping_ms.is_finite() && self.ping_ms > 0.0)
+            .map(|loaded| (loaded - self.ping_ms).max(0.0));
+        self.bufferbloat_grade = self.bufferbloat_ms.map(|ms| bufferbloat_grade(ms, thresholds));
+    }
+
+    /// Returns `true` if download and upload both pass [`is_plausible_bps`].
+    pub fn is_plausible(&self) -> bool {
+        is_plausible_bps(self.download_bps) && is_plausible_bps(self.upload_bps)
+    }
+
+    /// Returns a 0–100 connection health score for this result.
+    ///
+    /// See [`connection_score`] for the formula. Without a
+    /// [`jitter_ms`](Self::jitter_ms), the jitter weight is redistributed over the
+    /// other metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::{ScoreWeights, SpeedTestResult};
+    ///
+    /// let result = SpeedTestResult { download_mbps: 200.0, upload_mbps: 50.0, ping_ms: 8.0, ..Default::default() };
+    /// assert_eq!(result.quality_score(&ScoreWeights::default()), 100);
+    /// ```
+    pub fn quality_score(&self, cfg: &ScoreWeights) -> u8 {
+        connection_score(self.download_mbps, self.upload_mbps, self.ping_ms, self.jitter_ms, cfg)
+    }
+
+    /// Returns the numeric fields of the result keyed by field name.
+    ///
+    /// Intended for writing to columnar time-series databases. Optional fields are
+    /// only included when present.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::models::SpeedTestResult;
+    ///
+    /// let result = SpeedTestResult { ping_ms: 12.5, ..Default::default() };
+    /// let fields = result.numeric_fields();
+    /// assert_eq!(fields["ping_ms"], 12.5);
+    /// assert!(!fields.contains_key("nic_speed_mbps"));
+    /// ```
+    pub fn numeric_fields(&self) -> BTreeMap<String, f64> {
+        let mut fields = BTreeMap::from([
+            ("bytes_received".to_string(), self.bytes_received as f64),
+            ("bytes_sent".to_string(), self.bytes_sent as f64),
+            ("download_bps".to_string(), self.download_bps),
+            ("upload_bps".to_string(), self.upload_bps),
+            ("download_mbps".to_string(), self.download_mbps),
+            ("upload_mbps".to_string(), self.upload_mbps),
+            ("ping_ms".to_string(), self.ping_ms),
+        ]);
+        if let Some(nic_speed) = self.nic_speed_mbps {
+            fields.insert("nic_speed_mbps".to_string(), nic_speed);
+        }
+        if let Some(percent) = self.percent_of_nic {
+            fields.insert("percent_of_nic".to_string(), percent);
+        }
+        if let Some(jitter) = self.jitter_ms {
+            fields.insert("jitter_ms".to_string(), jitter);
+        }
+        if let Some(loss) = self.packet_loss {
+            fields.insert("packet_loss".to_string(), loss);
+        }
+        if let Some(ping) = self.external_ping_ms {
+            fields.insert("external_ping_ms".to_string(), ping);
+        }
+        if let Some(latency) = self.download_latency_ms {
+            fields.insert("download_latency_ms".to_string(), latency);
+        }
+        if let Some(latency) = self.upload_latency_ms {
+            fields.insert("upload_latency_ms".to_string(), latency);
+        }
+        if let Some(bufferbloat) = self.bufferbloat_ms {
+            fields.insert("bufferbloat_ms".to_string(), bufferbloat);
+        }
+        fields
+    }
+}
+
+/// Reference values and weights used by [`connection_score`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreWeights {
+    /// Download speed in Mbps which scores full marks.
+    pub reference_download_mbps: f64,
+
+    /// Upload speed in Mbps which scores full marks.
+    pub reference_upload_mbps: f64,
+
+    /// Ping in milliseconds at or below which ping scores full marks.
+    pub reference_ping_ms: f64,
+
+    /// Jitter in milliseconds at or below which jitter scores full marks.
+    pub reference_jitter_ms: f64,
+
+    /// Relative weight of the download component.
+    pub download: f64,
+
+    /// Relative weight of the upload component.
+    pub upload: f64,
+
+    /// Relative weight of the ping component.
+    pub ping: f64,
+
+    /// Relative weight of the jitter component.
+    pub jitter: f64,
+}
+
+impl Default for ScoreWeights {
+    /// 100/20 Mbps, 20 ms ping and 5 ms jitter as references, weighted 40/20/30/10.
+    fn default() -> Self {
+        ScoreWeights {
+            reference_download_mbps: 100.0,
+            reference_upload_mbps: 20.0,
+            reference_ping_ms: 20.0,
+            reference_jitter_ms: 5.0,
+            download: 0.4,
+            upload: 0.2,
+            ping: 0.3,
+            jitter: 0.1,
+        }
+    }
+}
+
+/// Scores a speed the higher the better, capped at `reference`.
+fn higher_is_better(value: f64, reference: f64) -> f64 {
+    if value.is_finite() && value > 0.0 && reference > 0.0 {
+        (value / reference).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Scores a delay the lower the better, with full marks at or below `reference`.
+fn lower_is_better(value: f64, reference: f64) -> f64 {
+    if value.is_finite() && value >= 0.0 && reference > 0.0 {
+        (reference / value.max(reference)).min(1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Combines the core metrics into a single 0–100 connection health score.
+///
+/// Each metric is normalized to `0.0..=1.0` against its reference value in `cfg`:
+///
+/// - download and upload: `min(value / reference, 1)`
+/// - ping and jitter: `reference / max(value, reference)`
+///
+/// The score is the weighted mean of the normalized metrics, multiplied by 100 and
+/// rounded. When `jitter_ms` is `None` the jitter component is left out and the
+/// mean is taken over the remaining weights. Non-finite or negative values score
+/// zero, as does a non-positive ping.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::models::{connection_score, ScoreWeights};
+///
+/// let cfg = ScoreWeights::default();
+/// // Half the reference download, everything else perfect: 100 - 40 * 0.5
+/// assert_eq!(connection_score(50.0, 20.0, 20.0, Some(5.0), &cfg), 80);
+/// ```
+pub fn connection_score(
+    download_mbps: f64,
+    upload_mbps: f64,
+    ping_ms: f64,
+    jitter_ms: Option<f64>,
+    cfg: &ScoreWeights,
+) -> u8 {
+    let ping = if ping_ms > 0.0 { lower_is_better(ping_ms, cfg.reference_ping_ms) } else { 0.0 };
+    let mut components = vec![
+        (cfg.download, higher_is_better(download_mbps, cfg.reference_download_mbps)),
+        (cfg.upload, higher_is_better(upload_mbps, cfg.reference_upload_mbps)),
+        (cfg.ping, ping),
+    ];
+    if let Some(jitter) = jitter_ms {
+        components.push((cfg.jitter, lower_is_better(jitter, cfg.reference_jitter_ms)));
+    }
+
+    let total_weight: f64 = components.iter().map(|(weight, _)| weight.max(0.0)).sum();
+    if total_weight <= 0.0 {
+        return 0;
+    }
+    let weighted: f64 = components.iter().map(|(weight, value)| weight.max(0.0) * value).sum();
+    (weighted / total_weight * 100.0).round().clamp(0.0, 100.0) as u8
+}
+
+/// Where a [`SpeedTestResult`] came from, for telling apart anomalous entries.
+///
+/// Results stored before the field existed deserialize as [`Origin::Scheduled`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    /// Produced by a regular scheduled run.
+    #[default]
+    Scheduled,
+
+    /// Produced by a run triggered by hand.
+    Manual,
+
+    /// Supplied from outside rather than measured by this process.
+    Ingested,
+
+    /// Produced by a warm-up run whose result is not representative.
+    Warmup,
+}
+
+/// Letter grade for the latency increase under load.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BufferbloatGrade {
+    /// No noticeable increase.
+    A,
+    /// A slight increase.
+    B,
+    /// Noticeable on calls and in games.
+    C,
+    /// Disruptive to real-time traffic.
+    D,
+    /// Severe bufferbloat.
+    F,
+}
+
+impl BufferbloatGrade {
+    /// Returns the grade as a number from 1 (A) to 5 (F), for the
+    /// `speedtest_bufferbloat_grade` gauge.
+    pub fn number(self) -> u8 {
+        match self {
+            BufferbloatGrade::A => 1,
+            BufferbloatGrade::B => 2,
+            BufferbloatGrade::C => 3,
+            BufferbloatGrade::D => 4,
+            BufferbloatGrade::F => 5,
+        }
+    }
+}
+
+impl fmt::Display for BufferbloatGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Upper bounds in milliseconds of bufferbloat for each grade, as used by
+/// [`bufferbloat_grade`].
+///
+/// Parsed from four comma-separated values, `a,b,c,d`; anything above `d` is an F.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferbloatThresholds {
+    /// Highest bufferbloat graded A.
+    pub a: f64,
+
+    /// Highest bufferbloat graded B.
+    pub b: f64,
+
+    /// Highest bufferbloat graded C.
+    pub c: f64,
+
+    /// Highest bufferbloat graded D.
+    pub d: f64,
+}
+
+impl Default for BufferbloatThresholds {
+    /// 30, 60, 200 and 400 ms.
+    fn default() -> Self {
+        BufferbloatThresholds { a: 30.0, b: 60.0, c: 200.0, d: 400.0 }
+    }
+}
+
+impl FromStr for BufferbloatThresholds {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("invalid bufferbloat thresholds '{}'", s))?;
+        match values[..] {
+            [a, b, c, d] if a <= b && b <= c && c <= d => Ok(BufferbloatThresholds { a, b, c, d }),
+            _ => Err(format!("expected four ascending bufferbloat thresholds, got '{}'", s)),
+        }
+    }
+}
+
+/// Grades a latency increase under load: a bufferbloat at or below a threshold
+/// gets that threshold's grade.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::models::{bufferbloat_grade, BufferbloatGrade, BufferbloatThresholds};
+///
+/// let thresholds = BufferbloatThresholds::default();
+/// assert_eq!(bufferbloat_grade(30.0, &thresholds), BufferbloatGrade::A);
+/// assert_eq!(bufferbloat_grade(30.5, &thresholds), BufferbloatGrade::B);
+/// assert_eq!(bufferbloat_grade(1000.0, &thresholds), BufferbloatGrade::F);
+/// ```
+pub fn bufferbloat_grade(bufferbloat_ms: f64, thresholds: &BufferbloatThresholds) -> BufferbloatGrade {
+    match bufferbloat_ms {
+        ms if ms <= thresholds.a => BufferbloatGrade::A,
+        ms if ms <= thresholds.b => BufferbloatGrade::B,
+        ms if ms <= thresholds.c => BufferbloatGrade::C,
+        ms if ms <= thresholds.d => BufferbloatGrade::D,
+        _ => BufferbloatGrade::F,
+    }
 }
@@ -0,0 +1,66 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Detection of the local network interface speed.
+//!
+//! The nominal link speed is taken from `NIC_SPEED_MBPS` when set, otherwise it is
+//! read from `/sys/class/net/<NIC_INTERFACE>/speed` on Linux. Detection falls back
+//! to `None` whenever the value is unavailable.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use crate::config;
+
+/// Reads the link speed of `interface` in megabits per second.
+///
+/// `read` is used to load the sysfs file so that detection can be tested without a
+/// real interface. Returns `None` if the file cannot be read, is not a number, or
+/// reports a non-positive speed (the kernel reports `-1` when the link is down).
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use speedtest_statuspage::nic::read_link_speed_mbps;
+///
+/// let speed = read_link_speed_mbps("eth0", |path: &Path| {
+///     assert_eq!(path, Path::new("/sys/class/net/eth0/speed"));
+///     Ok("1000\n".to_string())
+/// });
+/// assert_eq!(speed, Some(1000.0));
+/// ```
+pub fn read_link_speed_mbps(interface: &str, read: impl Fn(&Path) -> io::Result<String>) -> Option<f64> {
+    let path = PathBuf::from("/sys/class/net").join(interface).join("speed");
+    read(&path)
+        .ok()
+        .and_then(|s| s.trim().parse::<f64>().ok())
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+}
+
+/// Resolves the nominal interface speed in megabits per second.
+///
+/// Uses `NIC_SPEED_MBPS` if it parses as a positive number, otherwise detects the
+/// speed of `NIC_INTERFACE` via sysfs. Returns `None` when neither is available.
+pub fn nic_speed_mbps() -> Option<f64> {
+    config::NIC_SPEED_MBPS
+        .parse::<f64>()
+        .filter(|speed| speed.is_finite() && *speed > 0.0)
+        .or_else(|| {
+            let interface = config::NIC_INTERFACE.raw()?;
+            read_link_speed_mbps(interface.trim(), |path| fs::read_to_string(path))
+        })
+}
@@ -0,0 +1,328 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Outbound notifications sent after speedtest runs.
+//!
+//! Every channel implements [`Notifier`]. The channels configured in the environment
+//! and any added with [`register_notifier`] are combined by [`configured_notifiers`]
+//! into a [`CompositeNotifier`], so the scheduler only ever sends an [`AlertEvent`]
+//! to a single notifier. New channels such as Slack or Discord therefore only need a
+//! [`Notifier`] implementation.
+
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use async_trait::async_trait;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use tokio::task::JoinHandle;
+use tracing::{debug, error};
+use crate::config;
+use crate::models::SpeedTestResult;
+
+/// Shared HTTP client for all outbound notifications.
+///
+/// Requests time out after 10 seconds so a slow endpoint cannot stall the scheduler.
+pub static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("failed to build HTTP client")
+});
+
+/// Reads the environment variable `HEARTBEAT_URL`, if set and non-empty.
+///
+/// When set, the URL receives a GET request after every successful run, for use
+/// with dead-man's-switch monitors such as healthchecks.io.
+pub fn heartbeat_url() -> Option<String> {
+    config::HEARTBEAT_URL.raw().filter(|s| !s.trim().is_empty())
+}
+
+/// Pings the heartbeat `url` with a GET request.
+///
+/// # Errors
+///
+/// Returns a description of the failure if the request fails or the endpoint
+/// responds with a non-success status.
+pub async fn send_heartbeat(url: &str) -> Result<(), String> {
+    match HTTP_CLIENT.get(url).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("Heartbeat to {} returned {}", url, response.status())),
+        Err(e) => Err(format!("Failed to send heartbeat to {}: {}", url, e)),
+    }
+}
+
+/// Reads the environment variable `WEBHOOK_URL`, if set and non-empty.
+///
+/// When set, the URL receives every successful result as a JSON POST.
+pub fn webhook_url() -> Option<String> {
+    config::WEBHOOK_URL.raw().filter(|s| !s.trim().is_empty())
+}
+
+/// Reads `ALERT_WEBHOOK_URL` and `ALERT_MIN_DOWNLOAD_MBPS`, the low download alert.
+///
+/// Returns `None` unless both are set, the URL is non-empty and the threshold is a
+/// positive number.
+pub fn slow_download_alert() -> Option<SlowDownloadNotifier> {
+    let url = config::ALERT_WEBHOOK_URL.raw().filter(|s| !s.trim().is_empty())?;
+    let min_download_mbps = config::ALERT_MIN_DOWNLOAD_MBPS
+        .parse::<f64>()
+        .filter(|mbps| mbps.is_finite() && *mbps > 0.0)?;
+    Some(SlowDownloadNotifier { url, min_download_mbps })
+}
+
+/// POSTs `result` as JSON to the webhook `url`.
+///
+/// # Errors
+///
+/// Returns a description of the failure if the request fails or the endpoint
+/// responds with a non-success status.
+pub async fn send_webhook(url: &str, result: &SpeedTestResult) -> Result<(), String> {
+    match HTTP_CLIENT.post(url).json(result).send().await {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("Webhook {} returned {}", url, response.status())),
+        Err(e) => Err(format!("Failed to call webhook {}: {}", url, e)),
+    }
+}
+
+/// Something worth telling the outside world about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertEvent {
+    /// A run completed and its result was cached.
+    RunSucceeded(Box<SpeedTestResult>),
+
+    /// A run failed and no result was cached.
+    RunFailed(String),
+}
+
+/// A notification channel.
+///
+/// Implementations decide which events they care about and return `Ok(())` for
+/// the rest.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Delivers `event` on this channel.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the failure if delivery failed.
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String>;
+}
+
+#[async_trait]
+impl<N: Notifier + ?Sized> Notifier for Arc<N> {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        (**self).notify(event).await
+    }
+}
+
+/// Logs a one-line summary of every event at debug level.
+///
+/// Always part of [`configured_notifiers`]. The run pipeline already logs each
+/// result and rate-limits failure logs (`FAILURE_LOG_EVERY`) at info and warn
+/// level, so this one stays below them and only shows with `RUST_LOG=debug`.
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        match event {
+            AlertEvent::RunSucceeded(result) => debug!(
+                "Notifying speedtest result: {:.1} Mbps down, {:.1} Mbps up, {:.1} ms ping",
+                result.download_mbps, result.upload_mbps, result.ping_ms
+            ),
+            AlertEvent::RunFailed(message) => debug!("Notifying failed speedtest run: {}", message),
+        }
+        Ok(())
+    }
+}
+
+/// POSTs every successful result as JSON to a webhook.
+pub struct WebhookNotifier {
+    /// URL receiving the result as a JSON POST body.
+    pub url: String,
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        match event {
+            AlertEvent::RunSucceeded(result) => send_webhook(&self.url, result).await,
+            AlertEvent::RunFailed(_) => Ok(()),
+        }
+    }
+}
+
+/// Pings a dead-man's-switch URL after every successful run.
+pub struct HeartbeatNotifier {
+    /// URL receiving a GET request.
+    pub url: String,
+}
+
+#[async_trait]
+impl Notifier for HeartbeatNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        match event {
+            AlertEvent::RunSucceeded(_) => send_heartbeat(&self.url).await,
+            AlertEvent::RunFailed(_) => Ok(()),
+        }
+    }
+}
+
+/// POSTs the result to a webhook whenever a run's download speed is below a threshold.
+pub struct SlowDownloadNotifier {
+    /// URL receiving the result as a JSON POST body.
+    pub url: String,
+
+    /// Download speeds below this many Mbps trigger the webhook.
+    pub min_download_mbps: f64,
+}
+
+#[async_trait]
+impl Notifier for SlowDownloadNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        match event {
+            AlertEvent::RunSucceeded(result) if result.download_mbps < self.min_download_mbps => {
+                send_webhook(&self.url, result).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Fans an event out to several notifiers concurrently.
+#[derive(Default)]
+pub struct CompositeNotifier {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl CompositeNotifier {
+    /// Creates a notifier delivering to every one of `notifiers`.
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>) -> Self {
+        CompositeNotifier { notifiers }
+    }
+
+    /// Returns `true` if no channels are configured.
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    /// Returns the number of channels.
+    pub fn len(&self) -> usize {
+        self.notifiers.len()
+    }
+}
+
+#[async_trait]
+impl Notifier for CompositeNotifier {
+    /// Delivers `event` to every channel, even if some of them fail.
+    ///
+    /// # Errors
+    ///
+    /// Returns the failures of all channels that failed, joined by `"; "`.
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        let errors: Vec<String> = join_all(self.notifiers.iter().map(|n| n.notify(event)))
+            .await
+            .into_iter()
+            .filter_map(Result::err)
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+static REGISTERED_NOTIFIERS: RwLock<Vec<Arc<dyn Notifier>>> = RwLock::new(Vec::new());
+
+/// Adds `notifier` to the channels every event is sent to, alongside those
+/// configured in the environment.
+///
+/// # Examples
+///
+/// ```
+/// # use async_trait::async_trait;
+/// use speedtest_statuspage::notify::{clear_registered_notifiers, register_notifier, AlertEvent, Notifier};
+///
+/// struct Pager;
+///
+/// #[async_trait]
+/// impl Notifier for Pager {
+///     async fn notify(&self, _event: &AlertEvent) -> Result<(), String> {
+///         Ok(())
+///     }
+/// }
+///
+/// register_notifier(Pager);
+/// clear_registered_notifiers();
+/// ```
+pub fn register_notifier(notifier: impl Notifier + 'static) {
+    REGISTERED_NOTIFIERS.write().unwrap_or_else(|e| e.into_inner()).push(Arc::new(notifier));
+}
+
+/// Removes every notifier added with [`register_notifier`].
+pub fn clear_registered_notifiers() {
+    REGISTERED_NOTIFIERS.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Builds a [`CompositeNotifier`] of the [`LogNotifier`], every channel configured
+/// in the environment and every notifier added with [`register_notifier`].
+pub fn configured_notifiers() -> CompositeNotifier {
+    let mut notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(LogNotifier)];
+    if let Some(url) = webhook_url() {
+        notifiers.push(Box::new(WebhookNotifier { url }));
+    }
+    if let Some(url) = heartbeat_url() {
+        notifiers.push(Box::new(HeartbeatNotifier { url }));
+    }
+    if let Some(alert) = slow_download_alert() {
+        notifiers.push(Box::new(alert));
+    }
+    for notifier in REGISTERED_NOTIFIERS.read().unwrap_or_else(|e| e.into_inner()).iter() {
+        notifiers.push(Box::new(notifier.clone()));
+    }
+    CompositeNotifier::new(notifiers)
+}
+
+/// Notifications sent by [`dispatch`] which may still be in flight.
+static IN_FLIGHT: Mutex<Vec<JoinHandle<()>>> = Mutex::new(Vec::new());
+
+/// Sends `event` to the configured channels in the background, logging any failures.
+///
+/// Returns immediately, so a slow channel never holds up the run that raised the
+/// event. Nothing is sent while [`maintenance`](crate::maintenance) mode is on.
+pub fn dispatch(event: AlertEvent) {
+    if crate::maintenance::is_active() {
+        return;
+    }
+    let notifier = configured_notifiers();
+    let delivery = tokio::spawn(async move {
+        if let Err(e) = notifier.notify(&event).await {
+            error!("Notification failed: {}", e);
+        }
+    });
+    let mut in_flight = IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner());
+    in_flight.retain(|delivery| !delivery.is_finished());
+    in_flight.push(delivery);
+}
+
+/// Waits until every notification sent by [`dispatch`] so far has been delivered
+/// or has failed.
+pub async fn flush() {
+    let in_flight = std::mem::take(&mut *IN_FLIGHT.lock().unwrap_or_else(|e| e.into_inner()));
+    join_all(in_flight).await;
+}
@@ -0,0 +1,268 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Backend for the official Ookla `speedtest` binary.
+//!
+//! Selected with `SPEEDTEST_BACKEND=ookla`.
+//!
+//! ## Units
+//!
+//! The Ookla CLI reports bandwidth in **bytes** per second, while
+//! [`SpeedTestResult`] stores bits per second, so bandwidths are multiplied by 8.
+//! The interquartile mean of the latency measured while downloading and uploading
+//! fills [`SpeedTestResult::download_latency_ms`] and
+//! [`SpeedTestResult::upload_latency_ms`], and `packetLoss` fills
+//! [`SpeedTestResult::packet_loss`] when the server measured it. Ookla reports no
+//! ISP averages or server coordinates, so those fields are left at their defaults.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use crate::{watchdog, ClientInfo, RunnerConfig, ServerInfo, SpeedTestResult, SpeedtestError, SpeedtestRunner};
+
+/// Latency measured while the link was loaded.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct OoklaLatency {
+    /// Interquartile mean latency in milliseconds.
+    #[serde(default)]
+    pub iqm: Option<f64>,
+}
+
+/// Ping measured before the transfer phases.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct OoklaPing {
+    /// Idle latency in milliseconds.
+    pub latency: f64,
+
+    /// Jitter in milliseconds.
+    #[serde(default)]
+    pub jitter: Option<f64>,
+}
+
+/// Outcome of the download or upload phase.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct OoklaTransfer {
+    /// Average throughput in **bytes** per second.
+    pub bandwidth: f64,
+
+    /// Bytes transferred.
+    #[serde(default)]
+    pub bytes: u64,
+
+    /// Latency while the transfer was running, if reported.
+    #[serde(default)]
+    pub latency: Option<OoklaLatency>,
+}
+
+/// Network interface the test ran on.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OoklaInterface {
+    /// Public IP address of the client.
+    #[serde(default)]
+    pub external_ip: String,
+}
+
+/// Server the test ran against.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct OoklaServer {
+    /// Numeric server identifier.
+    pub id: u64,
+
+    /// Server hostname.
+    #[serde(default)]
+    pub host: String,
+
+    /// Name of the operator, e.g. `Example Networks`.
+    #[serde(default)]
+    pub name: String,
+
+    /// City the server is located in.
+    #[serde(default)]
+    pub location: String,
+
+    /// Country the server is located in.
+    #[serde(default)]
+    pub country: String,
+}
+
+/// Link to the result on speedtest.net.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct OoklaShare {
+    /// URL of the result page.
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// The raw response from `speedtest --format=json`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::ookla::OoklaResponse;
+///
+/// let json = r#"{"timestamp": "2025-08-07T12:34:56Z", "ping": {"latency": 8.1},
+///     "download": {"bandwidth": 12500000, "bytes": 125000000},
+///     "upload": {"bandwidth": 2500000, "bytes": 25000000},
+///     "isp": "Example ISP", "server": {"id": 12345, "name": "Example Networks"}}"#;
+/// let parsed: OoklaResponse = serde_json::from_str(json).unwrap();
+/// assert_eq!(parsed.download.bandwidth, 12_500_000.0);
+/// assert_eq!(parsed.server.id, 12345);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OoklaResponse {
+    /// Time of the test as an ISO 8601 string.
+    pub timestamp: String,
+
+    /// Idle latency.
+    pub ping: OoklaPing,
+
+    /// Download phase.
+    pub download: OoklaTransfer,
+
+    /// Upload phase.
+    pub upload: OoklaTransfer,
+
+    /// Percentage of packets lost, if measured.
+    #[serde(default)]
+    pub packet_loss: Option<f64>,
+
+    /// Client's Internet Service Provider.
+    #[serde(default)]
+    pub isp: String,
+
+    /// Interface the test ran on.
+    #[serde(default)]
+    pub interface: OoklaInterface,
+
+    /// Server the test ran against.
+    pub server: OoklaServer,
+
+    /// Link to the result, if it was uploaded.
+    #[serde(default)]
+    pub result: Option<OoklaShare>,
+}
+
+/// Converts a bandwidth in bytes per second, as reported by Ookla, into bits per second.
+///
+/// # Examples
+///
+/// ```
+/// # use speedtest_statuspage::ookla::bandwidth_to_bps;
+/// assert_eq!(bandwidth_to_bps(12_500_000.0), 100_000_000.0);
+/// ```
+pub fn bandwidth_to_bps(bytes_per_sec: f64) -> f64 {
+    bytes_per_sec * 8.0
+}
+
+impl From<OoklaResponse> for SpeedTestResult {
+    fn from(data: OoklaResponse) -> Self {
+        let download_bps = bandwidth_to_bps(data.download.bandwidth);
+        let upload_bps = bandwidth_to_bps(data.upload.bandwidth);
+
+        SpeedTestResult {
+            bytes_received: data.download.bytes,
+            bytes_sent: data.upload.bytes,
+            download_bps,
+            upload_bps,
+            download_mbps: download_bps / 1_000_000.0,
+            upload_mbps: upload_bps / 1_000_000.0,
+            ping_ms: data.ping.latency,
+            jitter_ms: data.ping.jitter,
+            packet_loss: data.packet_loss.filter(|loss| (0.0..=100.0).contains(loss)),
+            download_latency_ms: data.download.latency.and_then(|l| l.iqm),
+            upload_latency_ms: data.upload.latency.and_then(|l| l.iqm),
+            client: ClientInfo {
+                ip: data.interface.external_ip,
+                isp: data.isp,
+                ..Default::default()
+            },
+            server: ServerInfo {
+                country: data.server.country,
+                host: data.server.host,
+                id: data.server.id.to_string(),
+                latency: data.ping.latency,
+                name: data.server.location,
+                sponsor: data.server.name,
+                ..Default::default()
+            },
+            share: data.result.and_then(|r| r.url).map(serde_json::Value::String),
+            timestamp: data.timestamp,
+            ..Default::default()
+        }
+    }
+}
+
+/// Runner for the official Ookla backend, invoking `speedtest --format=json`.
+///
+/// The licence and GDPR prompts are accepted non-interactively. Only the first of
+/// [`RunnerConfig::server_ids`] is used, as the Ookla CLI accepts a single server;
+/// [`RunnerConfig::proxy`] and [`RunnerConfig::extra_args`] also apply.
+pub struct OoklaSpeedtestRunner;
+
+impl OoklaSpeedtestRunner {
+    /// Builds the command-line arguments for `cfg`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use speedtest_statuspage::RunnerConfig;
+    /// # use speedtest_statuspage::ookla::OoklaSpeedtestRunner;
+    /// let cfg = RunnerConfig { server_ids: vec!["1234".to_string()], ..Default::default() };
+    /// assert_eq!(
+    ///     OoklaSpeedtestRunner::build_args(&cfg),
+    ///     ["--format=json", "--accept-license", "--accept-gdpr", "--server-id=1234"],
+    /// );
+    /// ```
+    pub fn build_args(cfg: &RunnerConfig) -> Vec<String> {
+        let mut args: Vec<String> = ["--format=json", "--accept-license", "--accept-gdpr"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if let Some(id) = cfg.server_ids.first() {
+            args.push(format!("--server-id={}", id));
+        }
+        args.extend(cfg.extra_args.iter().cloned());
+        args
+    }
+}
+
+#[async_trait]
+impl SpeedtestRunner for OoklaSpeedtestRunner {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        let mut command = Command::new("speedtest");
+        command.args(Self::build_args(cfg));
+        if let Some(proxy) = &cfg.proxy {
+            command.env("HTTP_PROXY", proxy).env("HTTPS_PROXY", proxy);
+        }
+
+        let output = watchdog::run_watched(&mut command, "speedtest", cfg.stall_timeout).await?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(SpeedtestError::CommandFailed(format!("speedtest failed: {}", stderr)))
+        }
+    }
+
+    fn parse_output(&self, raw: &str) -> Result<SpeedTestResult, String> {
+        serde_json::from_str::<OoklaResponse>(raw)
+            .map(SpeedTestResult::from)
+            .map_err(|e| e.to_string())
+    }
+}
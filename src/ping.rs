@@ -0,0 +1,255 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Latency to a host of the user's choosing, independent of the speedtest server.
+//!
+//! The ping reported by the backends is the latency to the chosen speedtest server,
+//! which is not necessarily representative of the paths the user cares about. When
+//! `PING_TARGET` is set, the time taken to open a TCP connection to that host is
+//! measured after every run and stored as `external_ping_ms`. With
+//! `PROBE_METHOD=icmp` the system `ping` command is used instead (see
+//! [`ProbeMethod`]).
+
+use std::fmt;
+use std::process::Stdio;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::time;
+use tracing::warn;
+use crate::config;
+use crate::models::SpeedTestResult;
+
+/// Port probed when `PING_TARGET` does not name one.
+pub const DEFAULT_PING_PORT: u16 = 443;
+
+/// Maximum time a single probe may take before it counts as failed.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A `host:port` to measure latency to.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::ping::PingTarget;
+///
+/// let target: PingTarget = "vpn.example.com:1194".parse().unwrap();
+/// assert_eq!((target.host.as_str(), target.port), ("vpn.example.com", 1194));
+///
+/// let target: PingTarget = "[2001:db8::1]".parse().unwrap();
+/// assert_eq!((target.host.as_str(), target.port), ("2001:db8::1", 443));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PingTarget {
+    /// Host name or IP address, without brackets.
+    pub host: String,
+
+    /// TCP port, [`DEFAULT_PING_PORT`] unless given.
+    pub port: u16,
+}
+
+impl FromStr for PingTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (host, port) = if let Some(rest) = s.strip_prefix('[') {
+            let (host, rest) = rest.split_once(']').ok_or_else(|| format!("unclosed '[' in ping target '{}'", s))?;
+            match rest {
+                "" => (host, None),
+                _ => (host, Some(rest.strip_prefix(':').ok_or_else(|| format!("invalid ping target '{}'", s))?)),
+            }
+        } else if s.matches(':').count() > 1 {
+            // A bare IPv6 address; a port requires the bracketed form.
+            (s, None)
+        } else {
+            match s.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (s, None),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in ping target '{}'", s));
+        }
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .ok()
+                .filter(|&p| p != 0)
+                .ok_or_else(|| format!("invalid port in ping target '{}'", s))?,
+            None => DEFAULT_PING_PORT,
+        };
+        Ok(PingTarget { host: host.to_string(), port })
+    }
+}
+
+impl fmt::Display for PingTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Returns the target configured by `PING_TARGET`, if any.
+///
+/// An invalid value is logged and ignored.
+pub fn ping_target() -> Option<PingTarget> {
+    let raw = config::PING_TARGET.raw().filter(|s| !s.trim().is_empty())?;
+    raw.parse()
+        .map_err(|e| warn!("Ignoring {}: {}", config::PING_TARGET.name, e))
+        .ok()
+}
+
+/// Something able to measure the latency to a [`PingTarget`].
+#[async_trait]
+pub trait LatencyProbe: Send + Sync {
+    /// Measures the round-trip latency to `target`.
+    async fn probe(&self, target: &PingTarget) -> Result<Duration, String>;
+}
+
+/// Measures latency as the time taken to establish a TCP connection.
+///
+/// Unlike ICMP, this needs no privileges and works through most firewalls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpProbe;
+
+#[async_trait]
+impl LatencyProbe for TcpProbe {
+    async fn probe(&self, target: &PingTarget) -> Result<Duration, String> {
+        let started = Instant::now();
+        match time::timeout(PROBE_TIMEOUT, TcpStream::connect((target.host.as_str(), target.port))).await {
+            Ok(Ok(_)) => Ok(started.elapsed()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+        }
+    }
+}
+
+/// Measures latency with a single ICMP echo, by running the system `ping` command.
+///
+/// ICMP is often blocked on cloud hosts and may need privileges, so this is opt-in
+/// via `PROBE_METHOD=icmp`. The target's port is ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IcmpProbe;
+
+#[async_trait]
+impl LatencyProbe for IcmpProbe {
+    async fn probe(&self, target: &PingTarget) -> Result<Duration, String> {
+        let mut command = Command::new("ping");
+        command
+            .args(["-n", "-c", "1", "-W"])
+            .arg(PROBE_TIMEOUT.as_secs().to_string())
+            .arg(&target.host)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        let output = match time::timeout(PROBE_TIMEOUT + Duration::from_secs(1), command.output()).await {
+            Ok(Ok(output)) => output,
+            Ok(Err(e)) => return Err(format!("could not run ping: {}", e)),
+            Err(_) => return Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())),
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match parse_ping_time(&stdout) {
+            Some(latency) if output.status.success() => Ok(latency),
+            _ => Err(format!("no reply ({})", output.status)),
+        }
+    }
+}
+
+/// Extracts the round-trip time from the `time=12.3 ms` field of `ping` output.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use speedtest_statuspage::ping::parse_ping_time;
+///
+/// let output = "64 bytes from 1.1.1.1: icmp_seq=1 ttl=57 time=12.5 ms";
+/// assert_eq!(parse_ping_time(output), Some(Duration::from_micros(12_500)));
+/// assert_eq!(parse_ping_time("1 packets transmitted, 0 received"), None);
+/// ```
+pub fn parse_ping_time(output: &str) -> Option<Duration> {
+    let (_, rest) = output.split_once("time=")?;
+    let ms: f64 = rest.split(|c: char| c != '.' && !c.is_ascii_digit()).next()?.parse().ok()?;
+    Duration::try_from_secs_f64(ms / 1000.0).ok()
+}
+
+/// How latency to `PING_TARGET` is measured, selected via `PROBE_METHOD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProbeMethod {
+    /// Time to open a TCP connection, with [`TcpProbe`] (`tcp`, the default).
+    #[default]
+    Tcp,
+
+    /// Round trip of an ICMP echo, with [`IcmpProbe`] (`icmp`).
+    Icmp,
+}
+
+impl ProbeMethod {
+    /// Returns the name used to select this method.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProbeMethod::Tcp => "tcp",
+            ProbeMethod::Icmp => "icmp",
+        }
+    }
+
+    /// Reads `PROBE_METHOD`, falling back to [`ProbeMethod::Tcp`] with a warning on
+    /// unknown values.
+    pub fn from_env() -> Self {
+        match config::PROBE_METHOD.raw() {
+            Some(s) => s.parse().unwrap_or_else(|e| {
+                warn!("{}; falling back to {}", e, ProbeMethod::default().name());
+                ProbeMethod::default()
+            }),
+            None => ProbeMethod::default(),
+        }
+    }
+
+    /// Returns the probe implementing this method.
+    pub fn probe(&self) -> Box<dyn LatencyProbe> {
+        match self {
+            ProbeMethod::Tcp => Box::new(TcpProbe),
+            ProbeMethod::Icmp => Box::new(IcmpProbe),
+        }
+    }
+}
+
+impl FromStr for ProbeMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "tcp" => Ok(ProbeMethod::Tcp),
+            "icmp" => Ok(ProbeMethod::Icmp),
+            other => Err(format!("unknown probe method '{}'", other)),
+        }
+    }
+}
+
+/// Probes `target` and stores the latency on `result` as `external_ping_ms`.
+///
+/// A failed probe is logged and leaves the field unset.
+pub async fn record_external_ping(result: &mut SpeedTestResult, probe: &dyn LatencyProbe, target: &PingTarget) {
+    match probe.probe(target).await {
+        Ok(latency) => result.external_ping_ms = Some(latency.as_secs_f64() * 1000.0),
+        Err(e) => warn!("Failed to measure latency to {}: {}", target, e),
+    }
+}
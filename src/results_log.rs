@@ -0,0 +1,276 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Append-only log of every result, in JSON Lines.
+//!
+//! When `RESULTS_LOG` is set, every successful result is appended to it as one JSON
+//! object per line. Each line is written whole and flushed before the run
+//! finishes, so a crash loses at most the line being written. A log that cannot be
+//! written is reported and otherwise ignored.
+//!
+//! At startup the last `HISTORY_SIZE` lines are read back into the history. Only
+//! the tail of the file is read, so a log of any size loads quickly.
+//!
+//! With `RESULTS_LOG_ARCHIVE=true`, [`run_archival`] moves the log into
+//! `history/YYYY-MM-DD.ndjson.gz` next to it shortly after each local midnight, so
+//! the active file only holds the current day.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::{Local, NaiveDate};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tokio::time;
+use tracing::{error, info, warn};
+use crate::config;
+use crate::models::{migrate_result, SpeedTestResult};
+
+/// Held while appending to or rolling the log, so no line is written to a file
+/// that is being archived.
+static LOG_LOCK: Mutex<()> = Mutex::new(());
+
+/// Bytes read at a time when scanning the log backwards from its end.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// Returns the configured results log path, if any.
+pub fn results_log_path() -> Option<PathBuf> {
+    config::RESULTS_LOG.raw().filter(|s| !s.trim().is_empty()).map(PathBuf::from)
+}
+
+/// Appends `result` to `path` as a single JSON line, creating the file if missing.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, written or flushed.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{results_log, SpeedTestResult};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("speed.jsonl");
+/// results_log::append_result(&path, &SpeedTestResult::default()).unwrap();
+/// results_log::append_result(&path, &SpeedTestResult::default()).unwrap();
+/// assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+/// ```
+pub fn append_result(path: &Path, result: &SpeedTestResult) -> io::Result<()> {
+    let mut line = serde_json::to_vec(result).map_err(io::Error::other)?;
+    line.push(b'\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)?;
+    file.flush()?;
+    file.sync_data()
+}
+
+/// Appends `result` to the log at `RESULTS_LOG`, if configured. Failures are logged.
+pub fn record_result(result: &SpeedTestResult) {
+    let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(path) = results_log_path()
+        && let Err(e) = append_result(&path, result)
+    {
+        warn!("Failed to append speedtest result to {}: {}", path.display(), e);
+    }
+}
+
+/// Returns the last `n` lines of the file at `path`, oldest first.
+///
+/// The file is read backwards from its end in fixed-size chunks until enough lines
+/// have been seen, so the cost depends on `n` rather than on the size of the file.
+/// A file with fewer than `n` lines is returned whole. Invalid UTF-8 is replaced.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::results_log;
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("speed.jsonl");
+/// std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+/// assert_eq!(results_log::read_last_lines(&path, 2).unwrap(), ["two", "three"]);
+/// assert_eq!(results_log::read_last_lines(&path, 5).unwrap().len(), 3);
+/// ```
+pub fn read_last_lines(path: &Path, n: usize) -> io::Result<Vec<String>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut tail = Vec::new();
+    let mut newlines = 0;
+    // The newline before the first wanted line is the (n + 1)th from the end.
+    while pos > 0 && newlines <= n {
+        let start = pos.saturating_sub(TAIL_CHUNK);
+        let mut chunk = vec![0; (pos - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|&&b| b == b'\n').count();
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        pos = start;
+    }
+
+    let text = String::from_utf8_lossy(&tail);
+    let lines: Vec<&str> = text.lines().collect();
+    Ok(lines[lines.len().saturating_sub(n)..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Reads the last `n` results logged at `path`, oldest first.
+///
+/// Each line is passed through [`migrate_result`], so results written by older
+/// versions load too. Blank lines are skipped, as are lines that do not parse,
+/// such as one cut short by a crash; the latter are logged.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read.
+pub fn load_recent(path: &Path, n: usize) -> io::Result<Vec<SpeedTestResult>> {
+    let lines = read_last_lines(path, n)?;
+    Ok(lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .and_then(migrate_result)
+                .map_err(|e| warn!("Skipping unreadable line in {}: {}", path.display(), e))
+                .ok()
+        })
+        .collect())
+}
+
+/// Returns the day whose log is due to be archived on `today`, if any.
+///
+/// The log is rolled once per day boundary: when the last archived day is before
+/// yesterday, the file is archived under yesterday's date.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use speedtest_statuspage::results_log::archive_due;
+///
+/// let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+/// assert_eq!(archive_due(day(15), day(14)), None);
+/// assert_eq!(archive_due(day(16), day(14)), Some(day(15)));
+/// ```
+pub fn archive_due(today: NaiveDate, last_archived: NaiveDate) -> Option<NaiveDate> {
+    today.pred_opt().filter(|yesterday| last_archived < *yesterday)
+}
+
+/// Returns the archive the log at `log` is moved into for `day`:
+/// `history/YYYY-MM-DD.ndjson.gz` in the log's directory.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use chrono::NaiveDate;
+/// use speedtest_statuspage::results_log::archive_path;
+///
+/// let day = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+/// let archive = archive_path(Path::new("/var/lib/speed.jsonl"), day);
+/// assert_eq!(archive, Path::new("/var/lib/history/2026-10-14.ndjson.gz"));
+/// ```
+pub fn archive_path(log: &Path, day: NaiveDate) -> PathBuf {
+    history_dir(log).join(format!("{}.ndjson.gz", day.format("%Y-%m-%d")))
+}
+
+/// Returns the directory archives of the log at `log` are kept in.
+fn history_dir(log: &Path) -> PathBuf {
+    log.parent().unwrap_or(Path::new("")).join("history")
+}
+
+/// Returns the most recent day archived next to the log at `log`, if any.
+pub fn last_archived_day(log: &Path) -> Option<NaiveDate> {
+    fs::read_dir(history_dir(log))
+        .ok()?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            NaiveDate::parse_from_str(name.to_str()?.strip_suffix(".ndjson.gz")?, "%Y-%m-%d").ok()
+        })
+        .max()
+}
+
+/// Moves the log at `log` into the gzip archive for `day` and leaves an empty log
+/// in its place, returning the archive path.
+///
+/// The log is first renamed aside, so results recorded meanwhile go to the fresh
+/// file, and is removed once compressed. An existing archive for `day` gains a
+/// second gzip member rather than being overwritten. A missing log is archived as
+/// an empty one.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be renamed or read, or the archive cannot be
+/// written. A log left renamed aside by a failure is archived on the next roll.
+pub fn archive_log(log: &Path, day: NaiveDate) -> io::Result<PathBuf> {
+    let mut rolling = log.as_os_str().to_owned();
+    rolling.push(".rolling");
+    let rolling = PathBuf::from(rolling);
+    {
+        let _guard = LOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        if !rolling.exists() {
+            match fs::rename(log, &rolling) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    File::create(&rolling)?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        OpenOptions::new().create(true).append(true).open(log)?;
+    }
+
+    let archive = archive_path(log, day);
+    fs::create_dir_all(history_dir(log))?;
+    let file = OpenOptions::new().create(true).append(true).open(&archive)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    io::copy(&mut File::open(&rolling)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(&rolling)?;
+    Ok(archive)
+}
+
+/// Background task archiving the log at `log` once per day (see [`archive_due`]).
+///
+/// The date is checked every minute against local time. Before the first archive
+/// exists, the current log is treated as today's, so it is first rolled after the
+/// next midnight.
+pub async fn run_archival(log: PathBuf) {
+    let today = Local::now().date_naive();
+    let mut last_archived = last_archived_day(&log).or(today.pred_opt()).unwrap_or(today);
+    let mut ticker = time::interval(Duration::from_secs(60));
+    loop {
+        ticker.tick().await;
+        let Some(day) = archive_due(Local::now().date_naive(), last_archived) else {
+            continue;
+        };
+        let path = log.clone();
+        match tokio::task::spawn_blocking(move || archive_log(&path, day)).await {
+            Ok(Ok(archive)) => info!("Archived {} to {}", log.display(), archive.display()),
+            Ok(Err(e)) => error!("Failed to archive {}: {}", log.display(), e),
+            Err(e) => error!("Archiving {} panicked: {}", log.display(), e),
+        }
+        last_archived = day;
+    }
+}
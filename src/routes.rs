@@ -0,0 +1,77 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! The endpoints served by each HTTP listener.
+//!
+//! Every endpoint is served on the main `BIND_ADDRESS` listener, unless
+//! `METRICS_BIND_PORT` moves `/metrics` and `/health` to a listener of their own,
+//! so they can be firewalled apart from the public pages.
+
+use actix_web::web;
+use crate::compare::compare_backends_endpoint;
+use crate::isp::{isp_average_endpoint, isp_percentile_endpoint};
+use crate::maintenance::maintenance_endpoint;
+use crate::run::run_endpoint;
+use crate::sparkline::sparkline_endpoint;
+use crate::stats::{runtime_stats_endpoint, speed_stats_endpoint};
+use crate::{
+    clear_history_endpoint, debug, health, history_csv, history_endpoint, metrics, raw_download, raw_upload,
+    speed_csv, speedtest,
+};
+
+/// The listener an app is built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Listener {
+    /// The main listener, serving `/metrics` and `/health` too unless
+    /// `separate_metrics` is set.
+    Main {
+        /// `/metrics` and `/health` are served by the [`Listener::Metrics`] one.
+        separate_metrics: bool,
+    },
+
+    /// The `METRICS_BIND_PORT` listener, serving only `/metrics` and `/health`.
+    Metrics,
+}
+
+/// Returns an app configuration registering the endpoints `listener` serves.
+///
+/// The test endpoints are registered separately (see [`crate::testmode::configure`]).
+pub fn configure(listener: Listener) -> impl Fn(&mut web::ServiceConfig) + Clone {
+    move |cfg| {
+        if listener != (Listener::Main { separate_metrics: true }) {
+            cfg.service(health).service(metrics);
+        }
+        if listener == Listener::Metrics {
+            return;
+        }
+        cfg.service(speedtest)
+            .service(history_endpoint)
+            .service(history_csv)
+            .service(clear_history_endpoint)
+            .service(speed_csv)
+            .service(sparkline_endpoint)
+            .service(raw_download)
+            .service(raw_upload)
+            .service(isp_average_endpoint)
+            .service(isp_percentile_endpoint)
+            .service(compare_backends_endpoint)
+            .service(debug::debug_env)
+            .service(maintenance_endpoint)
+            .service(run_endpoint)
+            .service(runtime_stats_endpoint)
+            .service(speed_stats_endpoint);
+    }
+}
@@ -0,0 +1,74 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Speedtests triggered on demand with `POST /run`.
+//!
+//! A triggered run goes through the same pipeline as a scheduled one and is cached
+//! with [`Origin::Manual`]. Only one run happens at a time: a request made while any
+//! run, scheduled or triggered, is in progress gets HTTP 429 Too Many Requests. When
+//! `RUN_TOKEN` is set, requests must carry `Authorization: Bearer <RUN_TOKEN>`.
+
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use crate::models::Origin;
+use crate::{access, capture, config, run_speedtest_and_cache_as, speedtest_backend, RunnerConfig, SpeedtestRunner,
+            RUN_LOCK};
+
+/// What `POST /run` runs, registered as app data.
+pub struct ManualRun {
+    /// Runner used for triggered runs.
+    pub runner: Box<dyn SpeedtestRunner>,
+
+    /// Configuration handed to the runner.
+    pub cfg: RunnerConfig,
+
+    /// Bearer token requests must present, or `None` to accept any request.
+    pub token: Option<String>,
+}
+
+impl ManualRun {
+    /// Builds the configured backend's runner, its configuration and `RUN_TOKEN`
+    /// from the process environment.
+    pub fn from_env() -> Self {
+        ManualRun {
+            runner: capture::with_capture_from_env(speedtest_backend().runner()),
+            cfg: RunnerConfig::from_env(),
+            token: config::RUN_TOKEN.raw().filter(|token| !token.is_empty()),
+        }
+    }
+}
+
+/// HTTP POST endpoint `/run` runs a speedtest now and caches the result.
+///
+/// Returns the new result as JSON, or HTTP 500 Internal Server Error with the reason
+/// the run failed. Responds HTTP 429 Too Many Requests while another run is in
+/// progress, and HTTP 401 Unauthorized when `RUN_TOKEN` is set and the request does
+/// not carry it as a bearer token.
+#[post("/run")]
+pub async fn run_endpoint(req: HttpRequest, manual: web::Data<ManualRun>) -> impl Responder {
+    if let Some(token) = &manual.token
+        && let Some(unauthorized) = access::require_bearer(&req, token)
+    {
+        return unauthorized;
+    }
+    let Ok(_running) = RUN_LOCK.try_lock() else {
+        return HttpResponse::TooManyRequests().body("A speedtest is already running");
+    };
+
+    match run_speedtest_and_cache_as(manual.runner.as_ref(), &manual.cfg, Origin::Manual).await {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
@@ -0,0 +1,205 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Time-of-day dependent run intervals.
+//!
+//! `SCHEDULE` lists windows of local time, each with its own interval, e.g.
+//! `08:00-23:00@5m,23:00-08:00@30m`. The windows must cover the whole day without
+//! overlapping. Without `SCHEDULE`, every run is `INTERVAL_MINUTES` apart.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{NaiveTime, Timelike};
+use crate::{config, min_frequency_duration};
+
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+/// A window of the day and the interval between runs inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    /// Start of the window (inclusive), in minutes since midnight.
+    pub start: u16,
+
+    /// End of the window (exclusive), in minutes since midnight. A window ending at
+    /// or before its start wraps past midnight.
+    pub end: u16,
+
+    /// Interval between runs inside the window.
+    pub interval: Duration,
+}
+
+impl Window {
+    /// Returns whether the window contains `minute` (minutes since midnight).
+    pub fn contains(&self, minute: u16) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}@{}s", format_minute(self.start), format_minute(self.end), self.interval.as_secs())
+    }
+}
+
+fn format_minute(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+fn parse_minute(s: &str) -> Result<u16, String> {
+    let time = NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|_| format!("invalid time '{}', expected HH:MM", s.trim()))?;
+    Ok((time.hour() * 60 + time.minute()) as u16)
+}
+
+/// Parses an interval such as `30s`, `5m` or `1h`; a bare number is in minutes.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// use speedtest_statuspage::schedule::parse_interval;
+///
+/// assert_eq!(parse_interval("5m"), Ok(Duration::from_secs(300)));
+/// assert_eq!(parse_interval("90s"), Ok(Duration::from_secs(90)));
+/// assert_eq!(parse_interval("15"), Ok(Duration::from_secs(900)));
+/// assert!(parse_interval("0m").is_err());
+/// assert!(parse_interval("99999999999999999h").is_err());
+/// ```
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit_secs) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 3600),
+        _ => (s, 60),
+    };
+    match number.parse::<u64>() {
+        Ok(n) if n > 0 => n
+            .checked_mul(unit_secs)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("invalid interval '{}', too long to count in seconds", s)),
+        _ => Err(format!("invalid interval '{}', expected a positive number with an s, m or h suffix", s)),
+    }
+}
+
+/// Run intervals for every time of day.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// use chrono::NaiveTime;
+/// use speedtest_statuspage::schedule::Schedule;
+///
+/// let schedule: Schedule = "08:00-23:00@5m,23:00-08:00@30m".parse().unwrap();
+/// let at = |h, m| schedule.interval_at(NaiveTime::from_hms_opt(h, m, 0).unwrap());
+/// assert_eq!(at(12, 0), Duration::from_secs(300));
+/// assert_eq!(at(3, 0), Duration::from_secs(1800));
+///
+/// assert!("08:00-23:00@5m".parse::<Schedule>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    windows: Vec<Window>,
+}
+
+impl Schedule {
+    /// Creates a schedule running every `interval` all day.
+    pub fn fixed(interval: Duration) -> Self {
+        Schedule { windows: vec![Window { start: 0, end: 0, interval }] }
+    }
+
+    /// Builds the schedule from `SCHEDULE`, or from `INTERVAL_MINUTES` when unset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming `SCHEDULE` if it is set but invalid.
+    pub fn from_env() -> Result<Self, String> {
+        match config::SCHEDULE.raw().filter(|s| !s.trim().is_empty()) {
+            Some(spec) => spec.parse().map_err(|e| format!("invalid {}: {}", config::SCHEDULE.name, e)),
+            None => Ok(Schedule::fixed(min_frequency_duration())),
+        }
+    }
+
+    /// Returns the windows making up the schedule.
+    pub fn windows(&self) -> &[Window] {
+        &self.windows
+    }
+
+    /// Returns the interval for the window containing `time`.
+    pub fn interval_at(&self, time: NaiveTime) -> Duration {
+        let minute = (time.hour() * 60 + time.minute()) as u16;
+        self.windows
+            .iter()
+            .find(|window| window.contains(minute))
+            .unwrap_or(&self.windows[0])
+            .interval
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    /// Parses comma-separated `HH:MM-HH:MM@interval` windows.
+    ///
+    /// Fails if a window is malformed, if two windows overlap, or if part of the
+    /// day is not covered by any window.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut windows = Vec::new();
+        for spec in s.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+            let (range, interval) = spec
+                .split_once('@')
+                .ok_or_else(|| format!("window '{}' has no @interval", spec))?;
+            let (start, end) = range
+                .split_once('-')
+                .ok_or_else(|| format!("window '{}' is not of the form HH:MM-HH:MM", spec))?;
+            windows.push(Window {
+                start: parse_minute(start)?,
+                end: parse_minute(end)?,
+                interval: parse_interval(interval)?,
+            });
+        }
+        if windows.is_empty() {
+            return Err("no windows given".to_string());
+        }
+
+        let mut owner: Vec<Option<usize>> = vec![None; MINUTES_PER_DAY];
+        for (i, window) in windows.iter().enumerate() {
+            for minute in 0..MINUTES_PER_DAY as u16 {
+                if window.contains(minute) {
+                    if let Some(other) = owner[minute as usize] {
+                        return Err(format!("windows {} and {} overlap", windows[other], window));
+                    }
+                    owner[minute as usize] = Some(i);
+                }
+            }
+        }
+        if let Some(gap_start) = owner.iter().position(Option::is_none) {
+            let gap_end = (gap_start..MINUTES_PER_DAY).find(|&m| owner[m].is_some()).unwrap_or(MINUTES_PER_DAY);
+            return Err(format!(
+                "no window covers {}-{}",
+                format_minute(gap_start as u16),
+                format_minute((gap_end % MINUTES_PER_DAY) as u16)
+            ));
+        }
+        Ok(Schedule { windows })
+    }
+}
@@ -0,0 +1,156 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Persistence of the latest result across restarts.
+//!
+//! When `SNAPSHOT_FILE` is set, every successful result is written to it, and the
+//! scheduler loads it into the cache at startup so that `/speed` does not return
+//! 503 while the first run after a restart is in progress.
+//!
+//! If the filesystem turns out to be read-only, persistence is switched off for
+//! the rest of the session after one warning; serving continues from memory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::{error, warn};
+use crate::config;
+use crate::models::{migrate_result, SpeedTestResult};
+
+/// Returns the configured snapshot path, if any.
+pub fn snapshot_path() -> Option<PathBuf> {
+    config::SNAPSHOT_FILE
+        .raw()
+        .filter(|s| !s.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Atomically writes `result` to `path` as JSON (see [`write_atomic`]).
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed.
+pub fn write_snapshot(path: &Path, result: &SpeedTestResult) -> io::Result<()> {
+    let json = serde_json::to_string(result).map_err(io::Error::other)?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// Replaces the file at `path` with `contents`.
+///
+/// The contents are written to a temporary file alongside `path` and then renamed
+/// over it, so a crash mid-write never leaves a truncated file behind.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Tracks whether writes should still be attempted.
+///
+/// The first write failing because the filesystem is read-only or not writable
+/// by us disables all later writes; other errors are returned as usual.
+#[derive(Debug)]
+pub struct Persistence {
+    enabled: AtomicBool,
+}
+
+impl Persistence {
+    /// Creates a tracker with persistence enabled.
+    pub const fn new() -> Self {
+        Persistence { enabled: AtomicBool::new(true) }
+    }
+
+    /// Returns whether writes are still being attempted.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Runs `write` unless persistence has been disabled.
+    ///
+    /// Returns `Ok(false)` when the write was skipped, either because persistence
+    /// was already disabled or because `write` just hit a permission error.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `write` other than a permission or read-only error.
+    pub fn write(&self, what: &Path, write: impl FnOnce() -> io::Result<()>) -> io::Result<bool> {
+        if !self.is_enabled() {
+            return Ok(false);
+        }
+        match write() {
+            Ok(()) => Ok(true),
+            Err(e) if is_read_only_error(&e) => {
+                if self.enabled.swap(false, Ordering::Relaxed) {
+                    warn!(
+                        "Cannot write {} ({}); persistence disabled for this session, serving from memory",
+                        what.display(),
+                        e
+                    );
+                }
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Default for Persistence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_read_only_error(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem)
+}
+
+static PERSISTENCE: Persistence = Persistence::new();
+
+/// Writes the snapshot of `result` to `path`, unless persistence was disabled by
+/// an earlier permission error. Other failures are logged.
+pub fn persist_snapshot(path: &Path, result: &SpeedTestResult) {
+    if let Err(e) = PERSISTENCE.write(path, || write_snapshot(path, result)) {
+        error!("Failed to write snapshot {}: {}", path.display(), e);
+    }
+}
+
+/// Loads a snapshot from `path`, if present and valid.
+///
+/// Snapshots written by older releases are upgraded with [`migrate_result`]. A
+/// missing file is silently ignored; an unreadable or invalid one is logged.
+pub fn load_snapshot(path: &Path) -> Option<SpeedTestResult> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            error!("Failed to read snapshot {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    serde_json::from_str(&contents)
+        .and_then(migrate_result)
+        .map_err(|e| warn!("Ignoring invalid snapshot in {}: {}", path.display(), e))
+        .ok()
+}
@@ -0,0 +1,113 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! A tiny SVG line chart of recent download speeds, served at
+//! `/speed/sparkline.svg` for embedding in pages that cannot run scripts.
+//!
+//! [`sparkline_points`] maps the values onto the chart and [`render_sparkline`]
+//! wraps them in a single `<polyline>`.
+
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::Deserialize;
+use crate::get_history;
+
+/// Width of the chart in pixels unless `?width=` is given.
+pub const DEFAULT_WIDTH: u32 = 120;
+
+/// Height of the chart in pixels unless `?height=` is given.
+pub const DEFAULT_HEIGHT: u32 = 30;
+
+/// Largest width or height accepted; larger requests are clamped.
+pub const MAX_SIZE: u32 = 4000;
+
+/// Maps `values` onto a `width` by `height` chart, oldest on the left.
+///
+/// The points are spread evenly across the width, and the lowest value sits on
+/// the bottom edge and the highest on the top edge (SVG's y axis points down).
+/// A single point is centred horizontally, and equal values draw a flat line
+/// through the middle. Non-finite values are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::sparkline::sparkline_points;
+///
+/// let points = sparkline_points(&[10.0, 30.0, 20.0], 100.0, 20.0);
+/// assert_eq!(points, vec![(0.0, 20.0), (50.0, 0.0), (100.0, 10.0)]);
+/// ```
+pub fn sparkline_points(values: &[f64], width: f64, height: f64) -> Vec<(f64, f64)> {
+    let values: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let last = values.len().saturating_sub(1);
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let x = if last == 0 { width / 2.0 } else { width * i as f64 / last as f64 };
+            let y = if max > min { height * (max - value) / (max - min) } else { height / 2.0 };
+            (x, y)
+        })
+        .collect()
+}
+
+/// Renders `values` as an SVG document `width` by `height` pixels large (see
+/// [`sparkline_points`]).
+///
+/// Without values the chart is blank: an empty `<svg>` of the requested size.
+pub fn render_sparkline(values: &[f64], width: u32, height: u32) -> String {
+    let points = sparkline_points(values, width as f64, height as f64);
+    let line = if points.is_empty() {
+        String::new()
+    } else {
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        format!(
+            "<polyline fill=\"none\" stroke=\"currentColor\" stroke-width=\"1\" points=\"{}\"/>",
+            points.join(" ")
+        )
+    };
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">{line}</svg>",
+        w = width,
+        h = height,
+        line = line
+    )
+}
+
+/// Query parameters accepted by [`sparkline_endpoint`].
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SparklineQuery {
+    /// Chart width in pixels.
+    pub width: Option<u32>,
+
+    /// Chart height in pixels.
+    pub height: Option<u32>,
+}
+
+/// HTTP GET endpoint `/speed/sparkline.svg` draws the download speeds of the
+/// retained history as an `image/svg+xml` line chart.
+///
+/// `?width=` and `?height=` set the size in pixels, clamped to 1 to
+/// [`MAX_SIZE`]; non-numeric values are rejected with HTTP 400. An empty history
+/// gives a blank chart with HTTP 200.
+#[get("/speed/sparkline.svg")]
+pub async fn sparkline_endpoint(query: web::Query<SparklineQuery>) -> impl Responder {
+    let size = |value: Option<u32>, default| value.unwrap_or(default).clamp(1, MAX_SIZE);
+    let downloads: Vec<f64> = get_history().iter().map(|r| r.download_mbps).collect();
+    HttpResponse::Ok()
+        .content_type("image/svg+xml")
+        .body(render_sparkline(&downloads, size(query.width, DEFAULT_WIDTH), size(query.height, DEFAULT_HEIGHT)))
+}
@@ -0,0 +1,422 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Process-level counters served at `/stats/runtime`, and aggregates over the
+//! result history served at `/stats`.
+//!
+//! The run pipeline records every run here; [`runtime_stats`] combines these
+//! counters with the failure streak and panic count kept elsewhere into a single
+//! [`RuntimeStats`] snapshot. [`compute_stats`] summarises the retained results
+//! (see [`get_history`]), optionally only those within a time window (see
+//! [`within_window`]). [`render_run_durations`] exports how long the runs took as
+//! a [`Histogram`].
+//!
+//! The run counters are saved to `STATE_FILE` every minute and at shutdown (see
+//! [`run_state_saver`]) and restored at startup (see [`restore_state`]), so they
+//! carry on across restarts.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use actix_web::{get, web, HttpResponse, Responder};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tokio::time;
+use tracing::{error, info, warn};
+use crate::histogram::{Bounds, Histogram};
+use crate::snapshot::{self, Persistence};
+use crate::{config, consecutive_failures, get_history, schedule, scheduler_panic_count, SpeedTestResult};
+
+static STARTED: Lazy<Instant> = Lazy::new(Instant::now);
+static RUNS: AtomicU64 = AtomicU64::new(0);
+static SUCCESSES: AtomicU64 = AtomicU64::new(0);
+static BYTES_TRANSFERRED: AtomicU64 = AtomicU64::new(0);
+/// Milliseconds since the Unix epoch, `0` meaning never.
+static LAST_SUCCESS_MS: AtomicI64 = AtomicI64::new(0);
+/// Milliseconds since the Unix epoch, `0` meaning never.
+static LAST_FAILURE_MS: AtomicI64 = AtomicI64::new(0);
+/// Seconds taken by the cached runs, bucketed by `RUN_DURATION_BUCKETS`.
+static RUN_DURATIONS: Lazy<Mutex<Histogram>> = Lazy::new(|| Mutex::new(Histogram::new(run_duration_bounds())));
+
+/// Reads `RUN_DURATION_BUCKETS`, falling back to the default buckets with a
+/// warning if it does not parse.
+fn run_duration_bounds() -> Bounds {
+    if let Some(Err(e)) = config::RUN_DURATION_BUCKETS.raw().map(|s| s.parse::<Bounds>()) {
+        warn!("{}: {}; using the default buckets", config::RUN_DURATION_BUCKETS.name, e);
+    }
+    config::RUN_DURATION_BUCKETS.get()
+}
+
+/// Starts the uptime clock; called once at startup.
+pub fn mark_started() {
+    Lazy::force(&STARTED);
+}
+
+/// Returns the time since [`mark_started`], or since the first call if never marked.
+pub fn uptime() -> Duration {
+    STARTED.elapsed()
+}
+
+/// Records a finished run, which succeeded if it cached a result.
+pub(crate) fn record_run(succeeded: bool) {
+    RUNS.fetch_add(1, Ordering::Relaxed);
+    let now = chrono::Utc::now().timestamp_millis();
+    if succeeded {
+        SUCCESSES.fetch_add(1, Ordering::Relaxed);
+        LAST_SUCCESS_MS.store(now, Ordering::Relaxed);
+    } else {
+        LAST_FAILURE_MS.store(now, Ordering::Relaxed);
+    }
+}
+
+/// Returns the time since the last successful run, or since startup if no run
+/// has succeeded yet.
+pub fn since_last_success() -> Duration {
+    match LAST_SUCCESS_MS.load(Ordering::Relaxed) {
+        0 => uptime(),
+        ms => Duration::from_millis(chrono::Utc::now().timestamp_millis().saturating_sub(ms).max(0) as u64),
+    }
+}
+
+/// Backdates the last successful run to `at`, or forgets it for `None`.
+///
+/// Lets tests exercise [`since_last_success`] without waiting. Available in unit
+/// tests and, for downstream crates, behind the `testutil` feature.
+#[cfg(any(test, feature = "testutil"))]
+pub fn set_last_success_at(at: Option<chrono::DateTime<chrono::Utc>>) {
+    LAST_SUCCESS_MS.store(at.map_or(0, |at| at.timestamp_millis()), Ordering::Relaxed);
+}
+
+/// Adds the bytes received and sent by a cached run.
+pub(crate) fn record_bytes(bytes: u64) {
+    BYTES_TRANSFERRED.fetch_add(bytes, Ordering::Relaxed);
+}
+
+/// Adds the duration of a cached run to the run duration histogram.
+pub(crate) fn record_run_duration(duration: Duration) {
+    RUN_DURATIONS.lock().unwrap_or_else(|e| e.into_inner()).observe(duration.as_secs_f64());
+}
+
+/// Renders the run duration histogram as `speedtest_run_duration_seconds` for
+/// `/metrics` (see [`Histogram::render`]).
+pub fn render_run_durations() -> String {
+    RUN_DURATIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .render("speedtest_run_duration_seconds", "Time taken by the successful attempt of each cached speedtest run.")
+}
+
+fn timestamp(ms: &AtomicI64) -> Option<String> {
+    match ms.load(Ordering::Relaxed) {
+        0 => None,
+        ms => chrono::DateTime::from_timestamp_millis(ms).map(|at| at.to_rfc3339()),
+    }
+}
+
+/// Snapshot of the process-level counters.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeStats {
+    /// Seconds since startup.
+    pub uptime_seconds: u64,
+
+    /// Runs finished since startup.
+    pub total_runs: u64,
+
+    /// Runs which cached a result.
+    pub successes: u64,
+
+    /// Runs which did not cache a result, including discarded ones.
+    pub failures: u64,
+
+    /// Consecutive identical failures since the last success.
+    pub consecutive_failures: u64,
+
+    /// Runs which panicked and were recovered from.
+    pub panics: u64,
+
+    /// Bytes received and sent by the cached runs.
+    pub bytes_transferred: u64,
+
+    /// When the last successful run finished, if any.
+    pub last_success: Option<String>,
+
+    /// When the last failed run finished, if any.
+    pub last_failure: Option<String>,
+}
+
+/// Collects the current counters.
+pub fn runtime_stats() -> RuntimeStats {
+    let total_runs = RUNS.load(Ordering::Relaxed);
+    let successes = SUCCESSES.load(Ordering::Relaxed);
+    RuntimeStats {
+        uptime_seconds: uptime().as_secs(),
+        total_runs,
+        successes,
+        failures: total_runs.saturating_sub(successes),
+        consecutive_failures: consecutive_failures(),
+        panics: scheduler_panic_count(),
+        bytes_transferred: BYTES_TRANSFERRED.load(Ordering::Relaxed),
+        last_success: timestamp(&LAST_SUCCESS_MS),
+        last_failure: timestamp(&LAST_FAILURE_MS),
+    }
+}
+
+/// HTTP GET endpoint `/stats/runtime` returns the [`RuntimeStats`] as JSON.
+#[get("/stats/runtime")]
+pub async fn runtime_stats_endpoint() -> impl Responder {
+    HttpResponse::Ok().json(runtime_stats())
+}
+
+/// The run counters as saved to `STATE_FILE`.
+///
+/// Timestamps are milliseconds since the Unix epoch, `0` meaning never. Missing
+/// fields load as zero.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SavedCounters {
+    /// Runs finished.
+    pub total_runs: u64,
+
+    /// Runs which cached a result.
+    pub successes: u64,
+
+    /// Bytes received and sent by the cached runs.
+    pub bytes_transferred: u64,
+
+    /// When the last successful run finished.
+    pub last_success_ms: i64,
+
+    /// When the last failed run finished.
+    pub last_failure_ms: i64,
+}
+
+/// Returns the current run counters.
+pub fn saved_counters() -> SavedCounters {
+    SavedCounters {
+        total_runs: RUNS.load(Ordering::Relaxed),
+        successes: SUCCESSES.load(Ordering::Relaxed),
+        bytes_transferred: BYTES_TRANSFERRED.load(Ordering::Relaxed),
+        last_success_ms: LAST_SUCCESS_MS.load(Ordering::Relaxed),
+        last_failure_ms: LAST_FAILURE_MS.load(Ordering::Relaxed),
+    }
+}
+
+/// Adds `saved` to the run counters.
+///
+/// The counts are added to any runs already recorded since startup, and each
+/// timestamp is kept if it is later than the current one.
+pub fn restore_counters(saved: &SavedCounters) {
+    RUNS.fetch_add(saved.total_runs, Ordering::Relaxed);
+    SUCCESSES.fetch_add(saved.successes, Ordering::Relaxed);
+    BYTES_TRANSFERRED.fetch_add(saved.bytes_transferred, Ordering::Relaxed);
+    LAST_SUCCESS_MS.fetch_max(saved.last_success_ms, Ordering::Relaxed);
+    LAST_FAILURE_MS.fetch_max(saved.last_failure_ms, Ordering::Relaxed);
+}
+
+/// Returns the configured state file, or `None` if `STATE_FILE` is empty.
+pub fn state_path() -> Option<PathBuf> {
+    Some(config::STATE_FILE.get::<String>()).filter(|s| !s.trim().is_empty()).map(PathBuf::from)
+}
+
+/// Loads the counters saved at `path`, if present and valid.
+///
+/// A missing file is silently ignored; an unreadable or invalid one is logged.
+pub fn load_state(path: &Path) -> Option<SavedCounters> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            error!("Failed to read state {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    serde_json::from_str(&contents)
+        .map_err(|e| warn!("Ignoring invalid state in {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Restores the counters saved at `STATE_FILE`, if any, returning whether they were.
+pub fn restore_state() -> bool {
+    let Some(saved) = state_path().and_then(|path| load_state(&path)) else {
+        return false;
+    };
+    restore_counters(&saved);
+    info!("Restored runtime counters: {} runs, {} successes", saved.total_runs, saved.successes);
+    true
+}
+
+static STATE_PERSISTENCE: Persistence = Persistence::new();
+
+/// Writes the current counters to `path`, unless an earlier permission error
+/// disabled saving. Other failures are logged.
+pub fn save_state(path: &Path) {
+    let write = || {
+        let json = serde_json::to_vec(&saved_counters()).map_err(io::Error::other)?;
+        snapshot::write_atomic(path, &json)
+    };
+    if let Err(e) = STATE_PERSISTENCE.write(path, write) {
+        error!("Failed to write state {}: {}", path.display(), e);
+    }
+}
+
+/// Background task saving the counters to `path` every minute.
+pub async fn run_state_saver(path: PathBuf) {
+    let mut ticker = time::interval(Duration::from_secs(60));
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let path = path.clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || save_state(&path)).await {
+            error!("Saving runtime counters panicked: {}", e);
+        }
+    }
+}
+
+/// Minimum, maximum, mean and median of one measurement across the history.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    /// Smallest value.
+    pub min: f64,
+
+    /// Largest value.
+    pub max: f64,
+
+    /// Arithmetic mean.
+    pub mean: f64,
+
+    /// Middle value, or the mean of the two middle values for an even count.
+    pub median: f64,
+}
+
+impl Summary {
+    /// Summarises `values`, or returns `None` if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use speedtest_statuspage::stats::Summary;
+    ///
+    /// let summary = Summary::of(&[4.0, 1.0, 3.0, 2.0]).unwrap();
+    /// assert_eq!((summary.min, summary.max, summary.mean, summary.median), (1.0, 4.0, 2.5, 2.5));
+    /// assert_eq!(Summary::of(&[]), None);
+    /// ```
+    pub fn of(values: &[f64]) -> Option<Summary> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let mid = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        };
+        Some(Summary {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            median,
+        })
+    }
+}
+
+/// Aggregates over a set of results, as served by `/stats`.
+///
+/// The summaries are `null` when there are no results.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SpeedStats {
+    /// Number of results summarised.
+    pub count: usize,
+
+    /// Download speed in Mbps.
+    pub download_mbps: Option<Summary>,
+
+    /// Upload speed in Mbps.
+    pub upload_mbps: Option<Summary>,
+
+    /// Ping in milliseconds.
+    pub ping_ms: Option<Summary>,
+}
+
+/// Summarises the download, upload and ping of `history`.
+pub fn compute_stats(history: &[SpeedTestResult]) -> SpeedStats {
+    let summarise = |field: fn(&SpeedTestResult) -> f64| {
+        Summary::of(&history.iter().map(field).collect::<Vec<_>>())
+    };
+    SpeedStats {
+        count: history.len(),
+        download_mbps: summarise(|r| r.download_mbps),
+        upload_mbps: summarise(|r| r.upload_mbps),
+        ping_ms: summarise(|r| r.ping_ms),
+    }
+}
+
+/// Keeps the results of `history` measured at or after `cutoff`, judged by their
+/// RFC 3339 `timestamp`; results whose timestamp does not parse are dropped.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::{stats::within_window, SpeedTestResult};
+///
+/// let at = |timestamp: &str| SpeedTestResult { timestamp: timestamp.to_string(), ..Default::default() };
+/// let history = [at("2025-08-07T11:00:00Z"), at("2025-08-07T12:30:00Z")];
+/// let cutoff = "2025-08-07T12:00:00Z".parse().unwrap();
+/// assert_eq!(within_window(&history, cutoff), vec![at("2025-08-07T12:30:00Z")]);
+/// ```
+pub fn within_window(history: &[SpeedTestResult], cutoff: DateTime<Utc>) -> Vec<SpeedTestResult> {
+    history
+        .iter()
+        .filter(|r| DateTime::parse_from_rfc3339(&r.timestamp).is_ok_and(|at| at >= cutoff))
+        .cloned()
+        .collect()
+}
+
+/// Query parameters accepted by [`speed_stats_endpoint`].
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct StatsQuery {
+    /// Only summarise results from this long before now, e.g. `90s`, `30m` or `1h`
+    /// (see [`schedule::parse_interval`]).
+    pub window: Option<String>,
+}
+
+/// HTTP GET endpoint `/stats` returns the [`SpeedStats`] of the retained history as
+/// JSON, with HTTP 200 even while the history is empty.
+///
+/// `?window=1h` restricts the aggregates to results measured within the last hour
+/// (see [`within_window`]); an invalid window is rejected with HTTP 400.
+#[get("/stats")]
+pub async fn speed_stats_endpoint(query: web::Query<StatsQuery>) -> impl Responder {
+    let history = get_history();
+    match query.window.as_deref().map(schedule::parse_interval) {
+        None => HttpResponse::Ok().json(compute_stats(&history)),
+        Some(Ok(window)) => {
+            // A window reaching back before chrono's range covers everything.
+            let cutoff = chrono::Duration::from_std(window).ok().and_then(|w| Utc::now().checked_sub_signed(w));
+            let history = match cutoff {
+                Some(cutoff) => within_window(&history, cutoff),
+                None => history,
+            };
+            HttpResponse::Ok().json(compute_stats(&history))
+        }
+        Some(Err(e)) => HttpResponse::BadRequest().body(e),
+    }
+}
@@ -0,0 +1,95 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! User-defined tags attached to every result.
+//!
+//! `RESULT_TAGS` holds comma-separated `key=value` pairs such as
+//! `location=home,tier=fibre`. Keys are sanitized into valid Prometheus label names
+//! so that the same tags can be used as metric labels.
+
+use std::collections::BTreeMap;
+use tracing::warn;
+use crate::config;
+
+/// Turns `name` into a valid Prometheus label name.
+///
+/// Label names must match `[a-zA-Z_][a-zA-Z0-9_]*` and must not start with `__`,
+/// which is reserved. Invalid characters are replaced with `_`, a leading digit is
+/// prefixed with `_`, and reserved names lose their extra leading underscores.
+/// Returns `None` for names with nothing left to keep.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::tags::sanitize_label_name;
+///
+/// assert_eq!(sanitize_label_name("site-id"), Some("site_id".to_string()));
+/// assert_eq!(sanitize_label_name("2nd.floor"), Some("_2nd_floor".to_string()));
+/// assert_eq!(sanitize_label_name("__name__"), Some("_name__".to_string()));
+/// assert_eq!(sanitize_label_name(" "), None);
+/// ```
+pub fn sanitize_label_name(name: &str) -> Option<String> {
+    let mut label: String = name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+        .collect();
+    if label.is_empty() || label.chars().all(|c| c == '_') {
+        return None;
+    }
+    if label.starts_with(|c: char| c.is_ascii_digit()) {
+        label.insert(0, '_');
+    }
+    while label.starts_with("__") {
+        label.remove(0);
+    }
+    Some(label)
+}
+
+/// Parses a `key=value,key=value` list into tags.
+///
+/// Keys are passed through [`sanitize_label_name`]. Entries without `=` or with an
+/// unusable key are logged and skipped. Later duplicates replace earlier ones.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::tags::parse_tags;
+///
+/// let tags = parse_tags("location=home, tier=fibre, oops");
+/// assert_eq!(tags.len(), 2);
+/// assert_eq!(tags["tier"], "fibre");
+/// ```
+pub fn parse_tags(list: &str) -> BTreeMap<String, String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parsed = entry
+                .split_once('=')
+                .and_then(|(key, value)| sanitize_label_name(key).map(|key| (key, value.trim().to_string())));
+            if parsed.is_none() {
+                warn!("Ignoring malformed RESULT_TAGS entry '{}'", entry);
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Reads the tags configured in `RESULT_TAGS`.
+pub fn result_tags() -> BTreeMap<String, String> {
+    config::RESULT_TAGS.raw().map(|list| parse_tags(&list)).unwrap_or_default()
+}
@@ -0,0 +1,59 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Endpoints letting test harnesses script the cache over HTTP.
+//!
+//! With `TEST_ENDPOINTS_ENABLED=true`, `POST /test/set` caches the posted
+//! [`SpeedTestResult`] and `POST /test/clear` empties the cache, so dashboards can
+//! be tested against every state without running real speedtests. Anyone who can
+//! reach the server can then overwrite its results: never enable this in production.
+
+use actix_web::{post, web, HttpResponse, Responder};
+use crate::{clear_last_result_for_test, store_last_result, Origin, SpeedTestResult};
+
+/// HTTP POST endpoint `/test/set` caches the posted result as if a run produced it,
+/// stamped with [`Origin::Ingested`].
+///
+/// Returns HTTP 204 No Content, or 500 if the cache cannot be written.
+#[post("/test/set")]
+pub async fn test_set(result: web::Json<SpeedTestResult>) -> impl Responder {
+    let result = SpeedTestResult { origin: Origin::Ingested, ..result.into_inner() };
+    match store_last_result(result) {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// HTTP POST endpoint `/test/clear` empties the cache.
+///
+/// Returns HTTP 204 No Content.
+#[post("/test/clear")]
+pub async fn test_clear() -> impl Responder {
+    clear_last_result_for_test();
+    HttpResponse::NoContent().finish()
+}
+
+/// Returns an app configuration registering the test endpoints when `enabled`.
+///
+/// Pass `config::TEST_ENDPOINTS_ENABLED.flag()`; when `false` the routes are not
+/// registered at all and respond 404.
+pub fn configure(enabled: bool) -> impl Fn(&mut web::ServiceConfig) + Clone {
+    move |cfg| {
+        if enabled {
+            cfg.service(test_set).service(test_clear);
+        }
+    }
+}
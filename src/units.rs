@@ -0,0 +1,38 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Rescaling speeds into the unit a dashboard wants.
+//!
+//! Results store speeds in bits per second, plus megabits per second for
+//! convenience. `/speed?unit=gbps` (or `bps`, `Kbps`, `Mbps`, `MBps`) adds the
+//! download and upload speeds converted with [`bps_to`], next to the unchanged
+//! `*_mbps` fields.
+
+pub use crate::models::SpeedUnit as Unit;
+
+/// Converts `value` in bits per second into `unit`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::units::{bps_to, Unit};
+///
+/// assert_eq!(bps_to(94_500_000.0, Unit::Mbps), 94.5);
+/// assert_eq!(bps_to(80_000_000.0, Unit::MBps), 10.0);
+/// ```
+pub fn bps_to(value: f64, unit: Unit) -> f64 {
+    value / unit.to_bps(1.0)
+}
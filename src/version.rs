@@ -0,0 +1,165 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Startup check of the installed speedtest binary version.
+//!
+//! Old `speedtest-cli` releases emit JSON that is not handled correctly, so the
+//! backend's `--version` output is compared against a known minimum at startup.
+//! A version below the minimum is logged as a warning, or refused when
+//! `STRICT_VERSION=true`.
+
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::warn;
+use crate::{config, Backend};
+
+/// A `major.minor.patch` version triple.
+pub type Version = (u32, u32, u32);
+
+/// Parses the first `major.minor[.patch]` version found in `s`.
+///
+/// Tokens may carry a leading `v`. A missing patch component is treated as `0`.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::version::parse_version;
+///
+/// assert_eq!(parse_version("speedtest-cli 2.1.3\nPython 3.11.2"), Some((2, 1, 3)));
+/// assert_eq!(parse_version("v3.2"), Some((3, 2, 0)));
+/// assert_eq!(parse_version("unknown"), None);
+/// ```
+pub fn parse_version(s: &str) -> Option<Version> {
+    s.split_whitespace().find_map(|token| {
+        let token = token.strip_prefix('v').unwrap_or(token);
+        let mut parts = token.split('.').map(|part| part.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = match parts.next() {
+            Some(patch) => patch?,
+            None => 0,
+        };
+        Some((major, minor, patch))
+    })
+}
+
+/// Returns `true` if `found` is at least `minimum`.
+pub fn meets_minimum(found: Version, minimum: Version) -> bool {
+    found >= minimum
+}
+
+/// Returns the oldest supported version of `backend`'s binary.
+pub fn minimum_version(backend: Backend) -> Version {
+    match backend {
+        Backend::Legacy => (2, 1, 0),
+        Backend::Fast => (3, 0, 0),
+        Backend::Ookla => (1, 0, 0),
+    }
+}
+
+/// Outcome of comparing an installed binary against its minimum version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionStatus {
+    /// The installed version is at least the minimum.
+    Supported(Version),
+
+    /// The installed version is older than the minimum.
+    TooOld {
+        /// Version reported by the binary.
+        found: Version,
+        /// Oldest supported version.
+        minimum: Version,
+    },
+
+    /// The version could not be determined.
+    Unknown,
+}
+
+/// Classifies the `--version` output of `backend`'s binary.
+///
+/// # Examples
+///
+/// ```
+/// use speedtest_statuspage::Backend;
+/// use speedtest_statuspage::version::{check_version_output, VersionStatus};
+///
+/// assert_eq!(
+///     check_version_output(Backend::Legacy, "speedtest-cli 2.0.2"),
+///     VersionStatus::TooOld { found: (2, 0, 2), minimum: (2, 1, 0) },
+/// );
+/// ```
+pub fn check_version_output(backend: Backend, output: &str) -> VersionStatus {
+    let minimum = minimum_version(backend);
+    match parse_version(output) {
+        Some(found) if meets_minimum(found, minimum) => VersionStatus::Supported(found),
+        Some(found) => VersionStatus::TooOld { found, minimum },
+        None => VersionStatus::Unknown,
+    }
+}
+
+fn format_version((major, minor, patch): Version) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+/// Checks the installed version of `backend`'s binary.
+///
+/// An outdated or undetectable version is logged as a warning.
+///
+/// # Errors
+///
+/// With `STRICT_VERSION=true`, returns an error describing the required upgrade if
+/// the installed version is below the minimum.
+pub async fn check_backend_version(backend: Backend) -> Result<(), String> {
+    let binary = backend.binary();
+    let output = Command::new(binary)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .await;
+    let text = match output {
+        Ok(output) => format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        Err(e) => {
+            warn!("Could not determine {} version: {}", binary, e);
+            return Ok(());
+        }
+    };
+
+    match check_version_output(backend, &text) {
+        VersionStatus::Supported(_) => Ok(()),
+        VersionStatus::Unknown => {
+            warn!("Could not determine {} version from its --version output", binary);
+            Ok(())
+        }
+        VersionStatus::TooOld { found, minimum } => {
+            let message = format!(
+                "{} {} is older than the minimum supported {}; please upgrade it",
+                binary,
+                format_version(found),
+                format_version(minimum)
+            );
+            if config::STRICT_VERSION.flag() {
+                Err(message)
+            } else {
+                warn!("{}", message);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Watchdog killing speedtest processes which stop producing output.
+//!
+//! With `CLI_STALL_SECONDS` set, the CLI's stdout is read incrementally and the
+//! process is killed if no output arrives for that long. Note that
+//! `speedtest-cli --json` and `fast --json` only write their result once the test
+//! has finished, so the stall period must be longer than a complete run.
+
+use std::process::{Output, Stdio};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::time;
+use tracing::error;
+use crate::SpeedtestError;
+
+/// Reads `reader` to the end, failing if any single read waits longer than `stall`.
+///
+/// # Errors
+///
+/// Returns [`SpeedtestError::Stalled`] if no data or end-of-file arrives within
+/// `stall`, or [`SpeedtestError::CommandFailed`] if reading fails.
+pub async fn read_until_stall<R: AsyncRead + Unpin>(mut reader: R, stall: Duration) -> Result<Vec<u8>, SpeedtestError> {
+    let mut output = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        match time::timeout(stall, reader.read(&mut chunk)).await {
+            Err(_) => return Err(SpeedtestError::Stalled(stall)),
+            Ok(Err(e)) => return Err(SpeedtestError::CommandFailed(format!("Failed to read output: {}", e))),
+            Ok(Ok(0)) => return Ok(output),
+            Ok(Ok(n)) => output.extend_from_slice(&chunk[..n]),
+        }
+    }
+}
+
+/// Runs `command` to completion, capturing stdout and stderr.
+///
+/// With a `stall` period the process is killed once its stdout has been silent for
+/// that long. `binary` names the program in error messages.
+///
+/// # Errors
+///
+/// Returns [`SpeedtestError::BinaryNotFound`] if `binary` is not installed,
+/// [`SpeedtestError::CommandFailed`] if the process cannot otherwise be run, or
+/// [`SpeedtestError::Stalled`] if it was killed by the watchdog.
+pub async fn run_watched(command: &mut Command, binary: &str, stall: Option<Duration>) -> Result<Output, SpeedtestError> {
+    let spawn_error = |e: std::io::Error| match e.kind() {
+        std::io::ErrorKind::NotFound => SpeedtestError::BinaryNotFound(binary.to_string()),
+        _ => SpeedtestError::CommandFailed(format!("Failed to run {}: {}", binary, e)),
+    };
+    command.stdout(Stdio::piped()).stderr(Stdio::piped()).kill_on_drop(true);
+
+    let Some(stall) = stall else {
+        return command.output().await.map_err(spawn_error);
+    };
+
+    let mut child = command.spawn().map_err(spawn_error)?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        let _ = stderr.read_to_end(&mut buf).await;
+        buf
+    });
+
+    let stdout = match read_until_stall(stdout, stall).await {
+        Ok(stdout) => stdout,
+        Err(e) => {
+            if let Err(kill_error) = child.kill().await {
+                error!("Failed to kill stalled {}: {}", binary, kill_error);
+            }
+            stderr_task.abort();
+            return Err(e);
+        }
+    };
+    let status = child.wait().await.map_err(spawn_error)?;
+    let stderr = stderr_task.await.unwrap_or_default();
+    Ok(Output { status, stdout, stderr })
+}
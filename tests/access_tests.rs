@@ -0,0 +1,52 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `access` module: CIDR matching and the allow list.
+
+use speedtest_statuspage::*;
+
+/// CIDR matching covers IPv4, IPv6, IPv4-mapped addresses and single hosts.
+#[test]
+fn cidr_contains_matches_v4_and_v6() {
+    use access::Cidr;
+    use std::net::IpAddr;
+
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+    let lan: Cidr = "10.0.0.0/8".parse().unwrap();
+    assert!(lan.contains(ip("10.200.3.4")));
+    assert!(lan.contains(ip("::ffff:10.1.1.1")));
+    assert!(!lan.contains(ip("11.0.0.1")));
+    assert!(!lan.contains(ip("fd00::1")));
+
+    let ula: Cidr = "fd00::/8".parse().unwrap();
+    assert!(ula.contains(ip("fdab:1234::1")));
+    assert!(!ula.contains(ip("2001:db8::1")));
+
+    let host: Cidr = "192.0.2.5".parse().unwrap();
+    assert!(host.contains(ip("192.0.2.5")));
+    assert!(!host.contains(ip("192.0.2.6")));
+    assert!("0.0.0.0/0".parse::<Cidr>().unwrap().contains(ip("198.51.100.1")));
+}
+
+/// Malformed entries are rejected rather than silently widening access.
+#[test]
+fn cidr_parse_rejects_invalid_entries() {
+    assert!("10.0.0.0/33".parse::<access::Cidr>().is_err());
+    assert!("not-an-ip/8".parse::<access::Cidr>().is_err());
+    assert!(access::AllowList::parse("10.0.0.0/8,bogus").is_err());
+    assert!(access::AllowList::default().allows(None));
+    assert!(!access::AllowList::parse("10.0.0.0/8").unwrap().allows(None));
+}
@@ -44,6 +44,7 @@ fn dummy_result() -> SpeedTestResult {
         server: Default::default(),
         share: None,
         timestamp: "2025-08-07T12:34:56Z".to_string(),
+        ..Default::default()
     }
 }
 
@@ -98,6 +99,62 @@ async fn speedtest_returns_cached_result() {
     assert_eq!(result.download_mbps, 1.0);
     assert_eq!(result.bytes_received, 100);
 
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["score"], 34);
+    assert_eq!(json["age_seconds"], 0);
+
+    clear_last_result_for_test();
+}
+
+/// `?unit=` adds the speeds in that unit, leaves the Mbps fields alone, and
+/// rejects unknown units; without it the extra fields are absent.
+#[actix_web::test]
+#[serial]
+async fn speedtest_rescales_to_requested_unit() {
+    set_last_result_for_test(SpeedTestResult { download_bps: 2_500_000_000.0, ..dummy_result() });
+    let app = test::init_service(App::new().service(speedtest)).await;
+
+    let req = test::TestRequest::get().uri("/speed?unit=gbps").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["unit"], "Gbps");
+    assert_eq!(body["download"], 2.5);
+    assert_eq!(body["upload"], 0.0005);
+    assert_eq!(body["download_mbps"], 1.0);
+
+    let req = test::TestRequest::get().uri("/speed?unit=MBps").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["download"], 312.5);
+
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body.get("unit").is_none() && body.get("download").is_none());
+
+    let req = test::TestRequest::get().uri("/speed?unit=furlongs").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    clear_last_result_for_test();
+}
+
+/// `age_seconds` reflects how long ago the result was cached, as of the request.
+#[actix_web::test]
+#[serial]
+async fn speedtest_reports_age_at_request_time() {
+    use std::time::{Duration, Instant};
+
+    set_last_result_at(dummy_result(), Instant::now() - Duration::from_secs(90));
+    let app = test::init_service(App::new().service(speedtest)).await;
+
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    let json: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(json["age_seconds"], 90);
+
+    // Backdating the same result stands in for time passing between requests.
+    set_last_result_at(dummy_result(), Instant::now() - Duration::from_secs(150));
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    let json: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(json["age_seconds"], 150);
+
     clear_last_result_for_test();
 }
 
@@ -117,6 +174,7 @@ async fn speedtest_returns_cached_result() {
 /// assert!(get_last_result().is_none());
 /// ```
 #[tokio::test]
+#[serial]
 async fn test_set_get_clear_last_result_for_test() {
     clear_last_result_for_test();
     assert!(get_last_result().is_none());
@@ -128,4 +186,765 @@ async fn test_set_get_clear_last_result_for_test() {
 
     clear_last_result_for_test();
     assert!(get_last_result().is_none());
-}
\ No newline at end of file
+}
+
+/// Tests that a write into a poisoned cache is re-attempted after recovery
+/// and that the value is actually stored.
+#[tokio::test]
+#[serial]
+async fn store_last_result_recovers_from_poisoned_lock() {
+    clear_last_result_for_test();
+
+    let _ = std::thread::spawn(|| {
+        let _guard = LAST_RESULT.write().unwrap();
+        panic!("poisoning the cache lock");
+    })
+    .join();
+    assert!(LAST_RESULT.is_poisoned());
+
+    assert_eq!(store_last_result(dummy_result()), Ok(()));
+    assert!(!LAST_RESULT.is_poisoned());
+    assert_eq!(get_last_result().unwrap().bytes_received, 100);
+
+    clear_last_result_for_test();
+}
+
+/// Tests that `/speed` and the cache readers keep serving after a writer panicked
+/// while holding the cache lock.
+#[actix_web::test]
+#[serial]
+async fn readers_survive_poisoned_lock() {
+    set_last_result_for_test(dummy_result());
+    let _ = std::thread::spawn(|| {
+        let _guard = LAST_RESULT.write().unwrap();
+        panic!("poisoning the cache lock");
+    })
+    .join();
+    assert!(LAST_RESULT.is_poisoned());
+
+    let app = test::init_service(App::new().service(speedtest)).await;
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/speed").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(get_cached_speedtest_result().await.unwrap().bytes_received, 100);
+    assert!(get_last_result_age().is_some());
+
+    clear_last_result_for_test();
+    assert!(get_last_result().is_none());
+    assert_eq!(store_last_result(dummy_result()), Ok(()));
+    clear_last_result_for_test();
+}
+
+/// Tests that a single write attempt reports a poisoned lock without storing.
+#[tokio::test]
+#[serial]
+async fn try_store_last_result_reports_poisoned_lock() {
+    clear_last_result_for_test();
+
+    let _ = std::thread::spawn(|| {
+        let _guard = LAST_RESULT.write().unwrap();
+        panic!("poisoning the cache lock");
+    })
+    .join();
+
+    assert_eq!(try_store_last_result(dummy_result()), Err(CacheError::Poisoned));
+    assert!(get_last_result().is_none());
+
+    assert_eq!(try_store_last_result(dummy_result()), Ok(()));
+    assert!(get_last_result().is_some());
+
+    clear_last_result_for_test();
+}
+
+/// Tests that a backdated result reports its age through `get_last_result_age`.
+#[tokio::test]
+#[serial]
+async fn backdated_result_reports_age() {
+    use std::time::{Duration, Instant};
+
+    clear_last_result_for_test();
+    assert!(get_last_result_age().is_none());
+
+    set_last_result_at(dummy_result(), Instant::now() - Duration::from_secs(120));
+    let age = get_last_result_age().unwrap();
+    assert!(age >= Duration::from_secs(120));
+    assert!(age < Duration::from_secs(180));
+
+    clear_last_result_for_test();
+}
+
+/// `/speed/vs-isp-avg` serves the comparison for the cached result, or 503 without one.
+#[actix_web::test]
+#[serial]
+async fn isp_average_endpoint_compares_cached_result() {
+    let app = test::init_service(App::new().service(isp::isp_average_endpoint)).await;
+
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/speed/vs-isp-avg").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    let mut result = dummy_result();
+    result.client.ispdlavg = "2000".to_string();
+    set_last_result_for_test(result);
+    let req = test::TestRequest::get().uri("/speed/vs-isp-avg").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["isp_download_avg_mbps"], 2.0);
+    assert_eq!(body["download_percent_difference"], -50.0);
+    assert!(body["isp_upload_avg_mbps"].is_null());
+
+    clear_last_result_for_test();
+}
+
+/// `/speed/percentile-vs-isp` reports insufficient data when the ISP average is
+/// missing or garbage.
+#[actix_web::test]
+#[serial]
+async fn isp_percentile_endpoint_reports_insufficient_data() {
+    let app = test::init_service(App::new().service(isp::isp_percentile_endpoint)).await;
+
+    for ispdlavg in ["", "0", "n/a"] {
+        let mut result = dummy_result();
+        result.client.ispdlavg = ispdlavg.to_string();
+        set_last_result_for_test(result);
+        let req = test::TestRequest::get().uri("/speed/percentile-vs-isp").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["sufficient_data"], false, "ispdlavg={ispdlavg:?}");
+        assert!(body["download_percentile"].is_null());
+        assert!(body["summary"].as_str().unwrap().starts_with("Insufficient data"));
+    }
+    clear_last_result_for_test();
+}
+
+/// Builds a request for `/speed` from the client address `peer`.
+fn request_from(peer: &str) -> test::TestRequest {
+    test::TestRequest::get().uri("/speed").peer_addr(peer.parse().unwrap())
+}
+
+/// Clients inside `ALLOW_CIDRS` are served and clients outside it get 403.
+#[actix_web::test]
+#[serial]
+async fn allow_list_admits_listed_networks_only() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::access::{enforce_allow_list, AllowList};
+
+    set_last_result_for_test(dummy_result());
+    let allow_list = AllowList::parse("192.168.1.0/24, fd00::/8").unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::new(allow_list))
+            .wrap(from_fn(enforce_allow_list))
+            .service(speedtest),
+    )
+    .await;
+
+    let resp = test::call_service(&app, request_from("192.168.1.20:50000").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let resp = test::call_service(&app, request_from("[fd12::1]:50000").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let resp = test::call_service(&app, request_from("203.0.113.7:50000").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::FORBIDDEN);
+
+    clear_last_result_for_test();
+}
+
+/// An empty allow list keeps the previous allow-all behaviour.
+#[actix_web::test]
+#[serial]
+async fn empty_allow_list_allows_everyone() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::access::{enforce_allow_list, AllowList};
+
+    set_last_result_for_test(dummy_result());
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::new(AllowList::parse("").unwrap()))
+            .wrap(from_fn(enforce_allow_list))
+            .service(speedtest),
+    )
+    .await;
+
+    let resp = test::call_service(&app, request_from("203.0.113.7:50000").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    clear_last_result_for_test();
+}
+
+/// With `API_TOKEN` set, only requests presenting it as a bearer token are served.
+#[actix_web::test]
+#[serial]
+async fn api_token_required_when_set() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::auth::{require_api_token, ApiToken};
+
+    set_last_result_for_test(dummy_result());
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::new(ApiToken::new("s3cret")))
+            .wrap(from_fn(require_api_token))
+            .service(speedtest),
+    )
+    .await;
+
+    for (authorization, expected) in [
+        (None, http::StatusCode::UNAUTHORIZED),
+        (Some("Bearer wrong"), http::StatusCode::UNAUTHORIZED),
+        (Some("s3cret"), http::StatusCode::UNAUTHORIZED),
+        (Some("Bearer s3cret"), http::StatusCode::OK),
+    ] {
+        let mut req = test::TestRequest::get().uri("/speed");
+        if let Some(value) = authorization {
+            req = req.insert_header((http::header::AUTHORIZATION, value));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), expected, "Authorization: {authorization:?}");
+        if expected == http::StatusCode::UNAUTHORIZED {
+            assert_eq!(resp.headers().get(http::header::WWW_AUTHENTICATE).unwrap(), "Bearer");
+        }
+    }
+
+    clear_last_result_for_test();
+}
+
+/// An empty `API_TOKEN` keeps the endpoints open, with or without a header.
+#[actix_web::test]
+#[serial]
+async fn empty_api_token_allows_everyone() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::auth::{require_api_token, ApiToken};
+
+    set_last_result_for_test(dummy_result());
+    let app = test::init_service(
+        App::new()
+            .app_data(actix_web::web::Data::new(ApiToken::new("")))
+            .wrap(from_fn(require_api_token))
+            .service(speedtest),
+    )
+    .await;
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/speed").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let req = test::TestRequest::get().uri("/speed").insert_header((http::header::AUTHORIZATION, "Bearer anything"));
+    let resp = test::call_service(&app, req.to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    clear_last_result_for_test();
+}
+
+/// `/health` answers 200 whether or not a result is cached, reporting which.
+#[actix_web::test]
+#[serial]
+async fn health_returns_ok_with_and_without_result() {
+    let app = test::init_service(App::new().service(health)).await;
+
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["has_result"], false);
+    assert!(body["uptime_secs"].is_u64());
+
+    set_last_result_for_test(dummy_result());
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["has_result"], true);
+
+    clear_last_result_for_test();
+}
+
+/// `/health` answers 503 listing the breached checks only when a registered
+/// threshold is breached, across each threshold passing and failing.
+#[actix_web::test]
+#[serial]
+async fn health_thresholds_pass_fail_matrix() {
+    let cases = [
+        (HealthThresholds::default(), vec![]),
+        (HealthThresholds { min_download_mbps: Some(0.5), ..Default::default() }, vec![]),
+        (HealthThresholds { min_download_mbps: Some(2.0), ..Default::default() }, vec!["download"]),
+        (HealthThresholds { max_ping_ms: Some(20.0), ..Default::default() }, vec![]),
+        (HealthThresholds { max_ping_ms: Some(10.0), ..Default::default() }, vec!["ping"]),
+        (HealthThresholds { min_download_mbps: Some(0.5), max_ping_ms: Some(30.0), ..Default::default() }, vec![]),
+        (
+            HealthThresholds { min_download_mbps: Some(2.0), max_ping_ms: Some(10.0), ..Default::default() },
+            vec!["download", "ping"],
+        ),
+    ];
+
+    set_last_result_for_test(dummy_result());
+    for (thresholds, expected) in cases {
+        let app = test::init_service(
+            App::new().app_data(actix_web::web::Data::new(thresholds.clone())).service(health),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/health").to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status();
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let failed: Vec<&str> = body["failed_checks"]
+            .as_array()
+            .map(|checks| checks.iter().map(|check| check.as_str().unwrap()).collect())
+            .unwrap_or_default();
+        assert_eq!(failed.len(), expected.len(), "{thresholds:?}: {body}");
+        for (check, name) in failed.iter().zip(&expected) {
+            assert!(check.starts_with(name), "{thresholds:?}: {check}");
+        }
+        if expected.is_empty() {
+            assert_eq!((status, body["status"].as_str()), (http::StatusCode::OK, Some("ok")));
+        } else {
+            assert_eq!((status, body["status"].as_str()), (http::StatusCode::SERVICE_UNAVAILABLE, Some("unhealthy")));
+        }
+    }
+
+    clear_last_result_for_test();
+}
+
+/// Thresholds never fail `/health` before the first result, nor without being
+/// registered.
+#[actix_web::test]
+#[serial]
+async fn health_thresholds_need_result_and_registration() {
+    let strict = HealthThresholds { min_download_mbps: Some(1000.0), ..Default::default() };
+    let app = test::init_service(App::new().app_data(actix_web::web::Data::new(strict)).service(health)).await;
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/health").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::OK);
+
+    set_last_result_for_test(dummy_result());
+    let req = test::TestRequest::get().uri("/health").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    let app = test::init_service(App::new().service(health)).await;
+    let req = test::TestRequest::get().uri("/health").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::OK);
+
+    clear_last_result_for_test();
+}
+
+/// `/health` stays 200 while the last success is within `unhealthy_after` and
+/// turns 503 once it has aged past it, with or without a cached result.
+#[actix_web::test]
+#[serial]
+async fn health_fails_without_recent_success() {
+    let unhealthy_after = Some(std::time::Duration::from_secs(600));
+    let thresholds = HealthThresholds { unhealthy_after, ..Default::default() };
+    let app = test::init_service(App::new().app_data(actix_web::web::Data::new(thresholds)).service(health)).await;
+    let ago = |secs| Some(chrono::Utc::now() - chrono::Duration::seconds(secs));
+
+    set_last_result_for_test(dummy_result());
+    stats::set_last_success_at(ago(60));
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+
+    stats::set_last_success_at(ago(3600));
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "unhealthy");
+    assert!(body["failed_checks"][0].as_str().unwrap().starts_with("no successful run for 3600s"), "{body}");
+
+    clear_last_result_for_test();
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/health").to_request()).await;
+    assert_eq!(resp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    stats::set_last_success_at(None);
+}
+
+/// `/metrics` renders every gauge with HELP and TYPE lines, labelled with the node
+/// and tags, and only the metadata while no result is cached.
+#[actix_web::test]
+#[serial]
+async fn metrics_render_prometheus_text() {
+    let app = test::init_service(App::new().service(metrics)).await;
+
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(resp.headers().get(http::header::CONTENT_TYPE).unwrap(), PROMETHEUS_CONTENT_TYPE);
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains("# HELP speedtest_download_mbps "));
+    assert!(body.lines().all(|line| line.starts_with('#')), "no samples without a result:\n{body}");
+
+    let mut result = dummy_result();
+    result.node = "pi".to_string();
+    result.tags.insert("site".to_string(), "home \"lab\"".to_string());
+    set_last_result_for_test(result);
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    let labels = r#"{node="pi",site="home \"lab\""}"#;
+    for sample in [
+        format!("speedtest_download_mbps{labels} 1"),
+        format!("speedtest_upload_mbps{labels} 0.5"),
+        format!("speedtest_ping_ms{labels} 20"),
+        format!("speedtest_bytes_received{labels} 100"),
+        format!("speedtest_last_run_timestamp_seconds{labels} 1754570096"),
+    ] {
+        assert!(body.lines().any(|line| line == sample), "missing {sample:?} in:\n{body}");
+    }
+    for name in ["speedtest_ping_ms", "speedtest_last_run_timestamp_seconds"] {
+        assert!(body.contains(&format!("# TYPE {name} gauge\n")));
+    }
+    assert!(!body.lines().any(|line| line.starts_with("speedtest_jitter_ms")), "jitter unknown:\n{body}");
+
+    assert!(!body.lines().any(|line| line.starts_with("speedtest_packet_loss_percent")), "loss unknown:\n{body}");
+    assert!(!body.lines().any(|line| line.starts_with("speedtest_bufferbloat")), "bufferbloat unknown:\n{body}");
+
+    set_last_result_for_test(SpeedTestResult {
+        jitter_ms: Some(1.5),
+        packet_loss: Some(0.25),
+        bufferbloat_ms: Some(85.0),
+        bufferbloat_grade: Some(models::BufferbloatGrade::C),
+        ..dummy_result()
+    });
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    for sample in [
+        "speedtest_jitter_ms 1.5",
+        "speedtest_packet_loss_percent 0.25",
+        "speedtest_bufferbloat_ms 85",
+        "speedtest_bufferbloat_grade 3",
+    ] {
+        assert!(body.lines().any(|line| line == sample), "missing {sample:?} in:\n{body}");
+    }
+    for name in ["speedtest_bufferbloat_ms", "speedtest_bufferbloat_grade"] {
+        assert!(body.contains(&format!("# TYPE {name} gauge\n")));
+    }
+
+    clear_last_result_for_test();
+}
+
+/// `METRICS_UNITS=mbps` keeps the Mbps gauge names, valued from the Mbps fields.
+#[actix_web::test]
+async fn metrics_units_mbps_export_megabits() {
+    assert_eq!("MBPS".parse::<MetricsUnits>(), Ok(MetricsUnits::Mbps));
+    let body = render_prometheus_in(&dummy_result(), MetricsUnits::Mbps);
+    for sample in ["speedtest_download_mbps 1", "speedtest_upload_mbps 0.5"] {
+        assert!(body.lines().any(|line| line == sample), "missing {sample:?} in:\n{body}");
+    }
+    assert!(!body.contains("bits_per_second"), "no base-unit gauges in:\n{body}");
+}
+
+/// `METRICS_UNITS=bps` replaces the Mbps gauges with base-unit gauges valued from
+/// the bits-per-second fields.
+#[actix_web::test]
+async fn metrics_units_bps_export_bits_per_second() {
+    assert_eq!("bps".parse::<MetricsUnits>(), Ok(MetricsUnits::Bps));
+    assert!("kbps".parse::<MetricsUnits>().is_err());
+    let body = render_prometheus_in(&dummy_result(), MetricsUnits::Bps);
+    for sample in [
+        "# TYPE speedtest_download_bits_per_second gauge",
+        "speedtest_download_bits_per_second 1000000",
+        "speedtest_upload_bits_per_second 500000",
+        "speedtest_ping_ms 20",
+    ] {
+        assert!(body.lines().any(|line| line == sample), "missing {sample:?} in:\n{body}");
+    }
+    assert!(!body.contains("_mbps"), "no Mbps gauges in:\n{body}");
+}
+
+/// `/history` lists results newest first, honours `limit`, and rejects a
+/// non-numeric limit.
+#[actix_web::test]
+#[serial]
+async fn history_lists_newest_first() {
+    let app = test::init_service(App::new().service(history_endpoint)).await;
+
+    clear_history_for_test();
+    let req = test::TestRequest::get().uri("/history").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(test::read_body(resp).await, "[]");
+
+    for ping_ms in [10.0, 20.0, 30.0] {
+        push_history_for_test(SpeedTestResult { ping_ms, ..dummy_result() });
+    }
+    let req = test::TestRequest::get().uri("/history").to_request();
+    let results: Vec<SpeedTestResult> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(results.iter().map(|r| r.ping_ms).collect::<Vec<_>>(), vec![30.0, 20.0, 10.0]);
+
+    let req = test::TestRequest::get().uri("/history?limit=2").to_request();
+    let results: Vec<SpeedTestResult> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(results.iter().map(|r| r.ping_ms).collect::<Vec<_>>(), vec![30.0, 20.0]);
+
+    let req = test::TestRequest::get().uri("/history?limit=lots").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    clear_history_for_test();
+}
+
+/// `/history.csv` serves the header and one row per result, newest first, and
+/// honours `limit`; an empty history gives just the header.
+#[actix_web::test]
+#[serial]
+async fn history_csv_lists_newest_first() {
+    let app = test::init_service(App::new().service(history_csv)).await;
+
+    clear_history_for_test();
+    let req = test::TestRequest::get().uri("/history.csv").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(resp.headers().get(http::header::CONTENT_TYPE).unwrap(), "text/csv; charset=utf-8");
+    assert_eq!(test::read_body(resp).await, format!("{CSV_HEADER}\n"));
+
+    let results: Vec<SpeedTestResult> =
+        [10.0, 20.0, 30.0, 40.0].map(|ping_ms| SpeedTestResult { ping_ms, ..dummy_result() }).into();
+    for result in &results {
+        push_history_for_test(result.clone());
+    }
+    let req = test::TestRequest::get().uri("/history.csv").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    let expected: Vec<String> = results.iter().rev().map(to_csv_row).collect();
+    assert_eq!(body, format!("{CSV_HEADER}\n{}\n", expected.join("\n")));
+
+    let req = test::TestRequest::get().uri("/history.csv?limit=2").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    assert_eq!(body.lines().collect::<Vec<_>>(), [CSV_HEADER, &expected[0], &expected[1]]);
+
+    clear_history_for_test();
+}
+
+/// `/speed.csv` serves a header and one row as a CSV download, or 503 while empty.
+#[actix_web::test]
+#[serial]
+async fn speed_csv_returns_header_and_row() {
+    let app = test::init_service(App::new().service(speed_csv)).await;
+
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/speed.csv").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    let mut result = dummy_result();
+    result.server.name = "London".to_string();
+    result.client.isp = "Example \"Fibre\"".to_string();
+    set_last_result_for_test(result);
+    let req = test::TestRequest::get().uri("/speed.csv").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(resp.headers().get(http::header::CONTENT_TYPE).unwrap(), "text/csv; charset=utf-8");
+    assert_eq!(
+        resp.headers().get(http::header::CONTENT_DISPOSITION).unwrap(),
+        "attachment; filename=\"speed.csv\""
+    );
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert_eq!(
+        body,
+        "timestamp,download_mbps,upload_mbps,ping_ms,jitter_ms,bytes_received,bytes_sent,server_name,isp\n\
+         2025-08-07T12:34:56Z,1,0.5,20,,100,200,London,\"Example \"\"Fibre\"\"\"\n"
+    );
+
+    clear_last_result_for_test();
+}
+
+/// `/stats` summarises the retained history.
+#[actix_web::test]
+#[serial]
+async fn stats_summarise_history() {
+    let app = test::init_service(App::new().service(stats::speed_stats_endpoint)).await;
+
+    clear_history_for_test();
+    for download_mbps in [10.0, 30.0] {
+        push_history_for_test(SpeedTestResult { download_mbps, ..dummy_result() });
+    }
+    let req = test::TestRequest::get().uri("/stats").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["download_mbps"]["mean"], 20.0);
+    assert_eq!(body["ping_ms"]["median"], 20.0);
+
+    clear_history_for_test();
+}
+
+/// `/stats?window=` only summarises results measured within the window, and
+/// rejects a window it cannot parse.
+#[actix_web::test]
+#[serial]
+async fn stats_window_restricts_history() {
+    let app = test::init_service(App::new().service(stats::speed_stats_endpoint)).await;
+
+    clear_history_for_test();
+    let ago = |minutes| (chrono::Utc::now() - chrono::Duration::minutes(minutes)).to_rfc3339();
+    for (download_mbps, timestamp) in [(10.0, ago(120)), (30.0, ago(30)), (50.0, ago(1))] {
+        push_history_for_test(SpeedTestResult { download_mbps, timestamp, ..dummy_result() });
+    }
+    let req = test::TestRequest::get().uri("/stats?window=1h").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["download_mbps"]["mean"], 40.0);
+
+    let req = test::TestRequest::get().uri("/stats?window=10s").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["count"], 0);
+
+    let req = test::TestRequest::get().uri("/stats?window=soon").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::BAD_REQUEST);
+
+    clear_history_for_test();
+}
+
+/// The raw endpoints return the speeds truncated to integers, without a decimal point.
+#[actix_web::test]
+#[serial]
+async fn raw_endpoints_return_integer_bps() {
+    let app = test::init_service(App::new().service(raw_download).service(raw_upload)).await;
+
+    clear_last_result_for_test();
+    let req = test::TestRequest::get().uri("/speed/raw-download").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    set_last_result_for_test(SpeedTestResult {
+        download_bps: 94_372_819.52,
+        upload_bps: 18_234_102.11,
+        ..dummy_result()
+    });
+    let req = test::TestRequest::get().uri("/speed/raw-download").to_request();
+    assert_eq!(test::call_and_read_body(&app, req).await, "94372819");
+    let req = test::TestRequest::get().uri("/speed/raw-upload").to_request();
+    assert_eq!(test::call_and_read_body(&app, req).await, "18234102");
+
+    clear_last_result_for_test();
+}
+
+/// MessagePack is served on request and decodes back to the cached result.
+#[actix_web::test]
+#[serial]
+async fn speedtest_serves_msgpack_when_requested() {
+    let mut cached = dummy_result();
+    cached.client.isp = "Example ISP".to_string();
+    set_last_result_for_test(cached.clone());
+    let app = test::init_service(App::new().service(speedtest)).await;
+
+    for req in [
+        test::TestRequest::get().uri("/speed").insert_header(("Accept", "application/msgpack")),
+        test::TestRequest::get().uri("/speed?format=msgpack"),
+    ] {
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.headers().get("content-type").unwrap(), "application/msgpack");
+        assert_eq!(resp.headers().get("vary").unwrap(), "Accept");
+
+        let body = test::read_body(resp).await;
+        let decoded: SpeedTestResult = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded, cached);
+    }
+
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("content-type").unwrap(), "application/json");
+
+    clear_last_result_for_test();
+}
+
+/// Both negotiated representations of `/speed` carry `Vary: Accept`, so caches keep
+/// them apart.
+#[actix_web::test]
+#[serial]
+async fn speedtest_varies_on_accept() {
+    set_last_result_for_test(dummy_result());
+    let app = test::init_service(App::new().service(speedtest)).await;
+
+    for accept in ["application/json", "application/msgpack"] {
+        let req = test::TestRequest::get().uri("/speed").insert_header((http::header::ACCEPT, accept)).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get(http::header::CONTENT_TYPE).unwrap(), accept);
+        assert_eq!(resp.headers().get(http::header::VARY).unwrap(), "Accept");
+    }
+
+    clear_last_result_for_test();
+}
+
+/// With the test endpoints enabled, a posted result is served by `/speed` and
+/// clearing the cache brings back the 503.
+#[actix_web::test]
+#[serial]
+async fn test_endpoints_set_and_clear_cache() {
+    clear_last_result_for_test();
+    let app = test::init_service(App::new().service(speedtest).configure(testmode::configure(true))).await;
+
+    let req = test::TestRequest::post().uri("/test/set").set_json(dummy_result()).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::NO_CONTENT);
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["timestamp"], dummy_result().timestamp);
+    assert_eq!(body["origin"], "ingested");
+    assert_eq!(get_last_result().unwrap().origin, Origin::Ingested);
+
+    let req = test::TestRequest::post().uri("/test/clear").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::NO_CONTENT);
+    let req = test::TestRequest::get().uri("/speed").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// With the flag off the test endpoints do not exist and the cache is untouched.
+#[actix_web::test]
+#[serial]
+async fn test_endpoints_absent_when_disabled() {
+    set_last_result_for_test(dummy_result());
+    let app = test::init_service(App::new().configure(testmode::configure(false))).await;
+
+    for uri in ["/test/set", "/test/clear"] {
+        let req = test::TestRequest::post().uri(uri).set_json(SpeedTestResult::default()).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::NOT_FOUND);
+    }
+    assert_eq!(get_last_result(), Some(dummy_result()));
+    clear_last_result_for_test();
+}
+
+/// Bodies over the configured limit are refused with 413 on the JSON and raw body
+/// endpoints alike, while a normal result is still accepted.
+#[actix_web::test]
+#[serial]
+async fn oversized_bodies_rejected() {
+    use speedtest_statuspage::maintenance::maintenance_endpoint;
+
+    clear_last_result_for_test();
+    let app = test::init_service(
+        App::new()
+            .configure(limits::configure(1024))
+            .configure(testmode::configure(true))
+            .service(maintenance_endpoint),
+    )
+    .await;
+
+    let req = test::TestRequest::post().uri("/test/set").set_json(dummy_result()).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::NO_CONTENT);
+
+    let mut oversized = dummy_result();
+    oversized.tags.insert("padding".to_string(), "x".repeat(2048));
+    let req = test::TestRequest::post().uri("/test/set").set_json(&oversized).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(get_last_result().unwrap().tags, dummy_result().tags);
+
+    let req = test::TestRequest::post().uri("/maintenance").set_payload("x".repeat(2048)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::PAYLOAD_TOO_LARGE);
+
+    clear_last_result_for_test();
+}
+
+/// The middleware sets the configured `Server` header, and removes it when the
+/// configured value is empty.
+#[actix_web::test]
+async fn server_header_set_or_omitted() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::branding::{set_server_header, ServerHeader};
+
+    for (configured, expected) in [("acme-probe/2", Some("acme-probe/2")), ("", None)] {
+        let app = test::init_service(
+            App::new()
+                .app_data(actix_web::web::Data::new(ServerHeader::new(configured).unwrap()))
+                .wrap(from_fn(set_server_header))
+                .route(
+                    "/",
+                    actix_web::web::get().to(|| async {
+                        actix_web::HttpResponse::Ok().insert_header(("Server", "actix")).finish()
+                    }),
+                ),
+        )
+        .await;
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let server = resp.headers().get(http::header::SERVER).map(|v| v.to_str().unwrap());
+        assert_eq!(server, expected, "SERVER_HEADER={configured:?}");
+    }
+}
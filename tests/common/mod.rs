@@ -0,0 +1,134 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Helpers shared between the integration test binaries.
+
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+use actix_web::dev::ServerHandle;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use async_trait::async_trait;
+use speedtest_statuspage::*;
+
+/// Sample `speedtest-cli --json` output.
+pub const SPEEDTEST_CLI_JSON: &str = include_str!("../fixtures/speedtest_cli.json");
+
+/// Creates a `SpeedTestResult` with every core metric populated.
+pub fn complete_result() -> SpeedTestResult {
+    SpeedTestResult {
+        bytes_received: 100,
+        bytes_sent: 200,
+        download_bps: 1_000_000.0,
+        upload_bps: 500_000.0,
+        download_mbps: 1.0,
+        upload_mbps: 0.5,
+        ping_ms: 20.0,
+        client: Default::default(),
+        server: Default::default(),
+        share: None,
+        timestamp: "2025-08-07T12:34:56Z".to_string(),
+        ..Default::default()
+    }
+}
+
+/// A mock runner which records the configuration it was invoked with
+/// and returns a canned output.
+pub struct RecordingMockRunner {
+    pub output: Result<String, SpeedtestError>,
+    pub seen: Mutex<Vec<RunnerConfig>>,
+}
+
+impl RecordingMockRunner {
+    pub fn new(output: Result<String, SpeedtestError>) -> Self {
+        Self { output, seen: Mutex::new(Vec::new()) }
+    }
+
+    /// A runner returning the `speedtest-cli` fixture.
+    pub fn succeeding() -> Self {
+        Self::new(Ok(SPEEDTEST_CLI_JSON.to_string()))
+    }
+
+    /// A runner failing with `message`.
+    pub fn failing(message: &str) -> Self {
+        Self::new(Err(SpeedtestError::CommandFailed(message.to_string())))
+    }
+}
+
+#[async_trait]
+impl SpeedtestRunner for RecordingMockRunner {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        self.seen.lock().unwrap().push(cfg.clone());
+        self.output.clone()
+    }
+}
+
+/// A request captured by [`MockServer`].
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+/// A local HTTP server recording every request it receives.
+pub struct MockServer {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    handle: ServerHandle,
+}
+
+impl MockServer {
+    /// Starts a server on an ephemeral port answering every request with `status`.
+    ///
+    /// Must be called from within an Actix runtime (e.g. `#[actix_web::test]`).
+    pub async fn start(status: u16) -> Self {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let server = HttpServer::new(move || {
+            let recorded = recorded.clone();
+            App::new().default_service(web::to(move |req: HttpRequest, body: web::Bytes| {
+                let recorded = recorded.clone();
+                async move {
+                    recorded.lock().unwrap().push(RecordedRequest {
+                        method: req.method().to_string(),
+                        path: req.path().to_string(),
+                        body: body.to_vec(),
+                    });
+                    HttpResponse::build(actix_web::http::StatusCode::from_u16(status).unwrap()).finish()
+                }
+            }))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+        MockServer { url: format!("http://{}", addr), requests, handle }
+    }
+
+    /// Returns a snapshot of the requests received so far.
+    pub fn received(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Stops the server.
+    pub async fn stop(self) {
+        self.handle.stop(true).await;
+    }
+}
@@ -0,0 +1,191 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the environment variable registry and the generated `.env` template.
+
+use speedtest_statuspage::config::{self, env_template, ENV_VARS};
+
+/// The template lists the server and scheduler settings with their defaults.
+#[test]
+fn template_includes_bind_port_and_interval() {
+    let template = env_template();
+    assert!(template.contains("\nBIND_PORT=8080\n"), "{template}");
+    assert!(template.contains("\nINTERVAL_MINUTES=10\n"), "{template}");
+}
+
+/// Variables without a default are emitted commented out, after their description.
+#[test]
+fn template_comments_out_variables_without_default() {
+    let template = env_template();
+    assert!(template.contains("# URL pinged after every successful run\n#HEARTBEAT_URL=\n"));
+}
+
+/// Every registered variable appears exactly once, and every default parses where
+/// the application relies on [`config::EnvVar::get`].
+#[test]
+fn registry_is_consistent() {
+    let template = env_template();
+    for var in ENV_VARS {
+        let lines = template
+            .lines()
+            .filter(|line| line.trim_start_matches('#').starts_with(&format!("{}=", var.name)))
+            .count();
+        assert_eq!(lines, 1, "{} should appear once", var.name);
+    }
+    assert!(config::BIND_PORT.default.unwrap().parse::<u16>().is_ok());
+    assert!(config::INTERVAL_MINUTES.default.unwrap().parse::<u64>().is_ok());
+    assert!(config::FAILURE_LOG_EVERY.default.unwrap().parse::<u64>().is_ok());
+    assert!(config::COMPARE_TIMEOUT_SECS.default.unwrap().parse::<u64>().is_ok());
+    assert!(config::AUTO_TUNE_REFRESH_HOURS.default.unwrap().parse::<u64>().is_ok());
+    assert!(config::AUTO_TUNE_CANDIDATES.default.unwrap().parse::<usize>().is_ok());
+}
+
+/// Near-miss names suggest the registered variable they were meant to be.
+#[test]
+fn near_miss_names_suggest_registered_variable() {
+    assert_eq!(config::suggest_name("INTERVAL_MINUTE"), Some("INTERVAL_MINUTES"));
+    assert_eq!(config::suggest_name("SPEEDTEST_BAKEND"), Some("SPEEDTEST_BACKEND"));
+    assert_eq!(config::suggest_name("heartbeat_url"), Some("HEARTBEAT_URL"));
+    assert_eq!(config::suggest_name("INTERVAL_MINUTES"), None);
+    assert_eq!(config::suggest_name("HOSTNAME"), None);
+    assert_eq!(config::suggest_name("PATH"), None);
+}
+
+/// The report separates set, unset and mistyped variables.
+#[test]
+fn diagnose_env_reports_typos_and_unset_variables() {
+    use speedtest_statuspage::debug::{diagnose_env, PossibleTypo};
+
+    let report = diagnose_env(["HOME", "BIND_PORT", "INTERVAL_MINUTE", "LANG"]);
+    assert_eq!(report.recognized_set, ["BIND_PORT"]);
+    assert!(report.recognized_unset.contains(&"INTERVAL_MINUTES"));
+    assert!(!report.recognized_unset.contains(&"BIND_PORT"));
+    assert_eq!(
+        report.possible_typos,
+        [PossibleTypo { name: "INTERVAL_MINUTE".to_string(), suggestion: "INTERVAL_MINUTES" }]
+    );
+}
+
+/// Bogus server settings are all reported at once, each naming the variable and value.
+#[test]
+fn invalid_server_settings_are_reported() {
+    // SAFETY: no other test in this binary reads these variables.
+    unsafe {
+        std::env::set_var("BIND_PORT", "99999");
+        std::env::set_var("INTERVAL_MINUTES", "0");
+    }
+    let err = config::Config::from_env().unwrap_err();
+    unsafe {
+        std::env::remove_var("BIND_PORT");
+        std::env::remove_var("INTERVAL_MINUTES");
+    }
+
+    assert_eq!(
+        err.to_string(),
+        "invalid configuration: BIND_PORT must be a port number from 0 to 65535, got \"99999\"; \
+         INTERVAL_MINUTES must be a positive whole number of minutes, got \"0\""
+    );
+}
+
+/// An interval too large to express in seconds is a configuration error rather
+/// than an overflow.
+#[test]
+fn oversized_interval_is_reported() {
+    let lookup = |name: &str| (name == "INTERVAL_MINUTES").then(|| u64::MAX.to_string());
+    let err = config::Config::from_lookup(lookup).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        format!("invalid configuration: INTERVAL_MINUTES must be a positive whole number of minutes, got \"{}\"", u64::MAX)
+    );
+}
+
+/// Unset variables fall back to the registered defaults.
+#[test]
+fn server_settings_default_when_unset() {
+    let cfg = config::Config::from_lookup(|_| None).unwrap();
+    assert_eq!(cfg.bind_address, "127.0.0.1");
+    assert_eq!(cfg.bind_port, 8080);
+    assert_eq!(cfg.bind_targets, [config::BindTarget::Tcp { host: "127.0.0.1".to_string(), port: 8080 }]);
+    assert_eq!(cfg.interval, std::time::Duration::from_secs(600));
+}
+
+/// A `BIND_ADDRESS` list splits into one target per entry, each with its own or
+/// the shared port.
+#[test]
+fn bind_address_list_parses_into_targets() {
+    use config::BindTarget;
+
+    let tcp = |host: &str, port| BindTarget::Tcp { host: host.to_string(), port };
+    let targets = BindTarget::parse_list("0.0.0.0, ::, [::1]:9090,localhost:81,,unix:/run/speed.sock", 8080).unwrap();
+    assert_eq!(
+        targets,
+        [
+            tcp("0.0.0.0", 8080),
+            tcp("::", 8080),
+            tcp("::1", 9090),
+            tcp("localhost", 81),
+            BindTarget::Unix("/run/speed.sock".into()),
+        ]
+    );
+    let shown: Vec<_> = targets.iter().map(ToString::to_string).collect();
+    assert_eq!(shown, ["0.0.0.0:8080", "[::]:8080", "[::1]:9090", "localhost:81", "unix:/run/speed.sock"]);
+}
+
+/// `METRICS_BIND_PORT` moves the metrics listener onto each distinct TCP host of
+/// `BIND_ADDRESS`, and is rejected when invalid or without a TCP host.
+#[test]
+fn metrics_bind_port_uses_tcp_hosts() {
+    use config::{BindTarget, Config};
+
+    let lookup = |address: &'static str, metrics_port: &'static str| {
+        move |key: &str| match key {
+            "BIND_ADDRESS" => Some(address.to_string()),
+            "METRICS_BIND_PORT" => Some(metrics_port.to_string()),
+            _ => None,
+        }
+    };
+    assert!(Config::from_lookup(lookup("0.0.0.0", "")).unwrap().metrics_bind_targets.is_empty());
+
+    let cfg = Config::from_lookup(lookup("0.0.0.0,[::1]:81,0.0.0.0:82,unix:/run/speed.sock", "9100")).unwrap();
+    let tcp = |host: &str| BindTarget::Tcp { host: host.to_string(), port: 9100 };
+    assert_eq!(cfg.metrics_bind_targets, [tcp("0.0.0.0"), tcp("::1")]);
+
+    let err = Config::from_lookup(lookup("0.0.0.0", "prometheus")).unwrap_err();
+    assert_eq!(err.errors, ["METRICS_BIND_PORT must be a port number from 0 to 65535, got \"prometheus\""]);
+    let err = Config::from_lookup(lookup("unix:/run/speed.sock", "9100")).unwrap_err();
+    assert_eq!(err.errors, ["METRICS_BIND_PORT needs a TCP address in BIND_ADDRESS"]);
+}
+
+/// Malformed `BIND_ADDRESS` entries are reported rather than bound.
+#[test]
+fn bind_address_list_rejects_bad_entries() {
+    use config::BindTarget;
+
+    for (list, expected) in [
+        (" , ", "no addresses given"),
+        ("0.0.0.0:http", "entry \"0.0.0.0:http\" has an invalid port"),
+        ("[::1]:70000", "entry \"[::1]:70000\" has an invalid port"),
+        ("[::1", "entry \"[::1\" has an unclosed '['"),
+        (":8080", "entry \":8080\" has no host"),
+        ("unix:", "entry \"unix:\" has no socket path"),
+    ] {
+        assert_eq!(BindTarget::parse_list(list, 8080).unwrap_err(), expected, "BIND_ADDRESS={list:?}");
+    }
+
+    let err = config::Config::from_lookup(|key| (key == "BIND_ADDRESS").then(|| "127.0.0.1,[::1]:x".to_string()));
+    assert_eq!(err.unwrap_err().errors, ["BIND_ADDRESS entry \"[::1]:x\" has an invalid port"]);
+}
@@ -0,0 +1,60 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `db` module, the SQLite result store behind the `sqlite` feature.
+
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// A result inserted into an in-memory database loads back unchanged, and the
+/// newest insert is the one returned.
+#[test]
+fn database_round_trips_latest_result() {
+    let store = db::ResultStore::open(":memory:").unwrap();
+    assert_eq!(store.latest().unwrap(), None);
+
+    let first = SpeedTestResult { schema_version: SCHEMA_VERSION, ..complete_result() };
+    let mut second = SpeedTestResult { node: "pi".to_string(), jitter_ms: Some(1.5), ..first.clone() };
+    second.server.id = "12345".to_string();
+    second.client.isp = "Example ISP".to_string();
+    store.insert(&first).unwrap();
+    store.insert(&second).unwrap();
+    assert_eq!(store.latest().unwrap(), Some(second));
+}
+
+/// Results saved to a database file survive reopening it.
+#[test]
+fn database_file_persists_across_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("results.db");
+    let result = SpeedTestResult { schema_version: SCHEMA_VERSION, ..complete_result() };
+
+    db::ResultStore::open(&path).unwrap().insert(&result).unwrap();
+    assert_eq!(db::ResultStore::open(&path).unwrap().latest().unwrap(), Some(result));
+}
+
+/// Clearing the database removes every stored result.
+#[test]
+fn database_clear_removes_every_result() {
+    let store = db::ResultStore::open(":memory:").unwrap();
+    store.insert(&complete_result()).unwrap();
+    store.clear().unwrap();
+    assert_eq!(store.latest().unwrap(), None);
+}
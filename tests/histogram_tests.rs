@@ -0,0 +1,61 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `histogram` module, which renders Prometheus histograms.
+
+use speedtest_statuspage::histogram::{Bounds, Histogram};
+
+/// Observations land in cumulative buckets, including the `+Inf` bucket, with a
+/// matching `_sum` and `_count`; non-finite values are ignored.
+#[test]
+fn histogram_renders_cumulative_buckets() {
+    let mut histogram = Histogram::new("10,30,60".parse().unwrap());
+    for seconds in [4.5, 10.0, 22.0, 25.5, 59.0, 61.0, 240.0, f64::NAN] {
+        histogram.observe(seconds);
+    }
+    assert_eq!(histogram.count(), 7);
+    let text = histogram.render("speedtest_run_duration_seconds", "Time taken.");
+    assert_eq!(
+        text.lines().collect::<Vec<_>>(),
+        [
+            "# HELP speedtest_run_duration_seconds Time taken.",
+            "# TYPE speedtest_run_duration_seconds histogram",
+            "speedtest_run_duration_seconds_bucket{le=\"10\"} 2",
+            "speedtest_run_duration_seconds_bucket{le=\"30\"} 4",
+            "speedtest_run_duration_seconds_bucket{le=\"60\"} 5",
+            "speedtest_run_duration_seconds_bucket{le=\"+Inf\"} 7",
+            "speedtest_run_duration_seconds_sum 422",
+            "speedtest_run_duration_seconds_count 7",
+        ]
+    );
+}
+
+/// An empty histogram renders only its metadata.
+#[test]
+fn empty_histogram_renders_metadata_only() {
+    let histogram = Histogram::new("1,2".parse().unwrap());
+    let text = histogram.render("speedtest_run_duration_seconds", "Time taken.");
+    assert!(text.lines().all(|line| line.starts_with('#')), "{text}");
+}
+
+/// Bucket bounds must be finite numbers in increasing order.
+#[test]
+fn bounds_reject_invalid_lists() {
+    assert_eq!("0.5, 1,2.5".parse::<Bounds>(), Ok(Bounds(vec![0.5, 1.0, 2.5])));
+    for invalid in ["", " , ", "1,x", "5,5", "10,5", "1,inf"] {
+        assert!(invalid.parse::<Bounds>().is_err(), "{invalid:?} accepted");
+    }
+}
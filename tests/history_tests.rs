@@ -0,0 +1,58 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for clearing the result history with `POST /history/clear`.
+//!
+//! The endpoint reads its token from the environment, so these tests run serially.
+
+use actix_web::{http, test, App};
+use serial_test::serial;
+use speedtest_statuspage::*;
+
+/// A seeded history is emptied by an authenticated clear, which can be repeated,
+/// after which `/history` returns `[]`.
+#[actix_web::test]
+#[serial]
+async fn history_clear_empties_history() {
+    let app = test::init_service(App::new().service(history_endpoint).service(clear_history_endpoint)).await;
+    let clear = |token: &str| {
+        test::TestRequest::post()
+            .uri("/history/clear")
+            .insert_header((http::header::AUTHORIZATION, format!("Bearer {}", token)))
+            .to_request()
+    };
+    for download_mbps in [10.0, 20.0, 30.0] {
+        push_history_for_test(SpeedTestResult { download_mbps, ..Default::default() });
+    }
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::remove_var("HISTORY_CLEAR_TOKEN") };
+    assert_eq!(test::call_service(&app, clear("s3cret")).await.status(), http::StatusCode::NOT_FOUND);
+
+    // SAFETY: as above.
+    unsafe { std::env::set_var("HISTORY_CLEAR_TOKEN", "s3cret") };
+    assert_eq!(test::call_service(&app, clear("wrong")).await.status(), http::StatusCode::UNAUTHORIZED);
+    assert_eq!(get_history().len(), 3);
+
+    for _ in 0..2 {
+        assert_eq!(test::call_service(&app, clear("s3cret")).await.status(), http::StatusCode::NO_CONTENT);
+    }
+    let req = test::TestRequest::get().uri("/history").to_request();
+    assert_eq!(test::call_and_read_body(&app, req).await, "[]");
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("HISTORY_CLEAR_TOKEN") };
+}
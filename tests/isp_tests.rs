@@ -0,0 +1,76 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `isp` module, which compares results with the ISP averages.
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// Measured speeds are compared with the ISP averages, converted from kbps.
+#[test]
+fn isp_comparison_computes_percent_difference() {
+    let mut result = complete_result();
+    result.download_mbps = 75.0;
+    result.upload_mbps = 9.0;
+    result.client.ispdlavg = "50000".to_string();
+    result.client.ispulavg = "10000".to_string();
+
+    let comparison = isp::compare_to_isp_average(&result);
+    assert_eq!(comparison.isp_download_avg_mbps, Some(50.0));
+    assert_eq!(comparison.isp_upload_avg_mbps, Some(10.0));
+    assert_eq!(comparison.download_percent_difference, Some(50.0));
+    assert!((comparison.upload_percent_difference.unwrap() + 10.0).abs() < 1e-9);
+    assert!(comparison.isp_average_available);
+}
+
+/// Empty, zero or unparseable ISP averages produce nulls and a clear indicator.
+#[test]
+fn isp_comparison_handles_missing_averages() {
+    let mut result = complete_result();
+    result.client.ispdlavg = "0".to_string();
+    result.client.ispulavg = "n/a".to_string();
+
+    let comparison = isp::compare_to_isp_average(&result);
+    assert!(!comparison.isp_average_available);
+
+    let json = serde_json::to_value(&comparison).unwrap();
+    assert!(json["isp_download_avg_mbps"].is_null());
+    assert!(json["upload_percent_difference"].is_null());
+    assert_eq!(json["download_mbps"], 1.0);
+}
+
+/// A result well above the ISP average ranks near the top, one well below it near
+/// the bottom.
+#[test]
+fn isp_percentile_ranks_fast_and_slow_results() {
+    let mut result = SpeedTestResult::default();
+    result.client.isp = "Example ISP".to_string();
+    result.client.ispdlavg = "50000".to_string();
+
+    result.download_mbps = 100.0;
+    let fast = isp::percentile_vs_isp(&result);
+    assert!(fast.sufficient_data);
+    assert!((fast.download_percentile.unwrap() - 91.7).abs() < 0.1);
+    assert_eq!(fast.summary, "You're in the top 8% of Example ISP customers for download speed");
+
+    result.download_mbps = 25.0;
+    let slow = isp::percentile_vs_isp(&result);
+    assert!((slow.download_percentile.unwrap() - 8.3).abs() < 0.1);
+    assert_eq!(slow.summary, "You're in the top 92% of Example ISP customers for download speed");
+    assert_eq!(slow.upload_percentile, None);
+}
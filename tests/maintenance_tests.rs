@@ -0,0 +1,175 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for maintenance mode.
+//!
+//! Maintenance mode is process-wide state and its endpoint and notification
+//! targets are configured through the environment, so these tests live in their
+//! own binary and run serially.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+use actix_web::{http, test, App};
+use common::{MockServer, RecordingMockRunner};
+use serial_test::serial;
+use speedtest_statuspage::maintenance::{maintenance_endpoint, MAINTENANCE};
+use speedtest_statuspage::*;
+
+/// An expiry switches maintenance mode off by itself.
+#[actix_web::test]
+#[serial]
+async fn maintenance_expires() {
+    MAINTENANCE.enable(Some(Duration::from_millis(50)));
+    assert!(maintenance::is_active());
+    assert!(MAINTENANCE.expires_at().is_some());
+
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert!(!maintenance::is_active());
+    assert_eq!(MAINTENANCE.expires_at(), None);
+
+    MAINTENANCE.enable(None);
+    assert!(maintenance::is_active());
+    MAINTENANCE.disable();
+    assert!(!maintenance::is_active());
+}
+
+/// The scheduler skips every run while maintenance mode is on.
+#[actix_web::test]
+#[serial]
+async fn scheduler_skips_runs_during_maintenance() {
+    MAINTENANCE.enable(None);
+    let runner = Arc::new(RecordingMockRunner::succeeding());
+    let scheduler_runner = runner.clone();
+    let (_stop, shutdown) = tokio::sync::watch::channel(false);
+    let scheduler = tokio::spawn(async move {
+        let schedule = schedule::Schedule::fixed(Duration::from_millis(20));
+        run_scheduler(scheduler_runner.as_ref(), &RunnerConfig::default(), &schedule, None, None, shutdown).await;
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(runner.seen.lock().unwrap().is_empty());
+
+    MAINTENANCE.disable();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    scheduler.abort();
+    assert!(!runner.seen.lock().unwrap().is_empty());
+    clear_last_result_for_test();
+}
+
+/// No alert is sent for runs during maintenance.
+#[actix_web::test]
+#[serial]
+async fn alerts_suppressed_during_maintenance() {
+    let server = MockServer::start(200).await;
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("HEARTBEAT_URL", format!("{}/ping", server.url)) };
+    let cfg = RunnerConfig::default();
+
+    MAINTENANCE.enable(None);
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    notify::flush().await;
+    assert!(server.received().is_empty());
+
+    MAINTENANCE.disable();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    notify::flush().await;
+    assert_eq!(server.received().len(), 1);
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("HEARTBEAT_URL") };
+    clear_last_result_for_test();
+}
+
+/// `POST /maintenance` requires the token, toggles the state and flags `/speed`.
+#[actix_web::test]
+#[serial]
+async fn maintenance_endpoint_toggles_flag_on_speed() {
+    let app = test::init_service(App::new().service(maintenance_endpoint).service(speedtest)).await;
+    let toggle = |enabled: bool, token: Option<&str>| {
+        let req = test::TestRequest::post()
+            .uri("/maintenance")
+            .set_json(serde_json::json!({ "enabled": enabled, "expires_in_minutes": 60 }));
+        match token {
+            Some(token) => req.insert_header(("Authorization", format!("Bearer {}", token))),
+            None => req,
+        }
+        .to_request()
+    };
+    let speed = || test::TestRequest::get().uri("/speed").to_request();
+
+    let resp = test::call_service(&app, toggle(true, Some("secret"))).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("MAINTENANCE_TOKEN", "secret") };
+    let resp = test::call_service(&app, toggle(true, Some("wrong"))).await;
+    assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+    assert!(!maintenance::is_active());
+
+    let status: serde_json::Value = test::call_and_read_body_json(&app, toggle(true, Some("secret"))).await;
+    assert_eq!(status["enabled"], true);
+    assert!(status["expires_at"].is_string());
+
+    set_last_result_for_test(SpeedTestResult { timestamp: "2025-08-07T12:00:00Z".to_string(), ..Default::default() });
+    let body: serde_json::Value = test::call_and_read_body_json(&app, speed()).await;
+    assert_eq!(body["maintenance"], true);
+
+    let status: serde_json::Value = test::call_and_read_body_json(&app, toggle(false, Some("secret"))).await;
+    assert_eq!(status["enabled"], false);
+    let body: serde_json::Value = test::call_and_read_body_json(&app, speed()).await;
+    assert!(body.get("maintenance").is_none());
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("MAINTENANCE_TOKEN") };
+    clear_last_result_for_test();
+}
+
+/// Unauthenticated requests are turned away before their body is looked at, and an
+/// expiry too far off to represent keeps maintenance on instead of overflowing.
+#[actix_web::test]
+#[serial]
+async fn maintenance_endpoint_checks_token_before_body() {
+    let app = test::init_service(App::new().service(maintenance_endpoint)).await;
+    let post = |body: &str, token: &str| {
+        test::TestRequest::post()
+            .uri("/maintenance")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(body.to_string())
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, post("not json", "secret")).await;
+    assert_eq!(resp.status(), http::StatusCode::NOT_FOUND);
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("MAINTENANCE_TOKEN", "secret") };
+    let resp = test::call_service(&app, post("not json", "wrong")).await;
+    assert_eq!(resp.status(), http::StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, post("not json", "secret")).await;
+    assert_eq!(resp.status(), http::StatusCode::BAD_REQUEST);
+
+    let forever = format!("{{\"enabled\": true, \"expires_in_minutes\": {}}}", u64::MAX);
+    let resp = test::call_service(&app, post(&forever, "secret")).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert!(maintenance::is_active());
+
+    MAINTENANCE.disable();
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("MAINTENANCE_TOKEN") };
+}
@@ -0,0 +1,45 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `metadata` module, which attaches `METADATA_HOOK` output.
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// Output from a metadata hook is attached to the result under `metadata`.
+#[tokio::test]
+async fn hook_json_is_merged_into_metadata() {
+    let stdout = metadata::run_hook(r#"printf '{"temp_c": 21.5, "room": "study"}'"#)
+        .await
+        .unwrap();
+    let mut result = complete_result();
+    assert!(metadata::merge_hook_output(&mut result, &stdout));
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["metadata"]["temp_c"], 21.5);
+    assert_eq!(json["metadata"]["room"], "study");
+}
+
+/// Malformed hook output leaves the result without metadata.
+#[test]
+fn malformed_hook_output_is_ignored() {
+    let mut result = complete_result();
+    assert!(!metadata::merge_hook_output(&mut result, "temp=21.5"));
+    assert!(!metadata::merge_hook_output(&mut result, "42"));
+    assert!(result.metadata.is_null());
+}
@@ -0,0 +1,388 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Unit tests for the helper methods on the `models` types.
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// A result with download, upload and ping all present is complete.
+#[test]
+fn is_complete_accepts_fully_populated_result() {
+    assert!(complete_result().is_complete());
+}
+
+/// A zero download (partial failure) marks the result incomplete.
+#[test]
+fn is_complete_rejects_zero_download() {
+    let result = SpeedTestResult {
+        download_bps: 0.0,
+        download_mbps: 0.0,
+        ..complete_result()
+    };
+    assert!(!result.is_complete());
+}
+
+/// A zero ping marks the result incomplete.
+#[test]
+fn is_complete_rejects_zero_ping() {
+    let result = SpeedTestResult {
+        ping_ms: 0.0,
+        ..complete_result()
+    };
+    assert!(!result.is_complete());
+}
+
+/// Non-finite metrics are treated as implausible.
+#[test]
+fn is_complete_rejects_non_finite_values() {
+    let result = SpeedTestResult {
+        upload_bps: f64::NAN,
+        ..complete_result()
+    };
+    assert!(!result.is_complete());
+}
+
+/// A fully-populated result survives a JSON round-trip unchanged.
+#[test]
+fn speedtest_result_serde_round_trip() {
+    let result = SpeedTestResult {
+        client: ClientInfo {
+            country: "GB".to_string(),
+            ip: "192.0.2.1".to_string(),
+            isp: "Example ISP".to_string(),
+            ispdlavg: "50".to_string(),
+            isprating: "3.7".to_string(),
+            ispulavg: "10".to_string(),
+            lat: "51.5".to_string(),
+            loggedin: "0".to_string(),
+            lon: "-0.12".to_string(),
+            rating: "0".to_string(),
+        },
+        server: ServerInfo {
+            cc: "GB".to_string(),
+            country: "United Kingdom".to_string(),
+            d: 4.21,
+            host: "speedtest.example.net:8080".to_string(),
+            id: "12345".to_string(),
+            lat: "51.5074".to_string(),
+            latency: 12.345,
+            lon: "-0.1278".to_string(),
+            name: "London".to_string(),
+            sponsor: "Example Networks".to_string(),
+            url: "http://speedtest.example.net:8080/speedtest/upload.php".to_string(),
+        },
+        share: Some(serde_json::json!("http://www.speedtest.net/result/1.png")),
+        nic_speed_mbps: Some(1000.0),
+        percent_of_nic: Some(0.1),
+        ..complete_result()
+    };
+
+    let json = serde_json::to_string(&result).unwrap();
+    let parsed: SpeedTestResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, result);
+}
+
+/// The configured node name wins over the hostname and appears in the JSON.
+#[test]
+fn node_name_configured_appears_in_json() {
+    let node = resolve_node_name(Some("living-room".to_string()), || Some("raspberrypi".to_string()));
+    let result = SpeedTestResult {
+        node,
+        ..complete_result()
+    };
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["node"], "living-room");
+}
+
+/// The hostname is used when no node name is configured, with a final fallback.
+#[test]
+fn node_name_falls_back_to_hostname() {
+    assert_eq!(resolve_node_name(None, || Some("raspberrypi\n".to_string())), "raspberrypi");
+    assert_eq!(resolve_node_name(Some(String::new()), || None), "unknown");
+}
+
+/// Results stored before the node field existed still deserialize.
+#[test]
+fn node_defaults_when_missing_from_old_json() {
+    let mut json = serde_json::to_value(complete_result()).unwrap();
+    json.as_object_mut().unwrap().remove("node");
+
+    let result: SpeedTestResult = serde_json::from_value(json).unwrap();
+    assert_eq!(result.node, "");
+}
+
+/// Negative and non-finite speeds are replaced with zero; valid values pass through.
+#[test]
+fn sanitize_speed_clamps_invalid_values() {
+    assert_eq!(sanitize_speed(-12.5), 0.0);
+    assert_eq!(sanitize_speed(f64::NAN), 0.0);
+    assert_eq!(sanitize_speed(f64::INFINITY), 0.0);
+    assert_eq!(sanitize_speed(f64::NEG_INFINITY), 0.0);
+    assert_eq!(sanitize_speed(0.0), 0.0);
+    assert_eq!(sanitize_speed(94_372_819.52), 94_372_819.52);
+}
+
+/// A glitched `speedtest-cli` response converts to a result that serializes as numbers.
+#[test]
+fn conversion_sanitizes_glitched_speeds() {
+    let response = SpeedTestResponse {
+        bytes_received: 0,
+        bytes_sent: 0,
+        client: ClientInfo::default(),
+        download: f64::NAN,
+        ping: 20.0,
+        server: ServerInfo::default(),
+        share: None,
+        timestamp: String::new(),
+        upload: -1.0,
+        jitter_ms: Some(f64::NAN),
+    };
+    let result = SpeedTestResult::from(response);
+
+    assert_eq!(result.download_bps, 0.0);
+    assert_eq!(result.download_mbps, 0.0);
+    assert_eq!(result.upload_bps, 0.0);
+    assert_eq!(result.ping_ms, 20.0);
+    assert_eq!(result.jitter_ms, None);
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["download_bps"], 0.0);
+}
+
+/// The flat numeric map lists every measurement by field name.
+#[test]
+fn numeric_fields_lists_measurements() {
+    let mut result = complete_result();
+    result.set_nic_speed(10.0);
+    let fields = result.numeric_fields();
+
+    let keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+    assert_eq!(
+        keys,
+        [
+            "bytes_received", "bytes_sent", "download_bps", "download_mbps", "nic_speed_mbps",
+            "percent_of_nic", "ping_ms", "upload_bps", "upload_mbps",
+        ]
+    );
+    assert_eq!(fields["bytes_received"], 100.0);
+    assert_eq!(fields["download_bps"], 1_000_000.0);
+    assert_eq!(fields["upload_mbps"], 0.5);
+    assert_eq!(fields["ping_ms"], 20.0);
+    assert_eq!(fields["percent_of_nic"], 10.0);
+}
+
+/// A result stored by the first release upgrades to the current layout.
+#[test]
+fn v1_result_migrates_to_current_schema() {
+    let v1: serde_json::Value = serde_json::from_str(include_str!("fixtures/result_v1.json")).unwrap();
+    let result = migrate_result(v1).unwrap();
+
+    assert_eq!(result.schema_version, SCHEMA_VERSION);
+    assert_eq!(result.download_bps, 94_372_819.52);
+    assert_eq!(result.server.id, "12345");
+    assert_eq!(result.client.isp, "Example ISP");
+    assert_eq!(result.node, "");
+    assert_eq!(result.nic_speed_mbps, None);
+    assert!(result.metadata.is_null());
+    assert_eq!(result.origin, Origin::Scheduled);
+}
+
+/// A fast, low-latency connection scores full marks.
+#[test]
+fn quality_score_ideal_result_is_near_100() {
+    let cfg = ScoreWeights::default();
+    assert_eq!(connection_score(500.0, 100.0, 4.0, Some(0.5), &cfg), 100);
+    let result = SpeedTestResult { download_mbps: 95.0, upload_mbps: 20.0, ping_ms: 15.0, ..complete_result() };
+    assert!(result.quality_score(&cfg) >= 97);
+}
+
+/// A barely working connection scores close to zero.
+#[test]
+fn quality_score_terrible_result_is_near_0() {
+    let cfg = ScoreWeights::default();
+    assert!(connection_score(0.5, 0.05, 2_000.0, Some(400.0), &cfg) <= 2);
+    assert_eq!(connection_score(0.0, 0.0, 0.0, None, &cfg), 0);
+    assert_eq!(connection_score(f64::NAN, -1.0, f64::INFINITY, None, &cfg), 0);
+}
+
+/// Without jitter, its weight is spread over the remaining metrics.
+#[test]
+fn quality_score_redistributes_missing_jitter_weight() {
+    let cfg = ScoreWeights::default();
+    // Perfect download and upload, ping at twice the reference (0.5): 0.6 + 0.3 * 0.5
+    // over the remaining 0.9 weight, instead of also counting a jitter component.
+    assert_eq!(connection_score(100.0, 20.0, 40.0, None, &cfg), 83);
+    assert_eq!(connection_score(100.0, 20.0, 40.0, Some(5.0), &cfg), 85);
+    assert_eq!(connection_score(100.0, 20.0, 40.0, Some(50.0), &cfg), 76);
+}
+
+/// The plausibility guard accepts real speeds up to 10 Tbps and rejects anything beyond.
+#[test]
+fn plausibility_guard_boundary() {
+    assert!(is_plausible_bps(0.0));
+    assert!(is_plausible_bps(94_372_819.52));
+    assert!(is_plausible_bps(MAX_PLAUSIBLE_BPS));
+    assert!(!is_plausible_bps(MAX_PLAUSIBLE_BPS * 1.000_001));
+    assert!(!is_plausible_bps(f64::INFINITY));
+
+    // A 94 Mbps result wrongly treated as Mbps when it was already bps
+    let misscaled = SpeedTestResult {
+        download_bps: SpeedUnit::Mbps.to_bps(94_372_819.52),
+        ..complete_result()
+    };
+    assert!(!misscaled.is_plausible());
+    assert!(complete_result().is_plausible());
+}
+
+/// Each unit converts bits per second by its factor; megabytes are a factor of 8
+/// larger than megabits.
+#[test]
+fn unit_conversion_factors() {
+    use speedtest_statuspage::units::{bps_to, Unit};
+
+    let bps = 250_000_000.0;
+    assert_eq!(bps_to(bps, Unit::Bps), 250_000_000.0);
+    assert_eq!(bps_to(bps, Unit::Kbps), 250_000.0);
+    assert_eq!(bps_to(bps, Unit::Mbps), 250.0);
+    assert_eq!(bps_to(bps, Unit::Gbps), 0.25);
+    assert_eq!(bps_to(bps, Unit::MBps), 31.25);
+    assert_eq!(bps_to(bps, Unit::MBps), bps_to(bps, Unit::Mbps) / 8.0);
+    for unit in [Unit::Bps, Unit::Kbps, Unit::Mbps, Unit::Gbps, Unit::MBps] {
+        assert_eq!(bps_to(unit.to_bps(12.5), unit), 12.5, "{unit}");
+    }
+}
+
+/// Bit units parse case-insensitively; only a capital `B` means bytes.
+#[test]
+fn unit_names_parse() {
+    use speedtest_statuspage::units::Unit;
+
+    for (name, unit) in [
+        ("gbps", Unit::Gbps),
+        ("Gbps", Unit::Gbps),
+        ("mbps", Unit::Mbps),
+        ("Mbps", Unit::Mbps),
+        ("MBps", Unit::MBps),
+        ("MB/s", Unit::MBps),
+        ("kbps", Unit::Kbps),
+        (" bps ", Unit::Bps),
+    ] {
+        assert_eq!(name.parse::<Unit>(), Ok(unit), "{name:?}");
+    }
+    assert!("furlongs".parse::<Unit>().is_err());
+    assert_eq!(Unit::MBps.to_string(), "MBps");
+}
+
+/// Each grade covers bufferbloat up to and including its threshold.
+#[test]
+fn bufferbloat_grade_boundaries() {
+    let thresholds = BufferbloatThresholds { a: 5.0, b: 30.0, c: 60.0, d: 200.0 };
+    let cases = [
+        (0.0, BufferbloatGrade::A),
+        (5.0, BufferbloatGrade::A),
+        (5.1, BufferbloatGrade::B),
+        (30.0, BufferbloatGrade::B),
+        (30.1, BufferbloatGrade::C),
+        (60.0, BufferbloatGrade::C),
+        (60.1, BufferbloatGrade::D),
+        (200.0, BufferbloatGrade::D),
+        (200.1, BufferbloatGrade::F),
+    ];
+    for (ms, grade) in cases {
+        assert_eq!(bufferbloat_grade(ms, &thresholds), grade, "{ms} ms");
+    }
+}
+
+/// Thresholds parse from four ascending values and reject anything else.
+#[test]
+fn bufferbloat_thresholds_parse() {
+    assert_eq!("5, 30, 60, 200".parse(), Ok(BufferbloatThresholds { a: 5.0, b: 30.0, c: 60.0, d: 200.0 }));
+    for invalid in ["", "5,30,60", "5,30,60,200,400", "60,30,5,200", "5,30,sixty,200", "-1,30,60,200"] {
+        assert!(invalid.parse::<BufferbloatThresholds>().is_err(), "{invalid:?} should be rejected");
+    }
+}
+
+/// Without loaded latencies or an idle ping the bufferbloat stays unknown, and a
+/// loaded latency below the idle ping counts as none.
+#[test]
+fn bufferbloat_requires_loaded_latency() {
+    let thresholds = BufferbloatThresholds::default();
+    let mut result = SpeedTestResult { ping_ms: 10.0, ..Default::default() };
+    result.set_bufferbloat(&thresholds);
+    assert_eq!((result.bufferbloat_ms, result.bufferbloat_grade), (None, None));
+
+    result.upload_latency_ms = Some(8.0);
+    result.set_bufferbloat(&thresholds);
+    assert_eq!((result.bufferbloat_ms, result.bufferbloat_grade), (Some(0.0), Some(BufferbloatGrade::A)));
+
+    result.ping_ms = 0.0;
+    result.set_bufferbloat(&thresholds);
+    assert_eq!((result.bufferbloat_ms, result.bufferbloat_grade), (None, None));
+
+    let graded = SpeedTestResult { bufferbloat_grade: Some(BufferbloatGrade::F), ..Default::default() };
+    let json = serde_json::to_value(graded).unwrap();
+    assert_eq!(json["bufferbloat_grade"], "F");
+}
+
+/// Byte counts beyond the 32-bit range survive deserialization and round-tripping.
+#[test]
+fn large_byte_counts_are_preserved() {
+    let large: u64 = 6_000_000_000;
+    let mut response: serde_json::Value = serde_json::from_str(include_str!("fixtures/speedtest_cli.json")).unwrap();
+    response["bytes_received"] = large.into();
+    response["bytes_sent"] = (large * 2).into();
+    let parsed: SpeedTestResponse = serde_json::from_value(response).unwrap();
+    assert_eq!(parsed.bytes_received, large);
+
+    let result = SpeedTestResult::from(parsed);
+    assert_eq!((result.bytes_received, result.bytes_sent), (large, large * 2));
+    let round_tripped: SpeedTestResult = serde_json::from_str(&serde_json::to_string(&result).unwrap()).unwrap();
+    assert_eq!(round_tripped.bytes_received, large);
+}
+
+/// CSV rows have one field per header column, quoting fields containing separators.
+#[test]
+fn csv_row_matches_header_and_quotes_fields() {
+    let response: SpeedTestResponse = serde_json::from_str(include_str!("fixtures/speedtest_cli.json")).unwrap();
+    let result = SpeedTestResult::from(response);
+    let row = to_csv_row(&result);
+    assert_eq!(row.split(',').count(), CSV_HEADER.split(',').count());
+
+    let mut multiline = result;
+    multiline.server.name = "Line\nBreak".to_string();
+    assert!(to_csv_row(&multiline).contains(",\"Line\nBreak\","));
+}
+
+/// Jitter is carried through when the CLI reports it, and absent otherwise.
+#[test]
+fn jitter_parsed_when_present() {
+    let without: SpeedTestResponse = serde_json::from_str(include_str!("fixtures/speedtest_cli.json")).unwrap();
+    let with: SpeedTestResponse = serde_json::from_str(include_str!("fixtures/speedtest_cli_jitter.json")).unwrap();
+
+    let without = SpeedTestResult::from(without);
+    assert_eq!(without.jitter_ms, None);
+    assert!(!without.numeric_fields().contains_key("jitter_ms"));
+    assert!(to_csv_row(&without).contains(",12.345,,"));
+
+    let with = SpeedTestResult::from(with);
+    assert_eq!(with.jitter_ms, Some(1.872));
+    assert_eq!(with.numeric_fields()["jitter_ms"], 1.872);
+    assert!(to_csv_row(&with).contains(",12.345,1.872,"));
+    assert!(with.quality_score(&ScoreWeights::default()) > 0);
+}
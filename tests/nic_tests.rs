@@ -0,0 +1,49 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `nic` module, which reads the interface link speed.
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// A mocked sysfs reader feeds the link speed into the percentage calculation.
+#[test]
+fn nic_speed_from_mocked_reader_sets_percentage() {
+    let link_speed = nic::read_link_speed_mbps("eth0", |_| Ok("100\n".to_string())).unwrap();
+
+    let mut result = SpeedTestResult {
+        download_mbps: 94.5,
+        ..complete_result()
+    };
+    result.set_nic_speed(link_speed);
+
+    assert_eq!(result.nic_speed_mbps, Some(100.0));
+    assert_eq!(result.percent_of_nic, Some(94.5));
+}
+
+/// Unavailable or link-down sysfs values fall back to `None`.
+#[test]
+fn nic_speed_detection_falls_back_gracefully() {
+    let missing = nic::read_link_speed_mbps("eth0", |_| {
+        Err(std::io::Error::new(std::io::ErrorKind::NotFound, "no sysfs"))
+    });
+    assert_eq!(missing, None);
+
+    let link_down = nic::read_link_speed_mbps("eth0", |_| Ok("-1\n".to_string()));
+    assert_eq!(link_down, None);
+}
@@ -0,0 +1,297 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for outbound notifications, using a local mock HTTP server.
+//!
+//! Notification targets are configured through the environment, so these tests
+//! live in their own binary and run serially.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use common::{MockServer, RecordingMockRunner};
+use serial_test::serial;
+use speedtest_statuspage::notify::{AlertEvent, CompositeNotifier, LogNotifier, Notifier};
+use speedtest_statuspage::*;
+
+/// Notifier recording every event it receives, optionally failing each delivery.
+struct RecordingNotifier {
+    events: Arc<Mutex<Vec<AlertEvent>>>,
+    error: Option<String>,
+}
+
+impl RecordingNotifier {
+    fn new(error: Option<&str>) -> (Self, Arc<Mutex<Vec<AlertEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        (RecordingNotifier { events: events.clone(), error: error.map(String::from) }, events)
+    }
+}
+
+#[async_trait]
+impl Notifier for RecordingNotifier {
+    async fn notify(&self, event: &AlertEvent) -> Result<(), String> {
+        self.events.lock().unwrap().push(event.clone());
+        self.error.clone().map_or(Ok(()), Err)
+    }
+}
+
+/// Every successful run pings the heartbeat exactly once; failed runs never do.
+#[actix_web::test]
+#[serial]
+async fn heartbeat_pinged_once_per_successful_run_only() {
+    let server = MockServer::start(200).await;
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("HEARTBEAT_URL", format!("{}/ping/abc", server.url)) };
+
+    let cfg = RunnerConfig::default();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::failing("offline"), &cfg).await.unwrap_err();
+    notify::flush().await;
+    assert!(server.received().is_empty());
+
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+
+    notify::flush().await;
+    let received = server.received();
+    assert_eq!(received.len(), 2);
+    assert!(received.iter().all(|r| r.method == "GET" && r.path == "/ping/abc"));
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("HEARTBEAT_URL") };
+    clear_last_result_for_test();
+    server.stop().await;
+}
+
+/// An unreachable heartbeat endpoint does not prevent caching.
+#[actix_web::test]
+#[serial]
+async fn heartbeat_failure_is_ignored() {
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("HEARTBEAT_URL", "http://127.0.0.1:9/unreachable") };
+
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &RunnerConfig::default()).await.unwrap();
+    assert!(get_last_result().is_some());
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("HEARTBEAT_URL") };
+    clear_last_result_for_test();
+}
+
+/// A download below the threshold POSTs the full result to the alert webhook; one
+/// above it does not.
+#[actix_web::test]
+#[serial]
+async fn slow_download_posts_result_to_webhook() {
+    let server = MockServer::start(204).await;
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe {
+        std::env::set_var("ALERT_WEBHOOK_URL", format!("{}/alerts", server.url));
+        std::env::set_var("ALERT_MIN_DOWNLOAD_MBPS", "50");
+    }
+
+    let cfg = RunnerConfig::default();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    notify::flush().await;
+    assert!(server.received().is_empty(), "94 Mbps is above the threshold");
+
+    // SAFETY: as above.
+    unsafe { std::env::set_var("ALERT_MIN_DOWNLOAD_MBPS", "100") };
+    let cached = run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+
+    notify::flush().await;
+    let received = server.received();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].method, "POST");
+    assert_eq!(received[0].path, "/alerts");
+    let posted: SpeedTestResult = serde_json::from_slice(&received[0].body).unwrap();
+    assert_eq!(posted, cached);
+
+    // SAFETY: as above.
+    unsafe {
+        std::env::remove_var("ALERT_WEBHOOK_URL");
+        std::env::remove_var("ALERT_MIN_DOWNLOAD_MBPS");
+    }
+    clear_last_result_for_test();
+    server.stop().await;
+}
+
+/// Every successful result is POSTed to `WEBHOOK_URL`; failed runs are not.
+#[actix_web::test]
+#[serial]
+async fn webhook_receives_every_successful_result() {
+    let server = MockServer::start(200).await;
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("WEBHOOK_URL", format!("{}/results", server.url)) };
+
+    let cfg = RunnerConfig::default();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::failing("offline"), &cfg).await.unwrap_err();
+    let cached = run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+
+    notify::flush().await;
+    let received = server.received();
+    assert_eq!(received.len(), 1);
+    assert_eq!((received[0].method.as_str(), received[0].path.as_str()), ("POST", "/results"));
+    assert_eq!(serde_json::from_slice::<SpeedTestResult>(&received[0].body).unwrap(), cached);
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("WEBHOOK_URL") };
+    clear_last_result_for_test();
+    server.stop().await;
+}
+
+/// Registered notifiers receive the events of every run without any configuration.
+#[tokio::test]
+#[serial]
+async fn registered_notifier_receives_run_events() {
+    let (recorder, events) = RecordingNotifier::new(None);
+    notify::register_notifier(recorder);
+
+    let cfg = RunnerConfig::default();
+    let cached = run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::failing("offline"), &cfg).await.unwrap_err();
+    notify::clear_registered_notifiers();
+
+    notify::flush().await;
+    assert_eq!(
+        *events.lock().unwrap(),
+        [AlertEvent::RunSucceeded(Box::new(cached)), AlertEvent::RunFailed("offline".to_string())]
+    );
+    clear_last_result_for_test();
+}
+
+/// A slow channel neither delays the run which raised the event nor loses it.
+#[tokio::test]
+#[serial]
+async fn slow_notifier_does_not_delay_run() {
+    struct SlowNotifier(Arc<Mutex<usize>>);
+
+    #[async_trait]
+    impl Notifier for SlowNotifier {
+        async fn notify(&self, _event: &AlertEvent) -> Result<(), String> {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    let delivered = Arc::new(Mutex::new(0));
+    notify::register_notifier(SlowNotifier(delivered.clone()));
+
+    let started = Instant::now();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &RunnerConfig::default()).await.unwrap();
+    let elapsed = started.elapsed();
+    notify::clear_registered_notifiers();
+
+    assert!(elapsed < Duration::from_secs(1), "run waited {elapsed:?} for the notifier");
+    assert_eq!(*delivered.lock().unwrap(), 0);
+    notify::flush().await;
+    assert_eq!(*delivered.lock().unwrap(), 1);
+    clear_last_result_for_test();
+}
+
+/// With `ISOLATE_SCHEDULER=true`, a notification still in flight when the scheduler
+/// returns is delivered before its dedicated runtime shuts down.
+#[tokio::test]
+#[serial]
+async fn isolated_scheduler_delivers_pending_notifications() {
+    struct SlowNotifier(Arc<Mutex<usize>>);
+
+    #[async_trait]
+    impl Notifier for SlowNotifier {
+        async fn notify(&self, _event: &AlertEvent) -> Result<(), String> {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            *self.0.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    let delivered = Arc::new(Mutex::new(0));
+    notify::register_notifier(SlowNotifier(delivered.clone()));
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("ISOLATE_SCHEDULER", "true") };
+
+    let scheduler = start_scheduler(async {
+        let runner = RecordingMockRunner::succeeding();
+        run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await.unwrap();
+    })
+    .unwrap();
+    scheduler.await.unwrap();
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("ISOLATE_SCHEDULER") };
+    notify::clear_registered_notifiers();
+    assert_eq!(*delivered.lock().unwrap(), 1);
+    clear_last_result_for_test();
+}
+
+/// The log notifier accepts every event.
+#[tokio::test]
+async fn log_notifier_never_fails() {
+    assert_eq!(LogNotifier.notify(&AlertEvent::RunFailed("offline".to_string())).await, Ok(()));
+    assert_eq!(LogNotifier.notify(&AlertEvent::RunSucceeded(Box::default())).await, Ok(()));
+}
+
+/// Without configuration or registered notifiers only the log notifier is built.
+#[test]
+#[serial]
+fn only_log_notifier_configured_by_default() {
+    // SAFETY: as above.
+    unsafe {
+        std::env::remove_var("WEBHOOK_URL");
+        std::env::remove_var("HEARTBEAT_URL");
+        std::env::remove_var("ALERT_WEBHOOK_URL");
+    }
+    notify::clear_registered_notifiers();
+    assert_eq!(notify::configured_notifiers().len(), 1);
+
+    // SAFETY: as above.
+    unsafe { std::env::set_var("WEBHOOK_URL", "http://127.0.0.1:9/hook") };
+    assert_eq!(notify::configured_notifiers().len(), 2);
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("WEBHOOK_URL") };
+}
+
+/// The composite delivers each event to every channel.
+#[tokio::test]
+async fn composite_fans_out_to_every_notifier() {
+    let (first, first_events) = RecordingNotifier::new(None);
+    let (second, second_events) = RecordingNotifier::new(None);
+    let composite = CompositeNotifier::new(vec![Box::new(first), Box::new(second)]);
+
+    let event = AlertEvent::RunFailed("offline".to_string());
+    assert_eq!(composite.notify(&event).await, Ok(()));
+
+    assert_eq!(*first_events.lock().unwrap(), *second_events.lock().unwrap());
+    assert_eq!(*first_events.lock().unwrap(), [event]);
+}
+
+/// Failures from several channels are aggregated without stopping delivery to the rest.
+#[tokio::test]
+async fn composite_aggregates_errors() {
+    let (failing, _) = RecordingNotifier::new(Some("webhook returned 500"));
+    let (healthy, healthy_events) = RecordingNotifier::new(None);
+    let (also_failing, _) = RecordingNotifier::new(Some("smtp refused"));
+    let composite = CompositeNotifier::new(vec![Box::new(failing), Box::new(healthy), Box::new(also_failing)]);
+
+    let event = AlertEvent::RunSucceeded(Box::default());
+    assert_eq!(
+        composite.notify(&event).await,
+        Err("webhook returned 500; smtp refused".to_string())
+    );
+    assert_eq!(healthy_events.lock().unwrap().len(), 1);
+}
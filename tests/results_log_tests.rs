@@ -0,0 +1,178 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `results_log` module, the JSON Lines log of every result.
+//!
+//! Restoring the history reads `RESULTS_LOG` and `HISTORY_SIZE` from the environment,
+//! so that test runs serially.
+
+mod common;
+
+use std::io::Read;
+use chrono::NaiveDate;
+use common::complete_result;
+use serial_test::serial;
+use speedtest_statuspage::*;
+
+/// Each appended result is one parseable JSON line, after any existing lines.
+#[test]
+fn results_log_appends_one_line_per_result() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("speed.jsonl");
+    std::fs::write(&path, "{\"earlier\":true}\n").unwrap();
+    let results: Vec<_> = [10.0, 20.0, 30.0]
+        .map(|download_mbps| SpeedTestResult { download_mbps, ..complete_result() })
+        .into();
+
+    for result in &results {
+        results_log::append_result(&path, result).unwrap();
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.ends_with('\n'));
+    let lines: Vec<_> = contents.lines().collect();
+    assert_eq!(lines.len(), 4);
+    let appended: Vec<SpeedTestResult> = lines[1..].iter().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(appended, results);
+}
+
+/// An unwritable log is reported as an error instead of panicking.
+#[test]
+fn results_log_reports_unwritable_file() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(results_log::append_result(dir.path(), &complete_result()).is_err());
+}
+
+/// Only the last `n` lines of a log spanning many read chunks are returned, in order.
+#[test]
+fn read_last_lines_returns_tail_of_large_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("speed.jsonl");
+    let contents: String = (0..50_000).map(|i| format!("line {}\n", i)).collect();
+    std::fs::write(&path, contents).unwrap();
+
+    let lines = results_log::read_last_lines(&path, 100).unwrap();
+    let expected: Vec<String> = (49_900..50_000).map(|i| format!("line {}", i)).collect();
+    assert_eq!(lines, expected);
+}
+
+/// A file with fewer lines than asked for is returned whole, with or without a
+/// trailing newline, and an empty file gives no lines.
+#[test]
+fn read_last_lines_handles_short_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("speed.jsonl");
+
+    std::fs::write(&path, "one\ntwo\n").unwrap();
+    assert_eq!(results_log::read_last_lines(&path, 10).unwrap(), ["one", "two"]);
+    std::fs::write(&path, "one\ntwo").unwrap();
+    assert_eq!(results_log::read_last_lines(&path, 10).unwrap(), ["one", "two"]);
+    assert_eq!(results_log::read_last_lines(&path, 1).unwrap(), ["two"]);
+    std::fs::write(&path, "").unwrap();
+    assert!(results_log::read_last_lines(&path, 10).unwrap().is_empty());
+    assert!(results_log::read_last_lines(&dir.path().join("missing.jsonl"), 1).is_err());
+}
+
+/// A line longer than a read chunk, and so cut by every chunk boundary, comes back
+/// whole.
+#[test]
+fn read_last_lines_joins_lines_across_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("speed.jsonl");
+    let long = "x".repeat(200_000);
+    std::fs::write(&path, format!("first\n{}\nlast\n", long)).unwrap();
+
+    assert_eq!(results_log::read_last_lines(&path, 2).unwrap(), [long.as_str(), "last"]);
+    assert_eq!(results_log::read_last_lines(&path, 3).unwrap()[0], "first");
+}
+
+/// At startup only the last `HISTORY_SIZE` logged results are loaded into the
+/// history, older schema versions are migrated and a torn final line is skipped.
+#[test]
+#[serial]
+fn restore_history_loads_last_results_from_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("speed.jsonl");
+    for i in 0..1_000 {
+        let result = SpeedTestResult { download_mbps: f64::from(i), ..complete_result() };
+        results_log::append_result(&path, &result).unwrap();
+    }
+    let mut old = serde_json::to_value(SpeedTestResult { download_mbps: 1000.0, ..complete_result() }).unwrap();
+    old["schema_version"] = 1.into();
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    std::io::Write::write_all(&mut file, format!("{}\n{{\"download_", old).as_bytes()).unwrap();
+    clear_history_for_test();
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe {
+        std::env::set_var("RESULTS_LOG", &path);
+        std::env::set_var("HISTORY_SIZE", "5");
+    }
+    // The torn line counts towards the five read, so four results are restored.
+    assert_eq!(restore_history(), 4);
+    let history = get_history();
+    let downloads: Vec<f64> = history.iter().map(|result| result.download_mbps).collect();
+    assert_eq!(downloads, [997.0, 998.0, 999.0, 1000.0]);
+    assert_eq!(history[3].schema_version, SCHEMA_VERSION);
+
+    clear_history_for_test();
+    // SAFETY: as above.
+    unsafe { std::env::set_var("RESULTS_LOG", dir.path().join("missing.jsonl")) };
+    assert_eq!(restore_history(), 0);
+    assert!(get_history().is_empty());
+
+    // SAFETY: as above.
+    unsafe {
+        std::env::remove_var("RESULTS_LOG");
+        std::env::remove_var("HISTORY_SIZE");
+    }
+}
+
+/// Reads every gzip member of the archive at `path`.
+fn read_archive(path: &std::path::Path) -> String {
+    let mut contents = String::new();
+    flate2::read::MultiGzDecoder::new(std::fs::File::open(path).unwrap()).read_to_string(&mut contents).unwrap();
+    contents
+}
+
+/// Crossing midnight makes the previous day due; archiving it moves the log into
+/// `history/<day>.ndjson.gz`, leaves an empty log, and a second roll of the same day
+/// appends to the archive.
+#[test]
+fn archive_rolls_log_on_date_change() {
+    let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+    assert_eq!(results_log::archive_due(day(15), day(14)), None);
+    assert_eq!(results_log::archive_due(day(16), day(14)), Some(day(15)));
+    assert_eq!(results_log::archive_due(day(20), day(14)), Some(day(19)));
+    assert_eq!(results_log::archive_due(day(16), day(15)), None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("speed.jsonl");
+    results_log::append_result(&log, &complete_result()).unwrap();
+    let logged = std::fs::read_to_string(&log).unwrap();
+    assert_eq!(results_log::last_archived_day(&log), None);
+
+    let archive = results_log::archive_log(&log, day(15)).unwrap();
+    assert_eq!(archive, dir.path().join("history/2026-10-15.ndjson.gz"));
+    assert_eq!(read_archive(&archive), logged);
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "");
+    assert_eq!(results_log::last_archived_day(&log), Some(day(15)));
+
+    results_log::append_result(&log, &complete_result()).unwrap();
+    results_log::archive_log(&log, day(15)).unwrap();
+    assert_eq!(read_archive(&archive), logged.repeat(2));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+}
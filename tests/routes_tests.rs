@@ -0,0 +1,48 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `routes` module, which splits the endpoints between listeners.
+
+use actix_web::{http, test, App};
+use speedtest_statuspage::routes::{self, Listener};
+
+/// Status of a GET to each of `/speed`, `/metrics` and `/health` on an app built
+/// for `listener`.
+async fn statuses(listener: Listener) -> [http::StatusCode; 3] {
+    let app = test::init_service(App::new().configure(routes::configure(listener))).await;
+    let mut statuses = [http::StatusCode::OK; 3];
+    for (status, uri) in statuses.iter_mut().zip(["/speed", "/metrics", "/health"]) {
+        *status = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await.status();
+    }
+    statuses
+}
+
+/// Without a metrics port every endpoint is on the main listener; with one,
+/// `/metrics` and `/health` are reachable only on the metrics listener.
+#[actix_web::test]
+async fn metrics_routes_move_to_metrics_listener() {
+    use http::StatusCode as S;
+
+    let [speed, metrics, health] = statuses(Listener::Main { separate_metrics: false }).await;
+    assert_ne!(speed, S::NOT_FOUND);
+    assert_eq!((metrics, health), (S::OK, S::OK));
+
+    let [speed, metrics, health] = statuses(Listener::Main { separate_metrics: true }).await;
+    assert_ne!(speed, S::NOT_FOUND);
+    assert_eq!((metrics, health), (S::NOT_FOUND, S::NOT_FOUND));
+
+    assert_eq!(statuses(Listener::Metrics).await, [S::NOT_FOUND, S::OK, S::OK]);
+}
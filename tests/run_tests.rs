@@ -0,0 +1,172 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for speedtests triggered on demand, with `POST /run` or `/compare/backends`.
+//!
+//! Runs share the process-wide cache and run lock, so these tests run serially.
+
+mod common;
+
+use actix_web::{http, test, web, App};
+use common::RecordingMockRunner;
+use serial_test::serial;
+use speedtest_statuspage::run::{run_endpoint, ManualRun};
+use speedtest_statuspage::*;
+
+/// Registers `run_endpoint` with `runner` and an optional token.
+fn manual_run(runner: RecordingMockRunner, token: Option<&str>) -> web::Data<ManualRun> {
+    web::Data::new(ManualRun {
+        runner: Box::new(runner),
+        cfg: RunnerConfig::default(),
+        token: token.map(str::to_string),
+    })
+}
+
+/// A successful run is cached as a manual result and returned.
+#[actix_web::test]
+#[serial]
+async fn run_returns_and_caches_manual_result() {
+    clear_last_result_for_test();
+    let manual = manual_run(RecordingMockRunner::succeeding(), None);
+    let app = test::init_service(App::new().app_data(manual).service(run_endpoint)).await;
+
+    let req = test::TestRequest::post().uri("/run").to_request();
+    let body: SpeedTestResult = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(body.origin, Origin::Manual);
+    assert_eq!(get_last_result(), Some(body));
+    clear_last_result_for_test();
+}
+
+/// A failed run responds 500 with the reason and leaves the cache untouched.
+#[actix_web::test]
+#[serial]
+async fn run_failure_returns_error_message() {
+    clear_last_result_for_test();
+    let manual = manual_run(RecordingMockRunner::failing("network unreachable"), None);
+    let app = test::init_service(App::new().app_data(manual).service(run_endpoint)).await;
+
+    let resp = test::call_service(&app, test::TestRequest::post().uri("/run").to_request()).await;
+
+    assert_eq!(resp.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(test::read_body(resp).await, "network unreachable");
+    assert!(get_last_result().is_none());
+}
+
+/// A request made while another run holds the lock is turned away without running.
+#[actix_web::test]
+#[serial]
+async fn run_rejects_overlapping_runs() {
+    clear_last_result_for_test();
+    let manual = manual_run(RecordingMockRunner::succeeding(), None);
+    let app = test::init_service(App::new().app_data(manual).service(run_endpoint)).await;
+
+    let running = RUN_LOCK.lock().await;
+    let resp = test::call_service(&app, test::TestRequest::post().uri("/run").to_request()).await;
+    drop(running);
+
+    assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+    assert!(get_last_result().is_none());
+}
+
+/// With a token configured, only requests presenting it as a bearer token may run.
+#[actix_web::test]
+#[serial]
+async fn run_requires_token_when_configured() {
+    clear_last_result_for_test();
+    let manual = manual_run(RecordingMockRunner::succeeding(), Some("s3cret"));
+    let app = test::init_service(App::new().app_data(manual).service(run_endpoint)).await;
+
+    for (authorization, expected) in [
+        (None, http::StatusCode::UNAUTHORIZED),
+        (Some("Bearer wrong"), http::StatusCode::UNAUTHORIZED),
+        (Some("Bearer s3cret"), http::StatusCode::OK),
+    ] {
+        let mut req = test::TestRequest::post().uri("/run");
+        if let Some(value) = authorization {
+            req = req.insert_header((http::header::AUTHORIZATION, value));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), expected, "Authorization: {authorization:?}");
+    }
+    clear_last_result_for_test();
+}
+
+/// With `API_TOKEN` and `RUN_TOKEN` set to different values, `/run` accepts only
+/// its own token and every other route only the API token.
+#[actix_web::test]
+#[serial]
+async fn run_token_differs_from_api_token() {
+    use actix_web::middleware::from_fn;
+    use speedtest_statuspage::auth::{require_api_token, ApiToken};
+
+    clear_last_result_for_test();
+    let manual = manual_run(RecordingMockRunner::succeeding(), Some("run-token"));
+    let app = test::init_service(
+        App::new()
+            .app_data(manual)
+            .app_data(web::Data::new(ApiToken::new("api-token").exempt("/run")))
+            .wrap(from_fn(require_api_token))
+            .service(run_endpoint)
+            .service(health),
+    )
+    .await;
+
+    for (request, authorization, expected) in [
+        (test::TestRequest::post().uri("/run"), "Bearer run-token", http::StatusCode::OK),
+        (test::TestRequest::post().uri("/run"), "Bearer api-token", http::StatusCode::UNAUTHORIZED),
+        (test::TestRequest::get().uri("/health"), "Bearer api-token", http::StatusCode::OK),
+        (test::TestRequest::get().uri("/health"), "Bearer run-token", http::StatusCode::UNAUTHORIZED),
+    ] {
+        let req = request.insert_header((http::header::AUTHORIZATION, authorization)).to_request();
+        let path = req.path().to_string();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected, "{path} with {authorization}");
+    }
+    clear_last_result_for_test();
+}
+
+/// `/compare/backends` is hidden without `COMPARE_TOKEN`, requires it otherwise,
+/// and never runs alongside another speedtest.
+#[actix_web::test]
+#[serial]
+async fn compare_backends_requires_token_and_run_lock() {
+    use speedtest_statuspage::compare::compare_backends_endpoint;
+
+    let app = test::init_service(App::new().service(compare_backends_endpoint)).await;
+    let request = |authorization: &str| {
+        test::TestRequest::get()
+            .uri("/compare/backends")
+            .insert_header((http::header::AUTHORIZATION, authorization))
+            .to_request()
+    };
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::remove_var("COMPARE_TOKEN") };
+    assert_eq!(test::call_service(&app, request("Bearer s3cret")).await.status(), http::StatusCode::NOT_FOUND);
+
+    // SAFETY: as above.
+    unsafe { std::env::set_var("COMPARE_TOKEN", "s3cret") };
+    assert_eq!(test::call_service(&app, request("Bearer wrong")).await.status(), http::StatusCode::UNAUTHORIZED);
+
+    let running = RUN_LOCK.lock().await;
+    let resp = test::call_service(&app, request("Bearer s3cret")).await;
+    drop(running);
+    assert_eq!(resp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("COMPARE_TOKEN") };
+}
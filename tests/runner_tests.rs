@@ -0,0 +1,1117 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `SpeedtestRunner` abstraction and the run-and-cache pipeline.
+//!
+//! Runners are mocked so no real `speedtest-cli` binary is needed. Tests that
+//! touch the global cache are annotated with `serial_test::serial`.
+
+mod common;
+
+use async_trait::async_trait;
+use common::{RecordingMockRunner, SPEEDTEST_CLI_JSON};
+use serial_test::serial;
+use speedtest_statuspage::*;
+
+/// The runner receives exactly the configuration passed to the pipeline.
+#[tokio::test]
+#[serial]
+async fn runner_receives_expected_config() {
+    clear_last_result_for_test();
+
+    let cfg = RunnerConfig {
+        server_ids: vec!["12345".to_string()],
+        timeout_secs: Some(30),
+        proxy: Some("http://proxy.local:3128".to_string()),
+        extra_args: vec!["--single".to_string()],
+        ..Default::default()
+    };
+    let runner = RecordingMockRunner::new(Ok(SPEEDTEST_CLI_JSON.to_string()));
+
+    run_speedtest_and_cache_with_runner(&runner, &cfg).await.unwrap();
+
+    assert_eq!(*runner.seen.lock().unwrap(), vec![cfg]);
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+    assert_eq!(get_last_result().unwrap().origin, Origin::Scheduled);
+
+    clear_last_result_for_test();
+}
+
+/// A failing runner leaves the cache untouched.
+#[tokio::test]
+#[serial]
+async fn runner_failure_does_not_populate_cache() {
+    clear_last_result_for_test();
+
+    let runner = RecordingMockRunner::new(Err(SpeedtestError::CommandFailed("boom".to_string())));
+    let outcome = run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await;
+
+    assert_eq!(outcome, Err(SpeedtestError::CommandFailed("boom".to_string())));
+
+    assert_eq!(runner.seen.lock().unwrap().len(), 1);
+    assert!(get_last_result().is_none());
+}
+
+/// Output that does not parse is reported as a parse failure and leaves the cache alone.
+#[tokio::test]
+#[serial]
+async fn unparseable_output_reports_parse_failure() {
+    clear_last_result_for_test();
+    let runner = RecordingMockRunner::new(Ok("not json".to_string()));
+
+    let outcome = run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await;
+
+    assert!(matches!(outcome, Err(SpeedtestError::ParseFailed(_))), "got {outcome:?}");
+    assert!(get_last_result().is_none());
+}
+
+/// A successful run returns the result it cached.
+#[tokio::test]
+#[serial]
+async fn successful_run_returns_cached_result() {
+    clear_last_result_for_test();
+
+    let result = run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &RunnerConfig::default())
+        .await
+        .unwrap();
+
+    assert_eq!(Some(result), get_last_result());
+    clear_last_result_for_test();
+}
+
+/// A result rejected by the registered validator is not cached, and clearing the
+/// validator caches results again.
+#[tokio::test]
+#[serial]
+async fn validator_rejection_discards_result() {
+    clear_last_result_for_test();
+    set_result_validator(|result| match result.server.id.as_str() {
+        "12345" => Err("blacklisted server".to_string()),
+        _ => Ok(()),
+    });
+    let runner = RecordingMockRunner::succeeding();
+
+    assert_eq!(
+        run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await,
+        Err(SpeedtestError::Rejected("blacklisted server".to_string()))
+    );
+    assert!(get_last_result().is_none());
+
+    clear_result_validator();
+    assert!(run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await.is_ok());
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+    clear_last_result_for_test();
+}
+
+/// Environment values are parsed into the typed configuration.
+#[test]
+fn runner_config_from_lookup_parses_all_fields() {
+    let cfg = RunnerConfig::from_lookup(|key| {
+        match key {
+            "SPEEDTEST_SERVER_IDS" => Some("111,222".to_string()),
+            "SPEEDTEST_CLI_TIMEOUT_SECS" => Some("15".to_string()),
+            "SPEEDTEST_PROXY" => Some("socks5://10.0.0.1:1080".to_string()),
+            "SPEEDTEST_SECURE" => Some("1".to_string()),
+            "SPEEDTEST_NO_DOWNLOAD" => Some("false".to_string()),
+            "SPEEDTEST_NO_UPLOAD" => Some("true".to_string()),
+            "SPEEDTEST_EXTRA_ARGS" => Some("--single  --bytes".to_string()),
+            "CLI_STALL_SECONDS" => Some("90".to_string()),
+            "SPEEDTEST_TIMEOUT_SECS" => Some("120".to_string()),
+            "SPEEDTEST_RETRIES" => Some("2".to_string()),
+            "SPEEDTEST_RETRY_BASE_MS" => Some("250".to_string()),
+            "RETRY_JITTER" => Some("yes".to_string()),
+            "RUN_BUDGET_SECONDS" => Some("300".to_string()),
+            _ => None,
+        }
+    });
+
+    assert_eq!(
+        cfg,
+        RunnerConfig {
+            server_ids: vec!["111".to_string(), "222".to_string()],
+            timeout_secs: Some(15),
+            proxy: Some("socks5://10.0.0.1:1080".to_string()),
+            secure: true,
+            no_download: false,
+            no_upload: true,
+            extra_args: vec!["--single".to_string(), "--bytes".to_string()],
+            stall_timeout: Some(std::time::Duration::from_secs(90)),
+            run_timeout: Some(std::time::Duration::from_secs(120)),
+            retries: 2,
+            retry_base: std::time::Duration::from_millis(250),
+            retry_jitter: true,
+            run_budget: Some(std::time::Duration::from_secs(300)),
+        }
+    );
+}
+
+/// The run timeout defaults to 90 seconds and is disabled by zero.
+#[test]
+fn runner_config_run_timeout_defaults_and_disables() {
+    let default = RunnerConfig::from_lookup(|_| None);
+    assert_eq!(default.run_timeout, Some(std::time::Duration::from_secs(90)));
+
+    let disabled = RunnerConfig::from_lookup(|key| (key == "SPEEDTEST_TIMEOUT_SECS").then(|| "0".to_string()));
+    assert_eq!(disabled.run_timeout, None);
+}
+
+/// The CLI argument list reflects every configured option.
+#[test]
+fn runner_config_builds_cli_args() {
+    let cfg = RunnerConfig {
+        server_ids: vec!["111".to_string(), "222".to_string()],
+        timeout_secs: Some(15),
+        secure: true,
+        no_download: true,
+        extra_args: vec!["--single".to_string()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        cfg.build_args(),
+        vec![
+            "--json", "--server", "111", "--server", "222", "--timeout", "15",
+            "--secure", "--no-download", "--single",
+        ]
+    );
+}
+
+/// Sample `fast --upload --json` output.
+const FAST_CLI_JSON: &str = include_str!("fixtures/fast_cli.json");
+
+/// The fast-cli output is converted into a result with defaulted metadata.
+#[test]
+fn fast_cli_output_converts_to_result() {
+    let result = FastComRunner.parse_output(FAST_CLI_JSON).unwrap();
+
+    assert_eq!(result.download_bps, 450_000_000.0);
+    assert_eq!(result.upload_bps, 250_000_000.0);
+    assert_eq!(result.download_mbps, 450.0);
+    assert_eq!(result.upload_mbps, 250.0);
+    assert_eq!(result.ping_ms, 7.0);
+    assert_eq!(result.download_latency_ms, Some(15.0));
+    assert_eq!(result.upload_latency_ms, None);
+    assert_eq!(result.bytes_received, 230_000_000);
+    assert_eq!(result.bytes_sent, 150_000_000);
+    assert_eq!(result.client.ip, "192.0.2.1");
+    assert_eq!(result.client.isp, "");
+    assert_eq!(result.server.name, "");
+    assert!(result.share.is_none());
+    assert!(!result.timestamp.is_empty());
+}
+
+/// Sample `speedtest --format=json` output from the official Ookla CLI.
+const OOKLA_CLI_JSON: &str = include_str!("fixtures/ookla_cli.json");
+
+/// The Ookla output is converted into a result, with bandwidth scaled from bytes
+/// to bits per second.
+#[test]
+fn ookla_output_converts_to_result() {
+    let result = ookla::OoklaSpeedtestRunner.parse_output(OOKLA_CLI_JSON).unwrap();
+
+    assert_eq!(result.download_bps, 94_372_816.0);
+    assert_eq!(result.upload_bps, 23_068_672.0);
+    assert_eq!(result.download_mbps, 94.372816);
+    assert_eq!(result.ping_ms, 8.123);
+    assert_eq!(result.jitter_ms, Some(0.541));
+    assert_eq!(result.packet_loss, Some(0.0));
+    assert_eq!(result.download_latency_ms, Some(24.517));
+    assert_eq!(result.upload_latency_ms, Some(45.104));
+    assert_eq!(result.bytes_received, 118_456_320);
+    assert_eq!(result.bytes_sent, 23_068_672);
+    assert_eq!(result.client.ip, "192.0.2.1");
+    assert_eq!(result.client.isp, "Example ISP");
+    assert_eq!(result.server.id, "12345");
+    assert_eq!(result.server.name, "London");
+    assert_eq!(result.server.sponsor, "Example Networks");
+    assert_eq!(result.timestamp, "2025-08-07T12:34:56Z");
+    assert!(result.share.as_ref().unwrap().as_str().unwrap().starts_with("https://www.speedtest.net/result/"));
+    assert!(result.is_plausible());
+}
+
+/// Packet loss is taken from the Ookla output when reported, and left unset when
+/// the Ookla server did not measure it or the backend is `speedtest-cli`.
+#[test]
+fn packet_loss_parsed_per_backend() {
+    let mut ookla: serde_json::Value = serde_json::from_str(OOKLA_CLI_JSON).unwrap();
+    ookla["packetLoss"] = 1.25.into();
+    let result = ookla::OoklaSpeedtestRunner.parse_output(&ookla.to_string()).unwrap();
+    assert_eq!(result.packet_loss, Some(1.25));
+    assert_eq!(serde_json::to_value(&result).unwrap()["packet_loss"], 1.25);
+
+    ookla.as_object_mut().unwrap().remove("packetLoss");
+    let result = ookla::OoklaSpeedtestRunner.parse_output(&ookla.to_string()).unwrap();
+    assert_eq!(result.packet_loss, None);
+
+    let result = RealSpeedtestRunner.parse_output(SPEEDTEST_CLI_JSON).unwrap();
+    assert_eq!(result.packet_loss, None);
+    assert!(serde_json::to_value(&result).unwrap()["packet_loss"].is_null());
+}
+
+/// Backends are selected by name, with unknown names rejected.
+#[test]
+fn backend_parses_from_name() {
+    assert_eq!("fast".parse::<Backend>(), Ok(Backend::Fast));
+    assert_eq!("ookla".parse::<Backend>(), Ok(Backend::Ookla));
+    assert_eq!("Legacy".parse::<Backend>(), Ok(Backend::Legacy));
+    assert!("carrier-pigeon".parse::<Backend>().is_err());
+}
+
+/// Comparing backends reports each result or error by name without caching.
+#[tokio::test]
+#[serial]
+async fn compare_backends_reports_success_and_failure() {
+    use std::time::Duration;
+    use speedtest_statuspage::compare::{compare_backends, BackendOutcome};
+
+    clear_last_result_for_test();
+
+    let runners: Vec<(String, Box<dyn SpeedtestRunner>)> = vec![
+        ("good".to_string(), Box::new(RecordingMockRunner::new(Ok(SPEEDTEST_CLI_JSON.to_string())))),
+        (
+            "bad".to_string(),
+            Box::new(RecordingMockRunner::new(Err(SpeedtestError::CommandFailed("no route".to_string())))),
+        ),
+    ];
+
+    let report = compare_backends(&runners, &RunnerConfig::default(), Duration::from_secs(5)).await;
+
+    assert_eq!(report.len(), 2);
+    match &report["good"] {
+        BackendOutcome::Result(result) => assert_eq!(result.server.id, "12345"),
+        other => panic!("expected a result, got {:?}", other),
+    }
+    match &report["bad"] {
+        BackendOutcome::Error(e) => assert_eq!(e, "no route"),
+        other => panic!("expected an error, got {:?}", other),
+    }
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["bad"]["error"], "no route");
+    assert_eq!(json["good"]["result"]["server"]["id"], "12345");
+
+    assert!(get_last_result().is_none());
+}
+
+/// The first failure is logged, later ones only at every `log_every`-th occurrence.
+#[test]
+fn failure_log_decision_samples_repeated_failures() {
+    let decisions: Vec<FailureLog> = (1..=6).map(|n| failure_log_decision(n, 3)).collect();
+    assert_eq!(
+        decisions,
+        vec![
+            FailureLog::Full,
+            FailureLog::Suppressed,
+            FailureLog::Summary,
+            FailureLog::Suppressed,
+            FailureLog::Suppressed,
+            FailureLog::Summary,
+        ]
+    );
+}
+
+/// A `log_every` of zero or one disables suppression entirely.
+#[test]
+fn failure_log_decision_without_sampling_logs_everything() {
+    for n in 1..5 {
+        assert_eq!(failure_log_decision(n, 0), FailureLog::Full);
+        assert_eq!(failure_log_decision(n, 1), FailureLog::Full);
+    }
+}
+
+/// Identical failures extend the streak, which a success resets.
+#[tokio::test]
+#[serial]
+async fn consecutive_failures_track_streak() {
+    let failing = RecordingMockRunner::new(Err(SpeedtestError::CommandFailed("offline".to_string())));
+    let succeeding = RecordingMockRunner::new(Ok(SPEEDTEST_CLI_JSON.to_string()));
+    let cfg = RunnerConfig::default();
+
+    run_speedtest_and_cache_with_runner(&succeeding, &cfg).await.unwrap();
+    assert_eq!(consecutive_failures(), 0);
+
+    run_speedtest_and_cache_with_runner(&failing, &cfg).await.unwrap_err();
+    run_speedtest_and_cache_with_runner(&failing, &cfg).await.unwrap_err();
+    assert_eq!(consecutive_failures(), 2);
+
+    run_speedtest_and_cache_with_runner(&succeeding, &cfg).await.unwrap();
+    assert_eq!(consecutive_failures(), 0);
+
+    clear_last_result_for_test();
+}
+
+/// The pipeline still updates the shared cache when run on a separate runtime.
+#[test]
+#[serial]
+fn scheduler_updates_cache_on_dedicated_runtime() {
+    clear_last_result_for_test();
+
+    let runner = RecordingMockRunner::new(Ok(SPEEDTEST_CLI_JSON.to_string()));
+    let handle = spawn_on_dedicated_runtime("test-scheduler", async move {
+        run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await.unwrap();
+    })
+    .unwrap();
+    handle.join().unwrap();
+
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+
+    clear_last_result_for_test();
+}
+
+/// A mock runner whose ping depends on the server it is pointed at.
+struct LatencyMockRunner {
+    latencies: Vec<(&'static str, f64)>,
+}
+
+#[async_trait]
+impl SpeedtestRunner for LatencyMockRunner {
+    async fn run_speedtest(&self, cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        assert!(cfg.no_download && cfg.no_upload, "probes must be ping-only");
+        let id = cfg.server_ids.first().cloned().unwrap_or_default();
+        let (_, ping) = self
+            .latencies
+            .iter()
+            .find(|(server, _)| *server == id)
+            .ok_or_else(|| SpeedtestError::CommandFailed(format!("no such server {}", id)))?;
+
+        let mut json: serde_json::Value = serde_json::from_str(SPEEDTEST_CLI_JSON).unwrap();
+        json["ping"] = serde_json::json!(ping);
+        Ok(json.to_string())
+    }
+}
+
+/// Auto-tuning picks the candidate with the lowest probed latency.
+#[tokio::test]
+async fn autotune_picks_lowest_latency_server() {
+    use speedtest_statuspage::autotune::{tune, TuneState};
+
+    let runner = LatencyMockRunner {
+        latencies: vec![("100", 31.0), ("200", 9.5), ("300", 14.0)],
+    };
+    let candidates: Vec<String> = ["100", "200", "300", "400"].iter().map(|s| s.to_string()).collect();
+
+    let state = tune(&runner, &RunnerConfig::default(), &candidates, 1_700_000_000).await;
+
+    assert_eq!(
+        state,
+        Some(TuneState { server_id: "200".to_string(), tuned_at: 1_700_000_000 })
+    );
+}
+
+/// Unreachable servers are ignored and ties favour the closer server.
+#[test]
+fn pick_best_server_ignores_failed_probes_and_prefers_closer() {
+    use speedtest_statuspage::autotune::pick_best_server;
+
+    let probes = vec![
+        ("1".to_string(), None),
+        ("2".to_string(), Some(12.0)),
+        ("3".to_string(), Some(12.0)),
+        ("4".to_string(), Some(0.0)),
+    ];
+    assert_eq!(pick_best_server(&probes), Some("2".to_string()));
+    assert_eq!(pick_best_server(&[("1".to_string(), None)]), None);
+}
+
+/// The tuned server stays pinned until the refresh period has elapsed.
+#[test]
+fn tune_state_freshness_respects_refresh() {
+    use std::time::Duration;
+    use speedtest_statuspage::autotune::TuneState;
+
+    let state = TuneState { server_id: "200".to_string(), tuned_at: 1_000 };
+    let refresh = Duration::from_secs(3600);
+    assert!(state.is_fresh(1_000 + 3599, refresh));
+    assert!(!state.is_fresh(1_000 + 3600, refresh));
+}
+
+/// A mock runner which panics on its first invocation only.
+struct PanicOnceRunner {
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait]
+impl SpeedtestRunner for PanicOnceRunner {
+    async fn run_speedtest(&self, _cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        if self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+            panic!("simulated arithmetic overflow");
+        }
+        Ok(SPEEDTEST_CLI_JSON.to_string())
+    }
+}
+
+/// A panicking run is recorded and the scheduler carries on with the next tick.
+#[tokio::test]
+#[serial]
+async fn scheduler_survives_panicking_run() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    clear_last_result_for_test();
+    let panics_before = scheduler_panic_count();
+
+    let runner = Arc::new(PanicOnceRunner { calls: AtomicUsize::new(0) });
+    let scheduler_runner = runner.clone();
+    let (_stop, shutdown) = tokio::sync::watch::channel(false);
+    let scheduler = tokio::spawn(async move {
+        let schedule = schedule::Schedule::fixed(Duration::from_millis(20));
+        run_scheduler(scheduler_runner.as_ref(), &RunnerConfig::default(), &schedule, None, None, shutdown).await;
+    });
+
+    // Wait for the run after the panic rather than a fixed time, which a slow
+    // first unwind can overrun
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while runner.calls.load(Ordering::SeqCst) < 2 || get_last_result().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("scheduler must run again after a panic");
+    scheduler.abort();
+
+    assert!(!scheduler.await.unwrap_err().is_panic(), "scheduler task must not die");
+    assert_eq!(scheduler_panic_count(), panics_before + 1);
+    assert!(runner.calls.load(Ordering::SeqCst) >= 2);
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+
+    clear_last_result_for_test();
+}
+
+/// Runner taking a while to finish, recording when it completes.
+struct SlowRunner {
+    finished: std::sync::atomic::AtomicUsize,
+}
+
+#[async_trait]
+impl SpeedtestRunner for SlowRunner {
+    async fn run_speedtest(&self, _cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        self.finished.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(SPEEDTEST_CLI_JSON.to_string())
+    }
+}
+
+/// A shutdown requested mid-run lets the run finish, then stops the scheduler.
+#[tokio::test]
+#[serial]
+async fn scheduler_finishes_in_flight_run_on_shutdown() {
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    clear_last_result_for_test();
+    let runner = Arc::new(SlowRunner { finished: 0.into() });
+    let scheduler_runner = runner.clone();
+    let (stop, shutdown) = tokio::sync::watch::channel(false);
+    let scheduler = tokio::spawn(async move {
+        let schedule = schedule::Schedule::fixed(Duration::from_secs(3600));
+        run_scheduler(scheduler_runner.as_ref(), &RunnerConfig::default(), &schedule, None, None, shutdown).await
+    });
+
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    stop.send(true).unwrap();
+    let runs = tokio::time::timeout(Duration::from_secs(5), scheduler).await.unwrap().unwrap();
+
+    assert_eq!(runs, 1);
+    assert_eq!(runner.finished.load(Ordering::SeqCst), 1);
+    assert!(get_last_result().is_some());
+    clear_last_result_for_test();
+}
+
+/// Real `--version` output from both backends parses to a version triple.
+#[test]
+fn parses_real_version_strings() {
+    let legacy = "speedtest-cli 2.1.3\nPython 3.11.2 (main, Mar 13 2023, 12:18:29) [GCC 12.2.0]\n";
+    assert_eq!(version::parse_version(legacy), Some((2, 1, 3)));
+    assert_eq!(version::parse_version("3.2.0\n"), Some((3, 2, 0)));
+    assert_eq!(version::parse_version("speedtest-cli\n"), None);
+}
+
+/// Versions below the minimum are flagged; equal or newer versions are supported.
+#[test]
+fn version_check_compares_against_minimum() {
+    use version::VersionStatus;
+
+    assert_eq!(
+        version::check_version_output(Backend::Legacy, "speedtest-cli 1.0.7"),
+        VersionStatus::TooOld { found: (1, 0, 7), minimum: (2, 1, 0) }
+    );
+    assert_eq!(
+        version::check_version_output(Backend::Legacy, "speedtest-cli 2.1.0"),
+        VersionStatus::Supported((2, 1, 0))
+    );
+    assert_eq!(
+        version::check_version_output(Backend::Fast, "2.1.0"),
+        VersionStatus::TooOld { found: (2, 1, 0), minimum: (3, 0, 0) }
+    );
+    assert_eq!(version::check_version_output(Backend::Fast, ""), VersionStatus::Unknown);
+    assert!(version::meets_minimum((10, 0, 0), (2, 1, 0)));
+}
+
+/// A failure switches to the outage probe interval until a success restores the
+/// normal interval; without a probe interval the schedule never changes.
+#[test]
+fn outage_probe_schedule_follows_failures_and_recovery() {
+    use std::time::Duration;
+
+    let normal = Duration::from_secs(600);
+    let probe = Some(Duration::from_secs(30));
+    let mut mode = SchedulerMode::Normal;
+    let mut intervals = Vec::new();
+    for succeeded in [true, false, false, true, true] {
+        let (next_mode, interval) = next_schedule(succeeded, mode, normal, probe);
+        mode = next_mode;
+        intervals.push((mode, interval.as_secs()));
+    }
+    assert_eq!(
+        intervals,
+        [
+            (SchedulerMode::Normal, 600),
+            (SchedulerMode::OutageProbe, 30),
+            (SchedulerMode::OutageProbe, 30),
+            (SchedulerMode::Normal, 600),
+            (SchedulerMode::Normal, 600),
+        ]
+    );
+
+    assert_eq!(next_schedule(false, SchedulerMode::Normal, normal, None), (SchedulerMode::Normal, normal));
+    assert_eq!(
+        next_schedule(false, SchedulerMode::Normal, Duration::from_secs(10), probe),
+        (SchedulerMode::OutageProbe, Duration::from_secs(10))
+    );
+}
+
+/// The schedule picks the interval of the window containing the time, including
+/// exactly at window boundaries and across midnight.
+#[test]
+fn schedule_interval_follows_windows() {
+    use std::time::Duration;
+
+    let schedule: schedule::Schedule = "08:00-23:00@5m, 23:00-08:00@30m".parse().unwrap();
+    let at = |h, m| schedule.interval_at(chrono::NaiveTime::from_hms_opt(h, m, 30).unwrap());
+    let (peak, night) = (Duration::from_secs(300), Duration::from_secs(1800));
+
+    assert_eq!(at(7, 59), night);
+    assert_eq!(at(8, 0), peak);
+    assert_eq!(at(22, 59), peak);
+    assert_eq!(at(23, 0), night);
+    assert_eq!(at(0, 0), night);
+    assert_eq!(at(3, 0), night);
+
+    let fixed = schedule::Schedule::fixed(Duration::from_secs(60));
+    assert_eq!(fixed.interval_at(chrono::NaiveTime::from_hms_opt(13, 37, 0).unwrap()), Duration::from_secs(60));
+}
+
+/// Overlapping, incomplete and malformed schedules are rejected with the reason.
+#[test]
+fn schedule_rejects_invalid_specs() {
+    let err = |s: &str| s.parse::<schedule::Schedule>().unwrap_err();
+    assert_eq!(err("08:00-23:00@5m,22:00-08:00@30m"), "windows 08:00-23:00@300s and 22:00-08:00@1800s overlap");
+    assert_eq!(err("08:00-23:00@5m,23:00-06:00@30m"), "no window covers 06:00-08:00");
+    assert_eq!(err(""), "no windows given");
+    assert!(err("08:00-23:00").contains("no @interval"));
+    assert!(err("8am-11pm@5m").contains("expected HH:MM"));
+    assert!(err("00:00-00:00@0m").contains("invalid interval"));
+    assert!(err("00:00-00:00@99999999999999999h").contains("too long"));
+    assert!("00:00-00:00@1h".parse::<schedule::Schedule>().is_ok());
+}
+
+/// A stream which delivers some output and then goes silent is reported as stalled.
+#[tokio::test]
+async fn watchdog_detects_stalled_stream() {
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    let (mut child_stdout, reader) = tokio::io::duplex(64);
+    child_stdout.write_all(b"Retrieving speedtest.net configuration...").await.unwrap();
+
+    let result = watchdog::read_until_stall(reader, Duration::from_millis(50)).await;
+    assert_eq!(result, Err(SpeedtestError::Stalled(Duration::from_millis(50))));
+    drop(child_stdout);
+}
+
+/// Output that keeps arriving and then ends is returned in full.
+#[tokio::test]
+async fn watchdog_reads_complete_stream() {
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    let (mut child_stdout, reader) = tokio::io::duplex(64);
+    let writer = tokio::spawn(async move {
+        for part in ["{\"download\": ", "1.0}"] {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            child_stdout.write_all(part.as_bytes()).await.unwrap();
+        }
+    });
+
+    let output = watchdog::read_until_stall(reader, Duration::from_millis(500)).await.unwrap();
+    assert_eq!(output, b"{\"download\": 1.0}");
+    writer.await.unwrap();
+}
+
+/// A child which stops writing is killed long before it would have exited.
+#[tokio::test]
+async fn watchdog_kills_stalled_child() {
+    use std::time::{Duration, Instant};
+
+    let mut command = tokio::process::Command::new("sh");
+    command.args(["-c", "echo started; sleep 30"]);
+
+    let started = Instant::now();
+    let result = watchdog::run_watched(&mut command, "sh", Some(Duration::from_millis(200))).await;
+    assert_eq!(result.unwrap_err(), SpeedtestError::Stalled(Duration::from_millis(200)));
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+/// Runner which takes far longer than any test timeout.
+struct HangingRunner;
+
+#[async_trait]
+impl SpeedtestRunner for HangingRunner {
+    async fn run_speedtest(&self, _cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+        Ok(SPEEDTEST_CLI_JSON.to_string())
+    }
+}
+
+/// A run exceeding the run timeout fails with a timeout instead of blocking.
+#[tokio::test]
+#[serial]
+async fn hanging_run_times_out() {
+    use std::time::{Duration, Instant};
+    clear_last_result_for_test();
+    let cfg = RunnerConfig { run_timeout: Some(Duration::from_millis(100)), ..Default::default() };
+
+    let started = Instant::now();
+    let outcome = run_speedtest_and_cache_with_runner(&HangingRunner, &cfg).await;
+
+    assert_eq!(outcome, Err(SpeedtestError::TimedOut(Duration::from_millis(100))));
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(get_last_result().is_none());
+}
+
+/// Binding is retried until it succeeds within the retry budget.
+#[tokio::test]
+async fn retry_bind_recovers_from_transient_failures() {
+    let mut calls = 0;
+    let bound = retry_bind(
+        || {
+            calls += 1;
+            if calls <= 2 { Err("cannot assign requested address") } else { Ok("bound") }
+        },
+        3,
+        std::time::Duration::ZERO,
+    )
+    .await;
+    assert_eq!(bound, Ok("bound"));
+    assert_eq!(calls, 3);
+}
+
+/// Once the retries are exhausted the last error is returned.
+#[tokio::test]
+async fn retry_bind_gives_up_after_max_retries() {
+    let mut calls = 0;
+    let bound: Result<(), String> = retry_bind(
+        || {
+            calls += 1;
+            Err(format!("attempt {} failed", calls))
+        },
+        2,
+        std::time::Duration::ZERO,
+    )
+    .await;
+    assert_eq!(bound, Err("attempt 3 failed".to_string()));
+    assert_eq!(calls, 3);
+}
+
+/// A mis-scaled result is rejected instead of replacing the cached one.
+#[tokio::test]
+#[serial]
+async fn implausible_result_is_not_cached() {
+    clear_last_result_for_test();
+    let misscaled = SPEEDTEST_CLI_JSON.replace("94372819.52", "94372819520000000000.0");
+    let runner = RecordingMockRunner::new(Ok(misscaled));
+
+    let outcome = run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await;
+    assert!(matches!(outcome, Err(SpeedtestError::Rejected(_))));
+    assert!(get_last_result().is_none());
+}
+
+/// The capture wrapper saves the inner runner's output and passes it through unchanged.
+#[tokio::test]
+async fn recording_runner_captures_raw_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let captures = dir.path().join("captures");
+    let runner = capture::RecordingRunner::new(RecordingMockRunner::succeeding(), &captures);
+
+    let output = runner.run_speedtest(&RunnerConfig::default()).await.unwrap();
+    assert_eq!(output, SPEEDTEST_CLI_JSON);
+    assert_eq!(runner.parse_output(&output).unwrap().server.id, "12345");
+
+    let files: Vec<_> = std::fs::read_dir(&captures).unwrap().map(|e| e.unwrap().path()).collect();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].file_name().unwrap().to_str().unwrap().starts_with("capture-"));
+    assert_eq!(std::fs::read_to_string(&files[0]).unwrap(), SPEEDTEST_CLI_JSON);
+}
+
+/// A capture directory which cannot be written does not fail the run.
+#[tokio::test]
+async fn recording_runner_tolerates_unwritable_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let not_a_dir = dir.path().join("file");
+    std::fs::write(&not_a_dir, "").unwrap();
+    let runner = capture::RecordingRunner::new(RecordingMockRunner::succeeding(), &not_a_dir);
+
+    assert!(runner.run_speedtest(&RunnerConfig::default()).await.is_ok());
+}
+
+/// `PING_TARGET` values parse into a host and port, defaulting the port.
+#[test]
+fn ping_target_parses_host_and_port() {
+    use ping::{PingTarget, DEFAULT_PING_PORT};
+
+    let parse = |s: &str| s.parse::<PingTarget>().map(|t| (t.host, t.port));
+    assert_eq!(parse("vpn.example.com:1194"), Ok(("vpn.example.com".to_string(), 1194)));
+    assert_eq!(parse(" 1.1.1.1 "), Ok(("1.1.1.1".to_string(), DEFAULT_PING_PORT)));
+    assert_eq!(parse("[::1]:53"), Ok(("::1".to_string(), 53)));
+    assert_eq!(parse("2001:db8::1"), Ok(("2001:db8::1".to_string(), DEFAULT_PING_PORT)));
+    for invalid in ["", ":80", "host:", "host:0", "host:http", "[::1", "[::1]80"] {
+        assert!(parse(invalid).is_err(), "{invalid:?} should be rejected");
+    }
+    assert_eq!("[::1]:53".parse::<PingTarget>().unwrap().to_string(), "[::1]:53");
+}
+
+/// The TCP probe times the connection to a bound local socket, and reports a
+/// refused connection as an error rather than a latency.
+#[tokio::test]
+async fn tcp_probe_measures_connect_time() {
+    use ping::LatencyProbe;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let target = ping::PingTarget { host: "127.0.0.1".to_string(), port };
+    let latency = ping::TcpProbe.probe(&target).await.unwrap();
+    assert!(latency < ping::PROBE_TIMEOUT, "{latency:?}");
+
+    drop(listener);
+    let err = ping::TcpProbe.probe(&target).await.unwrap_err();
+    assert!(!err.contains("timed out"), "{err}");
+}
+
+/// `PROBE_METHOD` selects TCP by default, ICMP on request, and falls back to TCP for
+/// unknown values.
+#[test]
+#[serial]
+fn probe_method_read_from_env() {
+    use ping::ProbeMethod;
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::remove_var("PROBE_METHOD") };
+    assert_eq!(ProbeMethod::from_env(), ProbeMethod::Tcp);
+    for (value, method) in [("icmp", ProbeMethod::Icmp), (" TCP ", ProbeMethod::Tcp), ("udp", ProbeMethod::Tcp)] {
+        // SAFETY: as above.
+        unsafe { std::env::set_var("PROBE_METHOD", value) };
+        assert_eq!(ProbeMethod::from_env(), method, "{value:?}");
+    }
+    assert!("udp".parse::<ProbeMethod>().is_err());
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("PROBE_METHOD") };
+}
+
+/// Probe returning a fixed latency.
+struct FixedProbe(Option<std::time::Duration>);
+
+#[async_trait]
+impl ping::LatencyProbe for FixedProbe {
+    async fn probe(&self, _target: &ping::PingTarget) -> Result<std::time::Duration, String> {
+        self.0.ok_or_else(|| "unreachable".to_string())
+    }
+}
+
+/// The external ping is stored separately and leaves the speedtest ping alone.
+#[tokio::test]
+async fn external_ping_recorded_separately() {
+    let target: ping::PingTarget = "vpn.example.com:1194".parse().unwrap();
+    let mut result = RecordingMockRunner::succeeding().parse_output(SPEEDTEST_CLI_JSON).unwrap();
+    let speedtest_ping = result.ping_ms;
+
+    ping::record_external_ping(&mut result, &FixedProbe(Some(std::time::Duration::from_millis(42))), &target).await;
+    assert_eq!(result.external_ping_ms, Some(42.0));
+    assert_eq!(result.ping_ms, speedtest_ping);
+
+    let mut result = RecordingMockRunner::succeeding().parse_output(SPEEDTEST_CLI_JSON).unwrap();
+    ping::record_external_ping(&mut result, &FixedProbe(None), &target).await;
+    assert_eq!(result.external_ping_ms, None);
+}
+
+/// Transient failures are retryable; a missing binary is not.
+#[test]
+fn speedtest_errors_classified_as_retryable_or_fatal() {
+    use std::time::Duration;
+
+    assert!(SpeedtestError::CommandFailed("speedtest-cli failed: 503".to_string()).is_retryable());
+    assert!(SpeedtestError::Stalled(Duration::from_secs(30)).is_retryable());
+    assert!(!SpeedtestError::BinaryNotFound("speedtest-cli".to_string()).is_retryable());
+}
+
+/// A fatal error is returned after a single attempt, while retryable ones use up
+/// every attempt.
+#[tokio::test]
+async fn run_with_retries_fails_fast_on_fatal_errors() {
+    let cfg = RunnerConfig::default();
+
+    let fatal = RecordingMockRunner::new(Err(SpeedtestError::BinaryNotFound("speedtest-cli".to_string())));
+    let outcome = run_with_retries(&fatal, &cfg, 3).await;
+    assert_eq!(outcome, Err(SpeedtestError::BinaryNotFound("speedtest-cli".to_string())));
+    assert_eq!(fatal.seen.lock().unwrap().len(), 1);
+
+    let transient = RecordingMockRunner::failing("connection reset");
+    assert!(run_with_retries(&transient, &cfg, 3).await.is_err());
+    assert_eq!(transient.seen.lock().unwrap().len(), 3);
+
+    let succeeding = RecordingMockRunner::succeeding();
+    assert!(run_with_retries(&succeeding, &cfg, 3).await.is_ok());
+    assert_eq!(succeeding.seen.lock().unwrap().len(), 1);
+}
+
+/// Runner failing a fixed number of times before succeeding.
+struct FlakyRunner {
+    failures_left: std::sync::atomic::AtomicU32,
+}
+
+#[async_trait]
+impl SpeedtestRunner for FlakyRunner {
+    async fn run_speedtest(&self, _cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        use std::sync::atomic::Ordering;
+        match self.failures_left.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)) {
+            Ok(_) => Err(SpeedtestError::CommandFailed("server hiccup".to_string())),
+            Err(_) => Ok(SPEEDTEST_CLI_JSON.to_string()),
+        }
+    }
+}
+
+/// Transient failures are retried with backoff until the run succeeds and is cached,
+/// and the recorded duration leaves out the failed attempts and delays.
+#[tokio::test]
+#[serial]
+async fn transient_failures_are_retried_before_caching() {
+    use std::time::{Duration, Instant};
+    clear_last_result_for_test();
+    let runner = FlakyRunner { failures_left: 2.into() };
+    let cfg = RunnerConfig { retries: 3, retry_base: Duration::from_millis(20), ..Default::default() };
+
+    let started = Instant::now();
+    let result = run_speedtest_and_cache_with_runner(&runner, &cfg).await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(60), "backs off 20ms, then 40ms");
+    assert!(result.test_duration_ms.unwrap() < 20, "only the successful attempt is timed");
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+    clear_last_result_for_test();
+}
+
+/// Full jitter keeps the delay within `[0, backoff]` for any injected value.
+#[test]
+fn jittered_delay_stays_within_backoff() {
+    use std::time::Duration;
+    let backoff = Duration::from_millis(4000);
+    assert_eq!(jittered_delay(backoff, 0.0), Duration::ZERO);
+    assert_eq!(jittered_delay(backoff, 0.5), Duration::from_millis(2000));
+    assert_eq!(jittered_delay(backoff, 1.0), backoff);
+    for random in [-1.0, 0.001, 0.3, 0.999_999, 2.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let delay = jittered_delay(backoff, random);
+        assert!(delay <= backoff, "{random} gave {delay:?}");
+    }
+}
+
+/// With jitter enabled, transient failures are still retried until the run succeeds.
+#[tokio::test]
+#[serial]
+async fn jittered_retries_still_succeed() {
+    use std::time::Duration;
+    clear_last_result_for_test();
+    let runner = FlakyRunner { failures_left: 2.into() };
+    let cfg = RunnerConfig { retries: 3, retry_base: Duration::from_millis(20), retry_jitter: true, ..Default::default() };
+
+    run_speedtest_and_cache_with_runner(&runner, &cfg).await.unwrap();
+
+    assert_eq!(get_last_result().unwrap().server.id, "12345");
+    clear_last_result_for_test();
+}
+
+/// Runner whose every run fails after taking `took` on a fake clock.
+struct SlowFailingRunner {
+    clock: std::sync::Mutex<std::time::Instant>,
+    took: std::time::Duration,
+    runs: std::sync::atomic::AtomicU32,
+}
+
+#[async_trait]
+impl SpeedtestRunner for SlowFailingRunner {
+    async fn run_speedtest(&self, _cfg: &RunnerConfig) -> Result<String, SpeedtestError> {
+        self.runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        *self.clock.lock().unwrap() += self.took;
+        Err(SpeedtestError::CommandFailed("server hiccup".to_string()))
+    }
+}
+
+/// Once the run budget is used up no further attempt is started, even with
+/// retries left.
+#[tokio::test]
+async fn run_budget_stops_retrying() {
+    use std::time::{Duration, Instant};
+    let runner = SlowFailingRunner {
+        clock: Instant::now().into(),
+        took: Duration::from_secs(30),
+        runs: 0.into(),
+    };
+    let now = || *runner.clock.lock().unwrap();
+    let cfg = RunnerConfig {
+        retry_base: Duration::from_millis(1),
+        run_budget: Some(Duration::from_secs(100)),
+        ..Default::default()
+    };
+
+    // Attempts end 30s, 60s, 90s and 120s in; the fourth leaves no budget to retry.
+    let outcome = run_within_budget(&runner, &cfg, 10, now).await;
+    assert!(matches!(outcome, Err(SpeedtestError::CommandFailed(_))));
+    assert_eq!(runner.runs.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+    let runner = SlowFailingRunner { runs: 0.into(), ..runner };
+    let now = || *runner.clock.lock().unwrap();
+    let unlimited = RunnerConfig { run_budget: None, ..cfg };
+    run_within_budget(&runner, &unlimited, 10, now).await.unwrap_err();
+    assert_eq!(runner.runs.load(std::sync::atomic::Ordering::SeqCst), 10);
+}
+
+/// Reads the `_count` of the run duration histogram, zero while it is empty.
+fn run_duration_count() -> u64 {
+    stats::render_run_durations()
+        .lines()
+        .find_map(|line| line.strip_prefix("speedtest_run_duration_seconds_count "))
+        .map_or(0, |count| count.parse().unwrap())
+}
+
+/// Each cached run records how long it took on the result and in the run
+/// duration histogram, which accumulates across runs.
+#[tokio::test]
+#[serial]
+async fn cached_runs_record_their_duration() {
+    clear_last_result_for_test();
+    let before = run_duration_count();
+    let runner = RecordingMockRunner::new(Ok(SPEEDTEST_CLI_JSON.to_string()));
+
+    for _ in 0..2 {
+        let result = run_speedtest_and_cache_with_runner(&runner, &RunnerConfig::default()).await.unwrap();
+        assert!(result.test_duration_ms.is_some());
+    }
+
+    assert_eq!(run_duration_count(), before + 2);
+    clear_last_result_for_test();
+}
+
+/// Output which fails to parse is not retried.
+#[tokio::test]
+#[serial]
+async fn parse_failures_are_not_retried() {
+    let runner = RecordingMockRunner::new(Ok("not json".to_string()));
+    let cfg = RunnerConfig { retries: 3, ..Default::default() };
+
+    let outcome = run_speedtest_and_cache_with_runner(&runner, &cfg).await;
+
+    assert!(matches!(outcome, Err(SpeedtestError::ParseFailed(_))));
+    assert_eq!(runner.seen.lock().unwrap().len(), 1);
+}
+
+/// Running a binary which does not exist is reported as such.
+#[tokio::test]
+async fn missing_binary_reported_as_not_found() {
+    let mut command = tokio::process::Command::new("speedtest-statuspage-no-such-binary");
+    let outcome = watchdog::run_watched(&mut command, "speedtest-statuspage-no-such-binary", None).await;
+    assert_eq!(outcome.unwrap_err(), SpeedtestError::BinaryNotFound("speedtest-statuspage-no-such-binary".to_string()));
+}
+
+/// A result older than the expiry is cleared from the cache; a fresh one is kept.
+#[test]
+#[serial]
+fn expired_result_is_cleared() {
+    use std::time::{Duration, Instant};
+
+    set_last_result_at(SpeedTestResult::default(), Instant::now() - Duration::from_secs(30));
+    assert!(!expire_last_result(Duration::from_secs(60)));
+    assert!(get_last_result().is_some());
+
+    set_last_result_at(SpeedTestResult::default(), Instant::now() - Duration::from_secs(90));
+    assert!(expire_last_result(Duration::from_secs(60)));
+    assert!(get_last_result().is_none());
+    assert!(!expire_last_result(Duration::from_secs(60)));
+}
+
+/// The runtime counters reflect the runs driven through the pipeline.
+#[tokio::test]
+#[serial]
+async fn runtime_stats_count_runs() {
+    let before = stats::runtime_stats();
+    let cfg = RunnerConfig::default();
+
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::failing("offline"), &cfg).await.unwrap_err();
+    let after = stats::runtime_stats();
+
+    assert_eq!(after.total_runs - before.total_runs, 3);
+    assert_eq!(after.successes - before.successes, 2);
+    assert_eq!(after.failures - before.failures, 1);
+    assert_eq!(after.bytes_transferred - before.bytes_transferred, 2 * (118_456_320 + 23_068_672));
+    assert_eq!(after.consecutive_failures, 1);
+    assert!(after.last_success.is_some());
+    assert!(after.last_failure.is_some());
+    clear_last_result_for_test();
+}
+
+/// Each stored result is appended to the history, oldest first.
+#[tokio::test]
+#[serial]
+async fn stored_results_are_appended_to_history() {
+    clear_history_for_test();
+    let cfg = RunnerConfig::default();
+
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::failing("offline"), &cfg).await.unwrap_err();
+    run_speedtest_and_cache_with_runner(&RecordingMockRunner::succeeding(), &cfg).await.unwrap();
+
+    let history = get_history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].origin, Origin::Scheduled);
+    clear_history_for_test();
+    clear_last_result_for_test();
+}
+
+/// The history never grows beyond the default capacity of 100.
+#[test]
+#[serial]
+fn history_drops_oldest_beyond_capacity() {
+    clear_history_for_test();
+    for i in 0..105 {
+        push_history_for_test(SpeedTestResult { ping_ms: i as f64, ..Default::default() });
+    }
+
+    let history = get_history();
+    assert_eq!(history.len(), 100);
+    assert_eq!(history[0].ping_ms, 5.0);
+    assert_eq!(history[99].ping_ms, 104.0);
+    clear_history_for_test();
+}
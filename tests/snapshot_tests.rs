@@ -0,0 +1,89 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `snapshot` module, which keeps the latest result across restarts.
+
+mod common;
+
+use common::complete_result;
+use speedtest_statuspage::*;
+
+/// A result written to the snapshot file loads back unchanged, without leaving a
+/// temporary file behind.
+#[test]
+fn snapshot_round_trips_result() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.json");
+    let result = SpeedTestResult { node: "pi".to_string(), schema_version: SCHEMA_VERSION, ..complete_result() };
+
+    snapshot::write_snapshot(&path, &result).unwrap();
+    assert_eq!(snapshot::load_snapshot(&path), Some(result));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+/// Missing or corrupt snapshot files are ignored.
+#[test]
+fn snapshot_load_ignores_missing_and_invalid_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.json");
+    assert_eq!(snapshot::load_snapshot(&path), None);
+
+    std::fs::write(&path, "{\"download_bps\":").unwrap();
+    assert_eq!(snapshot::load_snapshot(&path), None);
+}
+
+/// A permission error on the first write disables persistence, so later writes
+/// are skipped instead of failing every run.
+#[test]
+fn persistence_disabled_after_permission_denied() {
+    let persistence = snapshot::Persistence::new();
+    let path = std::path::Path::new("/read-only/snapshot.json");
+
+    let written = persistence.write(path, || Err(std::io::ErrorKind::PermissionDenied.into()));
+    assert!(!written.unwrap());
+    assert!(!persistence.is_enabled());
+
+    let mut attempted = false;
+    let written = persistence.write(path, || {
+        attempted = true;
+        Ok(())
+    });
+    assert!(!written.unwrap());
+    assert!(!attempted);
+}
+
+/// Errors other than permission problems are reported and keep persistence on.
+#[test]
+fn persistence_reports_other_errors() {
+    let persistence = snapshot::Persistence::default();
+    let path = std::path::Path::new("snapshot.json");
+
+    assert!(persistence.write(path, || Err(std::io::ErrorKind::Other.into())).is_err());
+    assert!(persistence.is_enabled());
+    assert!(persistence.write(path, || Ok(())).unwrap());
+}
+
+/// A v1 snapshot left by an older release is upgraded when loaded.
+#[test]
+fn snapshot_load_migrates_old_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("snapshot.json");
+    std::fs::write(&path, include_str!("fixtures/result_v1.json")).unwrap();
+
+    let result = snapshot::load_snapshot(&path).unwrap();
+    assert_eq!(result.schema_version, SCHEMA_VERSION);
+    assert_eq!(result.timestamp, "2025-08-07T12:00:00.000000Z");
+}
@@ -0,0 +1,91 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `sparkline` module, which draws the download history as SVG.
+
+use actix_web::{http, test, App};
+use serial_test::serial;
+use speedtest_statuspage::sparkline::{render_sparkline, sparkline_endpoint, sparkline_points};
+use speedtest_statuspage::*;
+
+/// Extracts the `x,y` pairs of the polyline in `svg`.
+fn polyline_points(svg: &str) -> Vec<(f64, f64)> {
+    let Some(start) = svg.find("points=\"") else { return Vec::new() };
+    let rest = &svg[start + "points=\"".len()..];
+    rest[..rest.find('"').unwrap()]
+        .split(' ')
+        .map(|pair| {
+            let (x, y) = pair.split_once(',').unwrap();
+            (x.parse().unwrap(), y.parse().unwrap())
+        })
+        .collect()
+}
+
+/// Every finite value becomes one point spread across the width, with the lowest
+/// value at the bottom and the highest at the top.
+#[actix_web::test]
+async fn sparkline_points_span_the_chart() {
+    let points = sparkline_points(&[40.0, 80.0, f64::NAN, 60.0, 40.0, 100.0], 200.0, 60.0);
+    assert_eq!(points, vec![(0.0, 60.0), (50.0, 20.0), (100.0, 40.0), (150.0, 60.0), (200.0, 0.0)]);
+
+    assert_eq!(sparkline_points(&[7.0], 200.0, 50.0), vec![(100.0, 25.0)]);
+    assert_eq!(sparkline_points(&[7.0, 7.0], 200.0, 50.0), vec![(0.0, 25.0), (200.0, 25.0)]);
+    assert!(sparkline_points(&[], 200.0, 50.0).is_empty());
+}
+
+/// The rendered SVG has one coordinate pair per value, all inside the view box,
+/// and no polyline at all without values.
+#[actix_web::test]
+async fn sparkline_svg_has_valid_coordinates() {
+    let values: Vec<f64> = (0..25).map(|i| 50.0 + (i * 37 % 11) as f64).collect();
+    let svg = render_sparkline(&values, 300, 40);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"300\" height=\"40\""));
+    let points = polyline_points(&svg);
+    assert_eq!(points.len(), values.len());
+    assert!(points.iter().all(|&(x, y)| (0.0..=300.0).contains(&x) && (0.0..=40.0).contains(&y)));
+
+    let blank = render_sparkline(&[], 300, 40);
+    assert!(blank.ends_with("viewBox=\"0 0 300 40\"></svg>"), "{blank}");
+}
+
+/// `/speed/sparkline.svg` serves the history as SVG of the requested size, and a
+/// blank chart rather than 503 while the history is empty.
+#[actix_web::test]
+#[serial]
+async fn sparkline_endpoint_draws_history() {
+    let app = test::init_service(App::new().service(sparkline_endpoint)).await;
+
+    clear_history_for_test();
+    let req = test::TestRequest::get().uri("/speed/sparkline.svg").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), http::StatusCode::OK);
+    assert_eq!(resp.headers().get(http::header::CONTENT_TYPE).unwrap(), "image/svg+xml");
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains("width=\"120\" height=\"30\""), "{body}");
+    assert!(polyline_points(&body).is_empty());
+
+    for download_mbps in [10.0, 30.0, 20.0] {
+        push_history_for_test(SpeedTestResult { download_mbps, ..Default::default() });
+    }
+    let req = test::TestRequest::get().uri("/speed/sparkline.svg?width=100&height=20").to_request();
+    let body = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    assert_eq!(polyline_points(&body), vec![(0.0, 20.0), (50.0, 0.0), (100.0, 10.0)]);
+
+    let req = test::TestRequest::get().uri("/speed/sparkline.svg?width=wide").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), http::StatusCode::BAD_REQUEST);
+
+    clear_history_for_test();
+}
@@ -0,0 +1,146 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `stats` module, which summarises the result history and keeps the
+//! runtime counters.
+//!
+//! Restoring the counters reads `STATE_FILE` from the environment, so that test
+//! runs serially.
+
+use serial_test::serial;
+use speedtest_statuspage::*;
+
+/// Builds a result with the given download, upload and ping.
+fn measured(download_mbps: f64, upload_mbps: f64, ping_ms: f64) -> SpeedTestResult {
+    SpeedTestResult { download_mbps, upload_mbps, ping_ms, ..Default::default() }
+}
+
+/// Hand-computed statistics, covering the median of an odd and an even count.
+#[test]
+fn history_stats_match_hand_computed_values() {
+    let odd = [measured(100.0, 20.0, 12.0), measured(80.0, 10.0, 30.0), measured(90.0, 30.0, 9.0)];
+    let stats = stats::compute_stats(&odd);
+    assert_eq!(stats.count, 3);
+    assert_eq!(
+        stats.download_mbps,
+        Some(stats::Summary { min: 80.0, max: 100.0, mean: 90.0, median: 90.0 })
+    );
+    assert_eq!(stats.upload_mbps.unwrap().median, 20.0);
+    assert_eq!(stats.ping_ms, Some(stats::Summary { min: 9.0, max: 30.0, mean: 17.0, median: 12.0 }));
+
+    let even = [odd.as_slice(), &[measured(50.0, 5.0, 15.0)]].concat();
+    let stats = stats::compute_stats(&even);
+    assert_eq!(stats.count, 4);
+    assert_eq!(
+        stats.download_mbps,
+        Some(stats::Summary { min: 50.0, max: 100.0, mean: 80.0, median: 85.0 })
+    );
+    assert_eq!(stats.upload_mbps.unwrap().median, 15.0);
+    assert_eq!(stats.ping_ms.unwrap().median, 13.5);
+}
+
+/// An empty history yields a zero count and `null` summaries instead of panicking.
+#[test]
+fn history_stats_of_empty_history_are_null() {
+    let stats = stats::compute_stats(&[]);
+    assert_eq!(stats.count, 0);
+    assert_eq!(
+        serde_json::to_value(&stats).unwrap(),
+        serde_json::json!({"count": 0, "download_mbps": null, "upload_mbps": null, "ping_ms": null})
+    );
+}
+
+/// Builds a result measured at the RFC 3339 `timestamp`.
+fn measured_at(timestamp: &str, download_mbps: f64) -> SpeedTestResult {
+    SpeedTestResult { timestamp: timestamp.to_string(), ..measured(download_mbps, 10.0, 20.0) }
+}
+
+/// Only results at or after the cutoff are kept, in their original order; results
+/// with an unparsable timestamp are dropped.
+#[test]
+fn window_keeps_results_inside_it() {
+    let history = [
+        measured_at("2025-08-07T10:00:00Z", 10.0),
+        measured_at("2025-08-07T11:59:59+00:00", 20.0),
+        measured_at("2025-08-07T12:00:00Z", 30.0),
+        measured_at("not a time", 40.0),
+        measured_at("2025-08-07T14:30:00+02:00", 50.0),
+    ];
+    let cutoff = "2025-08-07T12:00:00Z".parse().unwrap();
+    let kept = stats::within_window(&history, cutoff);
+    assert_eq!(kept, vec![history[2].clone(), history[4].clone()]);
+    assert_eq!(stats::compute_stats(&kept).download_mbps.unwrap().mean, 40.0);
+}
+
+/// A window excluding every result summarises to an empty count.
+#[test]
+fn window_excluding_everything_is_empty() {
+    let history = [measured_at("2025-08-07T10:00:00Z", 10.0), measured_at("2025-08-07T11:00:00Z", 20.0)];
+    let kept = stats::within_window(&history, "2025-08-08T00:00:00Z".parse().unwrap());
+    assert!(kept.is_empty());
+    assert_eq!(stats::compute_stats(&kept).count, 0);
+}
+
+/// Saved counters survive a JSON round trip, and a state file missing fields loads
+/// them as zero.
+#[test]
+fn saved_counters_round_trip() {
+    let saved = stats::SavedCounters {
+        total_runs: 7,
+        successes: 5,
+        bytes_transferred: 1 << 40,
+        last_success_ms: 1_760_000_000_000,
+        last_failure_ms: 0,
+    };
+    let json = serde_json::to_string(&saved).unwrap();
+    assert_eq!(serde_json::from_str::<stats::SavedCounters>(&json).unwrap(), saved);
+    let partial: stats::SavedCounters = serde_json::from_str("{\"total_runs\":3}").unwrap();
+    assert_eq!(partial, stats::SavedCounters { total_runs: 3, ..Default::default() });
+}
+
+/// At startup the counters in `STATE_FILE` are added to `/stats/runtime`, and saving
+/// writes them back out.
+#[test]
+#[serial]
+fn runtime_counters_restored_from_state_file() {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/runtime_state.json");
+    let before = stats::runtime_stats();
+
+    // SAFETY: tests in this binary run serially and no other thread reads the environment.
+    unsafe { std::env::set_var("STATE_FILE", fixture) };
+    assert!(stats::restore_state());
+    let after = stats::runtime_stats();
+    assert_eq!(after.total_runs, before.total_runs + 42);
+    assert_eq!(after.successes, before.successes + 40);
+    assert_eq!(after.failures, before.failures + 2);
+    assert_eq!(after.bytes_transferred, before.bytes_transferred + 123_456_789);
+    assert_eq!(after.last_success.as_deref(), Some("2025-10-09T08:53:20+00:00"));
+    assert_eq!(after.last_failure.as_deref(), Some("2025-10-09T06:06:40+00:00"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    stats::save_state(&path);
+    assert_eq!(stats::load_state(&path), Some(stats::saved_counters()));
+
+    // SAFETY: as above.
+    unsafe { std::env::set_var("STATE_FILE", dir.path().join("missing.json")) };
+    assert!(!stats::restore_state());
+    // SAFETY: as above.
+    unsafe { std::env::set_var("STATE_FILE", "") };
+    assert_eq!(stats::state_path(), None);
+    // SAFETY: as above.
+    unsafe { std::env::remove_var("STATE_FILE") };
+}
@@ -0,0 +1,49 @@
+//! # speedtest-statuspage
+//!
+//! A utility application to serve speedtest results over an HTTP endpoint.
+//!
+//! ## Disclaimer
+//! This project is not affiliated with, endorsed by, or sponsored by Ookla. (Ookla®).
+//! All trademarks and copyrights belong to their respective owners.
+
+// Copyright (c) 2025 Jak Bracegirdle
+//
+// This file is part of the speedtest_statuspage crate.
+//
+// Licensed under the Apache License, Version 2.0 <http://www.apache.org/licenses/LICENSE-2.0>
+// or the MIT license <http://opensource.org/licenses/MIT>, at your option.
+// This file may not be copied, modified, or distributed except according to those terms.
+
+//! Tests for the `tags` module, which parses `RESULT_TAGS`.
+
+use speedtest_statuspage::*;
+
+/// Well-formed tags are parsed, trimmed and keyed by sanitized label names.
+#[test]
+fn result_tags_parse_well_formed_entries() {
+    let parsed = tags::parse_tags(" location = home ,tier=fibre,site-id=7");
+    let expected: std::collections::BTreeMap<String, String> = [("location", "home"), ("tier", "fibre"), ("site_id", "7")]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    assert_eq!(parsed, expected);
+}
+
+/// Entries without `=` or without a usable key are skipped.
+#[test]
+fn result_tags_skip_malformed_entries() {
+    let parsed = tags::parse_tags("novalue,=orphan,-=dash,,ok=1");
+    assert_eq!(parsed.len(), 1);
+    assert_eq!(parsed["ok"], "1");
+}
+
+/// Label names are coerced into the Prometheus `[a-zA-Z_][a-zA-Z0-9_]*` form.
+#[test]
+fn result_tags_sanitize_label_names() {
+    assert_eq!(tags::sanitize_label_name("rack.position"), Some("rack_position".to_string()));
+    assert_eq!(tags::sanitize_label_name("9lives"), Some("_9lives".to_string()));
+    assert_eq!(tags::sanitize_label_name("__internal"), Some("_internal".to_string()));
+    assert_eq!(tags::sanitize_label_name("Ünïcode"), Some("_n_code".to_string()));
+    assert_eq!(tags::sanitize_label_name("valid_name"), Some("valid_name".to_string()));
+    assert_eq!(tags::sanitize_label_name("--"), None);
+}